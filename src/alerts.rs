@@ -0,0 +1,591 @@
+//! Threshold alerts (`[[alerts]]`).
+//!
+//! A rule watches one frame metric. Threshold rules fire when the metric
+//! reaches the threshold and re-arm once it drops back below; event rules
+//! fire when the event happens. Debounce state lives in the session cache,
+//! so a rule fires once per crossing across statusline invocations.
+//! Actions are a terminal bell or OSC 9 notification appended to the
+//! output, or a detached shell command.
+
+use std::{hash::Hash, process::Stdio};
+
+use crate::{
+    config::{AlertConfig, RenderConfig},
+    providers::custom::shell_command,
+    state::SessionState,
+    types::{AlertState, RenderFrame},
+};
+
+/// Burn rate is ignored until the session is this old: a few early requests
+/// extrapolate to a wild hourly rate.
+const BURN_RATE_MIN_DURATION_MS: u64 = 5 * 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertMetric {
+    /// Context window used, percent.
+    Context,
+    /// Session average cost per hour, USD.
+    BurnRate,
+    /// Highest of the 5-hour and 7-day quota windows, percent.
+    Quota,
+    /// Session cost, USD.
+    Cost,
+    /// A subagent finished.
+    AgentDone,
+    /// Every todo is complete.
+    TodosDone,
+}
+
+impl AlertMetric {
+    /// Names accepted by `metric`.
+    pub const NAMES: &'static [&'static str] = &[
+        "context",
+        "burn_rate",
+        "quota",
+        "cost",
+        "agent_done",
+        "todos_done",
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "context" => Some(Self::Context),
+            "burn_rate" => Some(Self::BurnRate),
+            "quota" => Some(Self::Quota),
+            "cost" => Some(Self::Cost),
+            "agent_done" => Some(Self::AgentDone),
+            "todos_done" => Some(Self::TodosDone),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Context => "context",
+            Self::BurnRate => "burn_rate",
+            Self::Quota => "quota",
+            Self::Cost => "cost",
+            Self::AgentDone => "agent_done",
+            Self::TodosDone => "todos_done",
+        }
+    }
+
+    /// Event metrics fire per occurrence and take no threshold.
+    pub fn is_event(self) -> bool {
+        matches!(self, Self::AgentDone | Self::TodosDone)
+    }
+
+    fn message_key(self) -> &'static str {
+        match self {
+            Self::Context => "alert.context",
+            Self::BurnRate => "alert.burn_rate",
+            Self::Quota => "alert.quota",
+            Self::Cost => "alert.cost",
+            Self::AgentDone => "alert.agent_done",
+            Self::TodosDone => "alert.todos_done",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AlertAction {
+    /// BEL (`\x07`).
+    Bell,
+    /// OSC 9 desktop notification carrying the alert message.
+    Notify,
+    /// Shell command, run detached.
+    Command(String),
+}
+
+/// A validated `[[alerts]]` rule.
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    /// Unused (0) for event metrics.
+    pub threshold: f64,
+    pub action: AlertAction,
+    pub message: Option<String>,
+}
+
+// The threshold is compared by bits so RenderConfig can stay `Eq + Hash`.
+impl PartialEq for AlertRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.metric == other.metric
+            && self.threshold.to_bits() == other.threshold.to_bits()
+            && self.action == other.action
+            && self.message == other.message
+    }
+}
+
+impl Eq for AlertRule {}
+
+impl Hash for AlertRule {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.metric.hash(state);
+        self.threshold.to_bits().hash(state);
+        self.action.hash(state);
+        self.message.hash(state);
+    }
+}
+
+impl AlertRule {
+    pub fn from_config(config: &AlertConfig) -> Result<Self, String> {
+        let metric = AlertMetric::parse(&config.metric).ok_or_else(|| {
+            format!(
+                "unknown metric {:?} (expected one of {})",
+                config.metric,
+                AlertMetric::NAMES.join(", ")
+            )
+        })?;
+        let threshold = match config.threshold {
+            _ if metric.is_event() => 0.0,
+            Some(threshold) if threshold.is_finite() => threshold,
+            _ => return Err(format!("metric {:?} needs a threshold", metric.name())),
+        };
+        let action = match config.action.to_ascii_lowercase().as_str() {
+            "bell" => AlertAction::Bell,
+            "notify" => AlertAction::Notify,
+            "command" => AlertAction::Command(
+                config
+                    .command
+                    .clone()
+                    .filter(|command| !command.trim().is_empty())
+                    .ok_or_else(|| "action \"command\" needs a command".to_string())?,
+            ),
+            other => {
+                return Err(format!(
+                    "unknown action {other:?} (expected bell, notify or command)"
+                ))
+            }
+        };
+        Ok(Self {
+            name: config.name.clone(),
+            metric,
+            threshold,
+            action,
+            message: config.message.clone(),
+        })
+    }
+
+    /// Identifies the rule in its `AlertState`; editing the rule re-arms it.
+    fn signature(&self) -> String {
+        if self.metric.is_event() {
+            self.metric.name().to_string()
+        } else {
+            format!("{}>={}", self.metric.name(), self.threshold)
+        }
+    }
+}
+
+/// An alert that fired on this render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiredAlert {
+    pub name: String,
+    pub metric: AlertMetric,
+    /// Plain value for commands: `82`, `12.50`, an agent description.
+    pub value: String,
+    pub message: String,
+    pub action: AlertAction,
+}
+
+/// What a rule's metric looks like in the current frame.
+enum Reading {
+    /// Threshold metric: raw value and its display text.
+    Level(f64, String),
+    /// `todos_done`: whether the condition holds, and display text.
+    Condition(bool, String),
+    /// `agent_done`: newest completion time and the agent's description.
+    Event(u64, String),
+    /// No data this render; the rule's state is left as is.
+    Missing,
+}
+
+/// Check every rule against `frame` and return the alerts that fire now.
+/// Updates the debounce state in `state.alerts`.
+pub fn evaluate_alerts(
+    state: &mut SessionState,
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    now_ms: u64,
+) -> Vec<FiredAlert> {
+    state
+        .alerts
+        .retain(|entry| config.alerts.iter().any(|rule| rule.name == entry.name));
+
+    let mut fired = Vec::new();
+    for rule in &config.alerts {
+        let reading = read_metric(rule, state, frame, config);
+        let (entry, created) = alert_entry_mut(&mut state.alerts, rule);
+
+        let hit = match reading {
+            Reading::Level(value, text) => {
+                let crossed = value >= rule.threshold;
+                let fire = crossed && !entry.active;
+                entry.active = crossed;
+                fire.then(|| (plain_number(value), text))
+            }
+            Reading::Condition(met, text) => {
+                let fire = met && !entry.active;
+                entry.active = met;
+                fire.then(|| (text.clone(), text))
+            }
+            Reading::Event(at, text) => {
+                // A new rule starts from the current state instead of replaying history
+                let fire = !created && entry.last_event_ms.map_or(true, |last| at > last);
+                entry.last_event_ms = Some(entry.last_event_ms.map_or(at, |last| last.max(at)));
+                fire.then(|| (text.clone(), text))
+            }
+            Reading::Missing => None,
+        };
+
+        if let Some((value, text)) = hit {
+            entry.fired_at_ms = Some(now_ms);
+            let message = match &rule.message {
+                Some(template) => template
+                    .replace("{name}", &rule.name)
+                    .replace("{value}", &text),
+                None => config
+                    .messages
+                    .format(rule.metric.message_key(), &[("value", &text)]),
+            };
+            fired.push(FiredAlert {
+                name: rule.name.clone(),
+                metric: rule.metric,
+                value,
+                message,
+                action: rule.action.clone(),
+            });
+        }
+    }
+    fired
+}
+
+fn read_metric(
+    rule: &AlertRule,
+    state: &SessionState,
+    frame: &RenderFrame,
+    config: &RenderConfig,
+) -> Reading {
+    let numbers = &config.number_format;
+    let line3 = &frame.line3;
+    match rule.metric {
+        AlertMetric::Context => match line3.context_used_percentage {
+            Some(pct) => Reading::Level(pct as f64, format!("{pct}%")),
+            None => Reading::Missing,
+        },
+        AlertMetric::Quota => {
+            let quota = &frame.quota;
+            match quota
+                .five_hour_pct
+                .into_iter()
+                .chain(quota.seven_day_pct)
+                .reduce(f64::max)
+            {
+                Some(pct) => Reading::Level(pct, format!("{pct:.0}%")),
+                None => Reading::Missing,
+            }
+        }
+        // Money thresholds are in the display currency, like the rendered cost
+        AlertMetric::Cost => match line3.total_cost_usd {
+            Some(cost) => Reading::Level(cost * numbers.currency_rate, numbers.money(cost)),
+            None => Reading::Missing,
+        },
+        AlertMetric::BurnRate => {
+            let duration = line3
+                .total_duration_ms
+                .filter(|duration| *duration >= BURN_RATE_MIN_DURATION_MS);
+            match (line3.total_cost_usd, duration) {
+                (Some(cost), Some(duration)) => {
+                    let per_hour = cost / (duration as f64 / 3_600_000.0);
+                    Reading::Level(per_hour * numbers.currency_rate, numbers.money(per_hour))
+                }
+                _ => Reading::Missing,
+            }
+        }
+        AlertMetric::AgentDone => state
+            .completed_agents
+            .iter()
+            .filter_map(|agent| Some((agent.completed_at?, agent)))
+            .max_by_key(|(at, _)| *at)
+            .map_or(Reading::Missing, |(at, agent)| {
+                Reading::Event(at, agent.description.clone())
+            }),
+        AlertMetric::TodosDone => match &frame.todo {
+            Some(todo) => Reading::Condition(
+                todo.all_done && todo.total > 0,
+                format!("{}/{}", todo.completed, todo.total),
+            ),
+            None => Reading::Condition(false, String::new()),
+        },
+    }
+}
+
+/// Find the state for a rule, resetting it when the rule changed. The flag
+/// is true when the state was (re)created.
+fn alert_entry_mut<'a>(
+    alerts: &'a mut Vec<AlertState>,
+    rule: &AlertRule,
+) -> (&'a mut AlertState, bool) {
+    let signature = rule.signature();
+    let position = match alerts.iter().position(|entry| entry.name == rule.name) {
+        Some(position) => position,
+        None => {
+            alerts.push(AlertState {
+                name: rule.name.clone(),
+                ..Default::default()
+            });
+            alerts.len() - 1
+        }
+    };
+
+    let entry = &mut alerts[position];
+    let created = entry.rule != signature;
+    if created {
+        *entry = AlertState {
+            name: rule.name.clone(),
+            rule: signature,
+            ..Default::default()
+        };
+    }
+    (entry, created)
+}
+
+/// `82` for whole numbers, `12.50` otherwise.
+fn plain_number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+/// BEL and OSC 9 sequences for the fired `bell` / `notify` alerts, to append
+/// to the output.
+pub fn terminal_sequences(fired: &[FiredAlert]) -> String {
+    fired
+        .iter()
+        .filter_map(|alert| match &alert.action {
+            AlertAction::Bell => Some("\x07".to_string()),
+            AlertAction::Notify => {
+                let text: String = alert.message.chars().filter(|c| !c.is_control()).collect();
+                Some(format!("\x1b]9;{text}\x07"))
+            }
+            AlertAction::Command(_) => None,
+        })
+        .collect()
+}
+
+/// Spawn the fired alerts' commands as detached children with the alert in
+/// `PULSELINE_ALERT*` env vars. Fire-and-forget — errors are silently ignored.
+pub fn spawn_alert_commands(fired: &[FiredAlert], cwd: Option<&str>) {
+    for alert in fired {
+        let AlertAction::Command(command) = &alert.action else {
+            continue;
+        };
+        let mut shell = shell_command(command);
+        if let Some(dir) = cwd.filter(|dir| std::path::Path::new(dir).is_dir()) {
+            shell.current_dir(dir);
+        }
+        let _ = shell
+            .env("PULSELINE_ALERT", &alert.name)
+            .env("PULSELINE_ALERT_METRIC", alert.metric.name())
+            .env("PULSELINE_ALERT_VALUE", &alert.value)
+            .env("PULSELINE_ALERT_MESSAGE", &alert.message)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgentSummary, TodoSummary};
+
+    fn rule(metric: &str, threshold: Option<f64>, action: &str) -> AlertConfig {
+        AlertConfig {
+            name: format!("{metric}-alert"),
+            metric: metric.to_string(),
+            threshold,
+            action: action.to_string(),
+            command: None,
+            message: None,
+        }
+    }
+
+    fn config_with(rules: &[AlertConfig]) -> RenderConfig {
+        RenderConfig {
+            alerts: rules
+                .iter()
+                .map(|rule| AlertRule::from_config(rule).unwrap())
+                .collect(),
+            ..RenderConfig::default()
+        }
+    }
+
+    fn frame_with_context(pct: u64) -> RenderFrame {
+        let mut frame = RenderFrame::default();
+        frame.line3.context_used_percentage = Some(pct);
+        frame
+    }
+
+    #[test]
+    fn from_config_validates_rules() {
+        assert!(AlertRule::from_config(&rule("context", Some(80.0), "bell")).is_ok());
+        assert!(AlertRule::from_config(&rule("agent_done", None, "notify")).is_ok());
+        assert_eq!(
+            AlertRule::from_config(&rule("tokens", Some(1.0), "bell")).unwrap_err(),
+            "unknown metric \"tokens\" (expected one of context, burn_rate, quota, cost, agent_done, todos_done)"
+        );
+        assert_eq!(
+            AlertRule::from_config(&rule("cost", None, "bell")).unwrap_err(),
+            "metric \"cost\" needs a threshold"
+        );
+        assert_eq!(
+            AlertRule::from_config(&rule("cost", Some(5.0), "command")).unwrap_err(),
+            "action \"command\" needs a command"
+        );
+        assert!(AlertRule::from_config(&rule("cost", Some(5.0), "email"))
+            .unwrap_err()
+            .starts_with("unknown action \"email\""));
+    }
+
+    #[test]
+    fn threshold_fires_once_per_crossing() {
+        let config = config_with(&[rule("context", Some(80.0), "bell")]);
+        let mut state = SessionState::default();
+        let mut fire_count =
+            |pct| evaluate_alerts(&mut state, &frame_with_context(pct), &config, 1).len();
+
+        assert_eq!(fire_count(50), 0);
+        assert_eq!(fire_count(81), 1);
+        assert_eq!(fire_count(90), 0, "still past the threshold");
+        assert_eq!(fire_count(30), 0, "dropping below re-arms");
+        assert_eq!(fire_count(80), 1);
+    }
+
+    #[test]
+    fn missing_metric_keeps_state_and_rule_change_rearms() {
+        let mut config = config_with(&[rule("context", Some(80.0), "bell")]);
+        let mut state = SessionState::default();
+        assert_eq!(
+            evaluate_alerts(&mut state, &frame_with_context(85), &config, 1).len(),
+            1
+        );
+        assert!(evaluate_alerts(&mut state, &RenderFrame::default(), &config, 2).is_empty());
+        assert!(evaluate_alerts(&mut state, &frame_with_context(85), &config, 3).is_empty());
+
+        config = config_with(&[rule("context", Some(70.0), "bell")]);
+        assert_eq!(
+            evaluate_alerts(&mut state, &frame_with_context(85), &config, 4).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn fired_alert_carries_value_and_message() {
+        let mut custom = rule("cost", Some(5.0), "notify");
+        custom.message = Some("{name} hit {value}".to_string());
+        let config = config_with(&[rule("quota", Some(90.0), "bell"), custom]);
+
+        let mut frame = RenderFrame::default();
+        frame.line3.total_cost_usd = Some(5.5);
+        frame.quota.five_hour_pct = Some(40.0);
+        frame.quota.seven_day_pct = Some(92.4);
+        let fired = evaluate_alerts(&mut SessionState::default(), &frame, &config, 1);
+
+        assert_eq!(fired.len(), 2);
+        assert_eq!(fired[0].value, "92.40");
+        assert_eq!(fired[0].message, "Quota at 92%");
+        assert_eq!(fired[1].value, "5.50");
+        assert_eq!(fired[1].message, "cost-alert hit $5.50");
+        assert_eq!(
+            terminal_sequences(&fired),
+            "\x07\x1b]9;cost-alert hit $5.50\x07"
+        );
+    }
+
+    #[test]
+    fn burn_rate_waits_for_a_few_minutes_of_session() {
+        let config = config_with(&[rule("burn_rate", Some(10.0), "bell")]);
+        let mut state = SessionState::default();
+        let mut frame = RenderFrame::default();
+        frame.line3.total_cost_usd = Some(2.0);
+        frame.line3.total_duration_ms = Some(60_000);
+        assert!(evaluate_alerts(&mut state, &frame, &config, 1).is_empty());
+
+        frame.line3.total_duration_ms = Some(6 * 60_000);
+        let fired = evaluate_alerts(&mut state, &frame, &config, 2);
+        assert_eq!(fired[0].message, "Burn rate $20.00/h");
+    }
+
+    #[test]
+    fn cost_threshold_is_in_the_display_currency() {
+        let mut config = config_with(&[rule("cost", Some(1000.0), "bell")]);
+        config.number_format.currency_symbol = "¥".to_string();
+        config.number_format.currency_rate = 150.0;
+        let mut state = SessionState::default();
+        let mut frame = RenderFrame::default();
+
+        // $5 = ¥750: below the ¥1000 budget
+        frame.line3.total_cost_usd = Some(5.0);
+        assert!(evaluate_alerts(&mut state, &frame, &config, 1).is_empty());
+
+        // $7 = ¥1050
+        frame.line3.total_cost_usd = Some(7.0);
+        let fired = evaluate_alerts(&mut state, &frame, &config, 2);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].value, "1050");
+    }
+
+    #[test]
+    fn agent_done_fires_for_new_completions_only() {
+        let config = config_with(&[rule("agent_done", None, "bell")]);
+        let mut state = SessionState::default();
+        let agent = |id: &str, at| AgentSummary {
+            id: id.to_string(),
+            description: format!("agent {id}"),
+            completed_at: Some(at),
+            ..Default::default()
+        };
+        state.completed_agents.push(agent("old", 100));
+        let frame = RenderFrame::default();
+        assert!(
+            evaluate_alerts(&mut state, &frame, &config, 1).is_empty(),
+            "history is not replayed"
+        );
+
+        state.completed_agents.push(agent("new", 200));
+        let fired = evaluate_alerts(&mut state, &frame, &config, 2);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].message, "Agent done: agent new");
+        assert!(evaluate_alerts(&mut state, &frame, &config, 3).is_empty());
+    }
+
+    #[test]
+    fn todos_done_rearms_when_new_todos_appear() {
+        let config = config_with(&[rule("todos_done", None, "bell")]);
+        let mut state = SessionState::default();
+        let mut frame = RenderFrame::default();
+        let todos = |completed, all_done| TodoSummary {
+            completed,
+            total: 3,
+            all_done,
+            ..Default::default()
+        };
+
+        frame.todo = Some(todos(2, false));
+        assert!(evaluate_alerts(&mut state, &frame, &config, 1).is_empty());
+        frame.todo = Some(todos(3, true));
+        assert_eq!(evaluate_alerts(&mut state, &frame, &config, 2).len(), 1);
+        assert!(evaluate_alerts(&mut state, &frame, &config, 3).is_empty());
+        frame.todo = Some(todos(1, false));
+        assert!(evaluate_alerts(&mut state, &frame, &config, 4).is_empty());
+        frame.todo = Some(todos(3, true));
+        assert_eq!(evaluate_alerts(&mut state, &frame, &config, 5).len(), 1);
+    }
+}
@@ -1,6 +1,11 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 
+use crate::alerts::AlertRule;
+use crate::messages::{self, Messages};
+use crate::providers::claude_dir::ClaudeConfigDir;
+use crate::render::{fmt::NumberFormat, terminal};
+
 // ── Pulseline Config (TOML file) ─────────────────────────────────────
 
 fn default_true() -> bool {
@@ -9,19 +14,69 @@ fn default_true() -> bool {
 fn default_dark() -> String {
     "dark".to_string()
 }
+fn default_style() -> String {
+    "plain".to_string()
+}
+fn default_bar() -> String {
+    "off".to_string()
+}
+fn default_decimal_separator() -> String {
+    ".".to_string()
+}
+
+fn default_numbers() -> String {
+    "compact".to_string()
+}
+
+fn default_currency_symbol() -> String {
+    "$".to_string()
+}
+
+fn default_currency_rate() -> f64 {
+    1.0
+}
+
+fn default_currency_position() -> String {
+    "before".to_string()
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+fn default_bar_width() -> usize {
+    10
+}
 fn default_max_lines() -> usize {
     2
 }
 fn default_max_completed() -> usize {
     4
 }
+fn default_custom_line() -> usize {
+    1
+}
+fn default_custom_ttl_secs() -> u64 {
+    30
+}
+fn default_custom_timeout_ms() -> u64 {
+    1000
+}
+fn default_alert_action() -> String {
+    "bell".to_string()
+}
 
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct PulselineConfig {
+    /// Claude Code config root when `CLAUDE_CONFIG_DIR` is not set.
+    #[serde(default)]
+    pub claude_config_dir: Option<String>,
     #[serde(default)]
     pub display: DisplayConfig,
     #[serde(default)]
     pub segments: SegmentsConfig,
+    #[serde(default)]
+    pub alerts: Vec<AlertConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -30,6 +85,62 @@ pub struct DisplayConfig {
     pub theme: String,
     #[serde(default = "default_true")]
     pub icons: bool,
+    #[serde(default = "default_style")]
+    pub style: String,
+    /// Fixed render width; detected from the terminal when unset.
+    #[serde(default)]
+    pub width: Option<usize>,
+    /// Columns subtracted from the detected width.
+    #[serde(default)]
+    pub width_offset: usize,
+    #[serde(default = "default_bar")]
+    pub bar: String,
+    #[serde(default = "default_bar_width")]
+    pub bar_width: usize,
+    #[serde(default)]
+    pub hyperlinks: bool,
+    /// Editor URL template for file links (`{path}`, `{line}`); `file://` when unset.
+    #[serde(default)]
+    pub editor_url: Option<String>,
+    /// Message catalog locale: `en`, `zh-TW` or `ja`.
+    #[serde(default = "default_locale")]
+    pub locale: String,
+    /// TOML file overriding individual catalog strings.
+    #[serde(default)]
+    pub messages: Option<String>,
+    #[serde(default = "default_decimal_separator")]
+    pub decimal_separator: String,
+    #[serde(default)]
+    pub thousands_separator: String,
+    /// `compact` (20.0k) or `full` (20,000) token counts.
+    #[serde(default = "default_numbers")]
+    pub numbers: String,
+    #[serde(default)]
+    pub currency: CurrencyConfig,
+}
+
+/// Display currency for cost, burn rate and cost-per-line. Costs arrive in USD
+/// and are multiplied by `rate`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CurrencyConfig {
+    #[serde(default = "default_currency_symbol")]
+    pub symbol: String,
+    /// Display units per USD.
+    #[serde(default = "default_currency_rate")]
+    pub rate: f64,
+    /// `before` ($1.50) or `after` (1,50 €).
+    #[serde(default = "default_currency_position")]
+    pub position: String,
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        Self {
+            symbol: default_currency_symbol(),
+            rate: default_currency_rate(),
+            position: default_currency_position(),
+        }
+    }
 }
 
 impl Default for DisplayConfig {
@@ -37,6 +148,19 @@ impl Default for DisplayConfig {
         Self {
             theme: default_dark(),
             icons: true,
+            style: default_style(),
+            width: None,
+            width_offset: 0,
+            bar: default_bar(),
+            bar_width: default_bar_width(),
+            hyperlinks: false,
+            editor_url: None,
+            locale: default_locale(),
+            messages: None,
+            decimal_separator: default_decimal_separator(),
+            thousands_separator: String::new(),
+            numbers: default_numbers(),
+            currency: CurrencyConfig::default(),
         }
     }
 }
@@ -57,6 +181,8 @@ pub struct SegmentsConfig {
     pub agents: SegmentToggle,
     #[serde(default)]
     pub todo: SegmentToggle,
+    #[serde(default)]
+    pub custom: Vec<CustomSegmentConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -73,6 +199,10 @@ pub struct IdentitySegmentConfig {
     pub show_git: bool,
     #[serde(default)]
     pub show_git_stats: bool,
+    #[serde(default = "default_true")]
+    pub show_workspace_dirs: bool,
+    #[serde(default = "default_true")]
+    pub show_phase: bool,
 }
 
 impl Default for IdentitySegmentConfig {
@@ -84,6 +214,8 @@ impl Default for IdentitySegmentConfig {
             show_project: true,
             show_git: true,
             show_git_stats: false,
+            show_workspace_dirs: true,
+            show_phase: true,
         }
     }
 }
@@ -130,6 +262,12 @@ pub struct BudgetSegmentConfig {
     pub show_cost: bool,
     #[serde(default)]
     pub show_speed: bool,
+    #[serde(default)]
+    pub show_lines_changed: bool,
+    #[serde(default)]
+    pub show_api_time: bool,
+    #[serde(default)]
+    pub show_sparklines: bool,
 }
 
 impl Default for BudgetSegmentConfig {
@@ -139,6 +277,9 @@ impl Default for BudgetSegmentConfig {
             show_tokens: true,
             show_cost: true,
             show_speed: false,
+            show_lines_changed: false,
+            show_api_time: false,
+            show_sparklines: false,
         }
     }
 }
@@ -151,6 +292,25 @@ pub struct QuotaSegmentConfig {
     pub show_five_hour: bool,
     #[serde(default)]
     pub show_seven_day: bool,
+    #[serde(default)]
+    pub absolute_reset: bool,
+    #[serde(default)]
+    pub show_projection: bool,
+    #[serde(default)]
+    pub show_model_buckets: bool,
+    #[serde(default)]
+    pub show_extra_usage: bool,
+    /// Usage API URL for the background fetch (a proxy or a local stand-in
+    /// server). Read from the user config only; `http://` is accepted for
+    /// loopback hosts only.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Credentials JSON file tried first (user config only).
+    #[serde(default)]
+    pub credentials_file: Option<String>,
+    /// Shell command printing the credentials JSON, tried next (user config only).
+    #[serde(default)]
+    pub credential_command: Option<String>,
 }
 
 impl Default for QuotaSegmentConfig {
@@ -159,6 +319,13 @@ impl Default for QuotaSegmentConfig {
             enabled: false,
             show_five_hour: true,
             show_seven_day: false,
+            absolute_reset: false,
+            show_projection: false,
+            show_model_buckets: false,
+            show_extra_usage: false,
+            endpoint: None,
+            credentials_file: None,
+            credential_command: None,
         }
     }
 }
@@ -200,6 +367,58 @@ impl Default for SegmentToggle {
     }
 }
 
+/// A `[[segments.custom]]` entry: a shell command whose first output line is
+/// shown on `line` (1-3). Output is cached for `ttl_secs` and refreshed in the
+/// background, so a slow command never delays rendering.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct CustomSegmentConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default = "default_custom_line")]
+    pub line: usize,
+    /// Named color (`blue`, `green`, `cyan`, `purple`, `amber`, `coral`, `red`,
+    /// `orange`, `teal`, `magenta`) or a 256-color index such as `"110"`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Text shown before the value, e.g. `"K8S:"`.
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default = "default_custom_ttl_secs")]
+    pub ttl_secs: u64,
+    #[serde(default = "default_custom_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// An `[[alerts]]` rule: runs `action` once each time `metric` crosses
+/// `threshold` (or, for event metrics, each time the event happens).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AlertConfig {
+    pub name: String,
+    /// `context`, `burn_rate`, `quota`, `cost`, `agent_done` or `todos_done`.
+    pub metric: String,
+    /// Trigger level: percent for `context`/`quota`; for `burn_rate` (per
+    /// hour) and `cost`, the display currency (`[display.currency]`, USD by
+    /// default). Unused by the event metrics.
+    #[serde(default)]
+    pub threshold: Option<f64>,
+    /// `bell`, `notify` (OSC 9 desktop notification) or `command`.
+    #[serde(default = "default_alert_action")]
+    pub action: String,
+    /// Shell command for `action = "command"`.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Notification text; `{name}` and `{value}` are filled in.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+impl AlertConfig {
+    /// Whether the rule runs a shell command, which only the user config may do.
+    pub fn runs_command(&self) -> bool {
+        self.action.eq_ignore_ascii_case("command")
+    }
+}
+
 /// Returns `~/.claude/pulseline/config.toml`
 pub fn config_path() -> PathBuf {
     let home = std::env::var("HOME")
@@ -225,9 +444,28 @@ pub fn load_config() -> PulselineConfig {
 
 /// Generate the default config file content.
 pub fn default_config_toml() -> &'static str {
-    r#"[display]
+    r#"# claude_config_dir = "~/.claude-work"  # Claude Code config root (default: $CLAUDE_CONFIG_DIR, then ~/.claude)
+
+[display]
 theme = "dark"          # dark | light
 icons = true            # nerd font icons vs ascii
+style = "plain"         # plain | powerline | powerline-round
+# width = 120           # fixed width (default: terminal width, then $COLUMNS)
+width_offset = 0        # columns to subtract from the detected width
+bar = "off"             # off | blocks | eighths | ascii (context + quota bars)
+bar_width = 10          # bar cells
+hyperlinks = false      # OSC 8 links on file targets, project path and branch
+# editor_url = "vscode://file{path}:{line}"  # file links (default: file://)
+locale = "en"           # en | zh-TW | ja (labels and messages)
+# messages = "messages.toml"  # override single strings (relative to this dir)
+numbers = "compact"     # compact (20.0k) | full (20,000) token counts
+decimal_separator = "."
+thousands_separator = ""  # e.g. "," or "." (full numbers and money)
+
+[display.currency]      # cost, burn rate and $/100L
+symbol = "$"
+rate = 1.0              # display units per USD
+position = "before"     # before ($1.50) | after (1,50 €)
 
 [segments.identity]     # Line 1 — model, style, version, project, git
 show_model = true
@@ -236,6 +474,8 @@ show_version = true
 show_project = true
 show_git = true
 show_git_stats = false  # !3 +1 ✘2 ?4 file stats after branch
+show_workspace_dirs = true  # +api:main* status for each added directory
+show_phase = true  # working / waiting for permission / idle for 4m, after the model
 
 [segments.config]       # Line 2 — CLAUDE.md, rules, memories, hooks, MCPs, skills, duration
 show_claude_md = true
@@ -251,11 +491,22 @@ show_context = true
 show_tokens = true
 show_cost = true
 show_speed = false          # output tok/s rate
+show_lines_changed = false  # +156 -23 lines edited, with $/100 lines rate
+show_api_time = false       # API time and its share of wall time
+show_sparklines = false     # ▁▂▄▇ recent trend for context, speed and cost
 
 [segments.quota]            # Usage/quota tracking (subscription plans)
 enabled = false             # opt-in: requires OAuth credentials
 show_five_hour = true
 show_seven_day = false
+absolute_reset = false      # "resets 14:30" / "Tue 09:00" instead of "resets 3h 12m"
+show_projection = false     # "limit in ~40m" / "on pace: 62% at reset" from the burn rate
+show_model_buckets = false  # model-specific weekly windows (Opus, Sonnet); the active model is highlighted
+show_extra_usage = false    # extra-usage credits: $12.34/$50.00, or "off"
+# endpoint = "http://127.0.0.1:8080/api/oauth/usage"  # usage API override (proxy, stand-in server; http:// for localhost only)
+# credentials_file = "~/.secrets/claude.json"   # tried first
+# credential_command = "pass show claude/oauth"  # stdout is the credentials JSON
+# then $CLAUDE_CODE_OAUTH_TOKEN, the macOS Keychain, .credentials.json in the config root
 
 [segments.tools]
 enabled = true
@@ -269,6 +520,25 @@ max_lines = 2
 [segments.todo]
 enabled = true
 max_lines = 2
+
+# Custom command segments (repeat the block for more)
+# [[segments.custom]]
+# name = "k8s"
+# command = "kubectl config current-context"
+# line = 1              # 1-3
+# color = "cyan"        # named color or 256-color index
+# label = "K8S:"
+# ttl_secs = 30         # cache output this long, then refresh in background
+# timeout_ms = 1000     # kill the command after this long
+
+# Alerts fire once each time a metric crosses its threshold (repeat for more)
+# [[alerts]]
+# name = "context-high"
+# metric = "context"    # context | burn_rate | quota | cost | agent_done | todos_done
+# threshold = 80        # % (context, quota); per hour (burn_rate) and total (cost) in the display currency
+# action = "notify"     # bell | notify (OSC 9) | command
+# command = "notify-send Claude \"$PULSELINE_ALERT_MESSAGE\""  # for action = "command"
+# message = "{name}: {value}"  # default: a per-metric message
 "#
 }
 
@@ -278,12 +548,29 @@ max_lines = 2
 pub struct ProjectOverrideConfig {
     pub display: Option<ProjectDisplayOverride>,
     pub segments: Option<ProjectSegmentsOverride>,
+    /// Appended to the user's alerts; a rule with the same name replaces it.
+    /// Only `bell` and `notify` rules apply: `command` rules are ignored.
+    pub alerts: Option<Vec<AlertConfig>>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct ProjectDisplayOverride {
     pub theme: Option<String>,
     pub icons: Option<bool>,
+    pub style: Option<String>,
+    pub width: Option<usize>,
+    pub width_offset: Option<usize>,
+    pub bar: Option<String>,
+    pub bar_width: Option<usize>,
+    pub hyperlinks: Option<bool>,
+    pub editor_url: Option<String>,
+    pub locale: Option<String>,
+    pub messages: Option<String>,
+    pub decimal_separator: Option<String>,
+    pub thousands_separator: Option<String>,
+    pub numbers: Option<String>,
+    /// Replaces the user `[display.currency]` table as a whole.
+    pub currency: Option<CurrencyConfig>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -295,6 +582,8 @@ pub struct ProjectSegmentsOverride {
     pub tools: Option<ProjectToolOverride>,
     pub agents: Option<ProjectSegmentToggleOverride>,
     pub todo: Option<ProjectSegmentToggleOverride>,
+    // No `custom`: a checked-out repository must not be able to run commands
+    // from the status line, so custom segments come from the user config only.
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -305,6 +594,8 @@ pub struct ProjectIdentityOverride {
     pub show_project: Option<bool>,
     pub show_git: Option<bool>,
     pub show_git_stats: Option<bool>,
+    pub show_workspace_dirs: Option<bool>,
+    pub show_phase: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -324,6 +615,9 @@ pub struct ProjectBudgetOverride {
     pub show_tokens: Option<bool>,
     pub show_cost: Option<bool>,
     pub show_speed: Option<bool>,
+    pub show_lines_changed: Option<bool>,
+    pub show_api_time: Option<bool>,
+    pub show_sparklines: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -331,6 +625,10 @@ pub struct ProjectQuotaOverride {
     pub enabled: Option<bool>,
     pub show_five_hour: Option<bool>,
     pub show_seven_day: Option<bool>,
+    pub absolute_reset: Option<bool>,
+    pub show_projection: Option<bool>,
+    pub show_model_buckets: Option<bool>,
+    pub show_extra_usage: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -381,6 +679,45 @@ pub fn merge_configs(
         if let Some(icons) = display.icons {
             user.display.icons = icons;
         }
+        if let Some(style) = &display.style {
+            user.display.style = style.clone();
+        }
+        if let Some(width) = display.width {
+            user.display.width = Some(width);
+        }
+        if let Some(offset) = display.width_offset {
+            user.display.width_offset = offset;
+        }
+        if let Some(v) = &display.bar {
+            user.display.bar = v.clone();
+        }
+        if let Some(v) = display.bar_width {
+            user.display.bar_width = v;
+        }
+        if let Some(v) = display.hyperlinks {
+            user.display.hyperlinks = v;
+        }
+        if let Some(template) = &display.editor_url {
+            user.display.editor_url = Some(template.clone());
+        }
+        if let Some(locale) = &display.locale {
+            user.display.locale = locale.clone();
+        }
+        if let Some(path) = &display.messages {
+            user.display.messages = Some(path.clone());
+        }
+        if let Some(separator) = &display.decimal_separator {
+            user.display.decimal_separator = separator.clone();
+        }
+        if let Some(separator) = &display.thousands_separator {
+            user.display.thousands_separator = separator.clone();
+        }
+        if let Some(numbers) = &display.numbers {
+            user.display.numbers = numbers.clone();
+        }
+        if let Some(currency) = &display.currency {
+            user.display.currency = currency.clone();
+        }
     }
 
     // Segment overrides
@@ -404,6 +741,12 @@ pub fn merge_configs(
             if let Some(v) = identity.show_git_stats {
                 user.segments.identity.show_git_stats = v;
             }
+            if let Some(v) = identity.show_workspace_dirs {
+                user.segments.identity.show_workspace_dirs = v;
+            }
+            if let Some(v) = identity.show_phase {
+                user.segments.identity.show_phase = v;
+            }
         }
         if let Some(config) = &segments.config {
             if let Some(v) = config.show_claude_md {
@@ -441,6 +784,15 @@ pub fn merge_configs(
             if let Some(v) = budget.show_speed {
                 user.segments.budget.show_speed = v;
             }
+            if let Some(v) = budget.show_lines_changed {
+                user.segments.budget.show_lines_changed = v;
+            }
+            if let Some(v) = budget.show_api_time {
+                user.segments.budget.show_api_time = v;
+            }
+            if let Some(v) = budget.show_sparklines {
+                user.segments.budget.show_sparklines = v;
+            }
         }
         if let Some(quota) = &segments.quota {
             if let Some(v) = quota.enabled {
@@ -452,6 +804,18 @@ pub fn merge_configs(
             if let Some(v) = quota.show_seven_day {
                 user.segments.quota.show_seven_day = v;
             }
+            if let Some(v) = quota.absolute_reset {
+                user.segments.quota.absolute_reset = v;
+            }
+            if let Some(v) = quota.show_projection {
+                user.segments.quota.show_projection = v;
+            }
+            if let Some(v) = quota.show_model_buckets {
+                user.segments.quota.show_model_buckets = v;
+            }
+            if let Some(v) = quota.show_extra_usage {
+                user.segments.quota.show_extra_usage = v;
+            }
         }
         if let Some(tools) = &segments.tools {
             if let Some(v) = tools.enabled {
@@ -482,9 +846,26 @@ pub fn merge_configs(
         }
     }
 
+    if let Some(alerts) = &project.alerts {
+        for rule in alerts.iter().filter(|rule| !rule.runs_command()) {
+            user.alerts.retain(|existing| existing.name != rule.name);
+            user.alerts.push(rule.clone());
+        }
+    }
+
     user
 }
 
+/// The Claude config root for env counts, credentials and the quota cache:
+/// `CLAUDE_CONFIG_DIR`, then `claude_config_dir`, then `~/.claude`.
+pub fn claude_config_dir(config: &PulselineConfig) -> Option<ClaudeConfigDir> {
+    let configured = config
+        .claude_config_dir
+        .as_deref()
+        .map(config_relative_path);
+    ClaudeConfigDir::resolve(configured.as_deref())
+}
+
 /// Load user config, then merge project overrides if available.
 pub fn load_merged_config(project_root: Option<&str>) -> PulselineConfig {
     let user_config = load_config();
@@ -514,13 +895,76 @@ pub fn check_configs(project_root: Option<&str>) -> Vec<(PathBuf, String)> {
         let project_path = project_config_path(root);
         if project_path.exists() {
             if let Ok(contents) = std::fs::read_to_string(&project_path) {
-                if let Err(err) = toml::from_str::<ProjectOverrideConfig>(&contents) {
-                    errors.push((project_path, err.to_string()));
+                match toml::from_str::<toml::Table>(&contents) {
+                    Ok(table) => {
+                        if table
+                            .get("segments")
+                            .and_then(|segments| segments.get("custom"))
+                            .is_some()
+                        {
+                            errors.push((
+                                project_path.clone(),
+                                "[[segments.custom]] is ignored in project configs; \
+                                 define custom segments in the user config"
+                                    .to_string(),
+                            ));
+                        }
+                        match toml::from_str::<ProjectOverrideConfig>(&contents) {
+                            Ok(project) => {
+                                for rule in project.alerts.iter().flatten() {
+                                    if rule.runs_command() {
+                                        errors.push((
+                                            project_path.clone(),
+                                            format!(
+                                                "alert {:?}: action \"command\" is ignored in \
+                                                 project configs",
+                                                rule.name
+                                            ),
+                                        ));
+                                    }
+                                }
+                            }
+                            Err(err) => errors.push((project_path, err.to_string())),
+                        }
+                    }
+                    Err(err) => errors.push((project_path, err.to_string())),
                 }
             }
         }
     }
 
+    let merged = load_merged_config(project_root);
+    for alert in &merged.alerts {
+        if let Err(err) = AlertRule::from_config(alert) {
+            errors.push((config_path(), format!("alert {:?}: {err}", alert.name)));
+        }
+    }
+
+    let display = merged.display;
+    if !messages::is_supported_locale(&display.locale) {
+        errors.push((
+            config_path(),
+            format!(
+                "unknown locale {:?} (expected one of {})",
+                display.locale,
+                messages::LOCALES.join(", ")
+            ),
+        ));
+    }
+    if let Some(path) = &display.messages {
+        let path = config_relative_path(path);
+        match messages::load_catalog(&path) {
+            Ok((_, unknown)) if !unknown.is_empty() => {
+                errors.push((
+                    path,
+                    format!("unknown message keys: {}", unknown.join(", ")),
+                ));
+            }
+            Ok(_) => {}
+            Err(err) => errors.push((path, err)),
+        }
+    }
+
     errors
 }
 
@@ -532,6 +976,14 @@ pub fn default_project_config_toml() -> &'static str {
 
 # [display]
 # theme = "light"
+# style = "powerline"
+# bar = "blocks"
+# width_offset = 4
+# locale = "ja"
+# [display.currency]   # replaces the user currency table
+# symbol = "€"
+# rate = 0.92
+# position = "after"
 
 # [segments.identity]
 # show_version = false
@@ -544,11 +996,17 @@ pub fn default_project_config_toml() -> &'static str {
 # [segments.budget]
 # show_tokens = false
 # show_speed = true
+# show_lines_changed = true
+# show_api_time = true
 
 # [segments.quota]
 # enabled = true
 # show_five_hour = true
 # show_seven_day = false
+# absolute_reset = false
+# show_projection = false
+# show_model_buckets = false
+# show_extra_usage = false
 
 # [segments.tools]
 # enabled = true
@@ -562,6 +1020,13 @@ pub fn default_project_config_toml() -> &'static str {
 # [segments.todo]
 # enabled = true
 # max_lines = 2
+
+# Project alerts may ring the bell or notify; command alerts need the user config.
+# [[alerts]]
+# name = "context"
+# metric = "context"
+# threshold = 90
+# action = "notify"
 "#
 }
 
@@ -579,10 +1044,42 @@ pub enum ColorTheme {
     Light,
 }
 
+/// Glyph set for context and quota progress bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarStyle {
+    /// `▰▰▰▱▱`
+    Blocks,
+    /// `███▍░` — eighth-block partials for sub-cell precision.
+    Eighths,
+    /// `[###--]`
+    Ascii,
+}
+
+/// How L1-L3 segments are joined.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineStyle {
+    /// Foreground colors with ` | ` separators.
+    Plain,
+    /// Segment backgrounds joined by arrow glyphs.
+    Powerline,
+    /// Segment backgrounds joined by rounded glyphs.
+    PowerlineRound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WidthDegradeStrategy {
+    /// Drop the quota, tool, agent and todo lines below L3 that are too wide;
+    /// the ones that fit stay.
     DropActivityLinesFirst,
+    /// Join L2 segments with a space instead of ` | `.
     CompressLine2,
+    /// Drop whole L1-L3 segments, lowest `StyledSpan::priority` first.
+    #[serde(alias = "compress_core_lines")]
+    DropLowPrioritySegments,
+    /// Former name of `DropLowPrioritySegments`, applied the same way.
+    #[deprecated(note = "use WidthDegradeStrategy::DropLowPrioritySegments")]
+    #[serde(skip)]
     CompressCoreLines,
 }
 
@@ -591,6 +1088,18 @@ pub struct RenderConfig {
     pub glyph_mode: GlyphMode,
     pub color_enabled: bool,
     pub color_theme: ColorTheme,
+    pub line_style: LineStyle,
+    /// Progress bar style for context and quota; None renders percentages only.
+    pub bar_style: Option<BarStyle>,
+    pub bar_width: usize,
+    /// Wrap file targets, the project path and the branch in OSC 8 links.
+    pub hyperlinks: bool,
+    /// Editor URL template for file links; `file://` URLs when None.
+    pub editor_url: Option<String>,
+    /// Labels and messages in the configured locale.
+    pub messages: Messages,
+    /// Separators, compact numbers and display currency.
+    pub number_format: NumberFormat,
     // L1 segment toggles
     pub show_model: bool,
     pub show_style: bool,
@@ -598,6 +1107,8 @@ pub struct RenderConfig {
     pub show_project: bool,
     pub show_git: bool,
     pub show_git_stats: bool,
+    pub show_workspace_dirs: bool,
+    pub show_phase: bool,
     // L2 segment toggles
     pub show_claude_md: bool,
     pub show_rules: bool,
@@ -611,10 +1122,17 @@ pub struct RenderConfig {
     pub show_tokens: bool,
     pub show_cost: bool,
     pub show_speed: bool,
+    pub show_lines_changed: bool,
+    pub show_api_time: bool,
+    pub show_sparklines: bool,
     // Quota segment toggles
     pub show_quota: bool,
     pub show_quota_five_hour: bool,
     pub show_quota_seven_day: bool,
+    pub quota_absolute_reset: bool,
+    pub show_quota_projection: bool,
+    pub show_quota_model_buckets: bool,
+    pub show_quota_extra_usage: bool,
     // Activity segment toggles + limits
     pub max_tool_lines: usize,
     pub max_completed_tools: usize,
@@ -627,6 +1145,9 @@ pub struct RenderConfig {
     pub transcript_poll_throttle_ms: u64,
     pub terminal_width: Option<usize>,
     pub degrade_order: Vec<WidthDegradeStrategy>,
+    pub custom_segments: Vec<CustomSegmentConfig>,
+    /// Valid `[[alerts]]` rules; invalid ones are reported by `--check`.
+    pub alerts: Vec<AlertRule>,
 }
 
 impl Default for RenderConfig {
@@ -635,12 +1156,21 @@ impl Default for RenderConfig {
             glyph_mode: GlyphMode::Ascii,
             color_enabled: false,
             color_theme: ColorTheme::Dark,
+            line_style: LineStyle::Plain,
+            bar_style: None,
+            bar_width: 10,
+            hyperlinks: false,
+            editor_url: None,
+            messages: Messages::default(),
+            number_format: NumberFormat::default(),
             show_model: true,
             show_style: true,
             show_version: true,
             show_project: true,
             show_git: true,
             show_git_stats: false,
+            show_workspace_dirs: true,
+            show_phase: true,
             show_claude_md: true,
             show_rules: true,
             show_memory: true,
@@ -652,9 +1182,16 @@ impl Default for RenderConfig {
             show_tokens: true,
             show_cost: true,
             show_speed: false,
+            show_lines_changed: false,
+            show_api_time: false,
+            show_sparklines: false,
             show_quota: false,
             show_quota_five_hour: true,
             show_quota_seven_day: false,
+            quota_absolute_reset: false,
+            show_quota_projection: false,
+            show_quota_model_buckets: false,
+            show_quota_extra_usage: false,
             max_tool_lines: 2,
             max_completed_tools: 4,
             max_agent_lines: 2,
@@ -666,16 +1203,62 @@ impl Default for RenderConfig {
             transcript_poll_throttle_ms: 250,
             terminal_width: None,
             degrade_order: vec![
-                WidthDegradeStrategy::DropActivityLinesFirst,
                 WidthDegradeStrategy::CompressLine2,
-                WidthDegradeStrategy::CompressCoreLines,
+                WidthDegradeStrategy::DropLowPrioritySegments,
+                WidthDegradeStrategy::DropActivityLinesFirst,
             ],
+            custom_segments: Vec::new(),
+            alerts: Vec::new(),
         }
     }
 }
 
-/// Build a RenderConfig from PulselineConfig + environment overrides.
+/// Resolve a path from the user config (`claude_config_dir`, `display.messages`,
+/// `segments.quota.credentials_file`): `~/` is the home directory, relative
+/// paths are relative to the user config directory.
+pub fn config_relative_path(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .unwrap_or_else(|_| ".".to_string());
+        return PathBuf::from(home).join(rest);
+    }
+    let path = PathBuf::from(path);
+    match config_path().parent() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
+
+/// The catalog for `display.locale` with `display.messages` overrides applied.
+/// An unreadable catalog file is reported and ignored.
+fn load_messages(display: &DisplayConfig) -> Messages {
+    let messages = Messages::for_locale(&display.locale);
+    let Some(path) = &display.messages else {
+        return messages;
+    };
+    let path = config_relative_path(path);
+    match messages::load_catalog(&path) {
+        Ok((overrides, _)) => messages.with_overrides(overrides),
+        Err(err) => {
+            eprintln!("warning: invalid messages {}: {err}", path.display());
+            messages
+        }
+    }
+}
+
+/// Build a RenderConfig from PulselineConfig + environment overrides, with
+/// the width of the terminal this process runs in (`terminal::detect_width`).
 pub fn build_render_config(pulseline: &PulselineConfig) -> RenderConfig {
+    build_render_config_with_width(pulseline, terminal::detect_width())
+}
+
+/// Like [`build_render_config`], but with the terminal width supplied by the
+/// caller (None for no terminal); `display.width` still wins over it.
+pub fn build_render_config_with_width(
+    pulseline: &PulselineConfig,
+    detected_width: Option<usize>,
+) -> RenderConfig {
     let color_enabled = std::env::var("NO_COLOR").is_err();
 
     let glyph_mode = if pulseline.display.icons {
@@ -689,12 +1272,51 @@ pub fn build_render_config(pulseline: &PulselineConfig) -> RenderConfig {
         _ => ColorTheme::Dark,
     };
 
-    let terminal_width = std::env::var("COLUMNS").ok().and_then(|v| v.parse().ok());
+    let line_style = match pulseline.display.style.to_lowercase().as_str() {
+        "powerline" => LineStyle::Powerline,
+        "powerline-round" => LineStyle::PowerlineRound,
+        _ => LineStyle::Plain,
+    };
+
+    let bar_style = match pulseline.display.bar.to_lowercase().as_str() {
+        "blocks" => Some(BarStyle::Blocks),
+        "eighths" => Some(BarStyle::Eighths),
+        "ascii" => Some(BarStyle::Ascii),
+        _ => None,
+    }
+    .filter(|_| pulseline.display.bar_width > 0);
+
+    let display = &pulseline.display;
+    let number_format = NumberFormat {
+        decimal_separator: display.decimal_separator.clone(),
+        thousands_separator: display.thousands_separator.clone(),
+        compact: !display.numbers.eq_ignore_ascii_case("full"),
+        currency_symbol: display.currency.symbol.clone(),
+        currency_rate: if display.currency.rate.is_finite() && display.currency.rate > 0.0 {
+            display.currency.rate
+        } else {
+            1.0
+        },
+        symbol_after: display.currency.position.eq_ignore_ascii_case("after"),
+    };
+
+    let terminal_width = terminal::resolve_width(
+        pulseline.display.width,
+        pulseline.display.width_offset,
+        detected_width,
+    );
 
     RenderConfig {
         color_enabled,
         color_theme,
         glyph_mode,
+        line_style,
+        bar_style,
+        bar_width: pulseline.display.bar_width,
+        hyperlinks: pulseline.display.hyperlinks,
+        editor_url: pulseline.display.editor_url.clone(),
+        messages: load_messages(&pulseline.display),
+        number_format,
         terminal_width,
         // L1 identity toggles
         show_model: pulseline.segments.identity.show_model,
@@ -703,6 +1325,8 @@ pub fn build_render_config(pulseline: &PulselineConfig) -> RenderConfig {
         show_project: pulseline.segments.identity.show_project,
         show_git: pulseline.segments.identity.show_git,
         show_git_stats: pulseline.segments.identity.show_git_stats,
+        show_workspace_dirs: pulseline.segments.identity.show_workspace_dirs,
+        show_phase: pulseline.segments.identity.show_phase,
         // L2 config toggles
         show_claude_md: pulseline.segments.config.show_claude_md,
         show_rules: pulseline.segments.config.show_rules,
@@ -716,10 +1340,17 @@ pub fn build_render_config(pulseline: &PulselineConfig) -> RenderConfig {
         show_tokens: pulseline.segments.budget.show_tokens,
         show_cost: pulseline.segments.budget.show_cost,
         show_speed: pulseline.segments.budget.show_speed,
+        show_lines_changed: pulseline.segments.budget.show_lines_changed,
+        show_api_time: pulseline.segments.budget.show_api_time,
+        show_sparklines: pulseline.segments.budget.show_sparklines,
         // Quota
         show_quota: pulseline.segments.quota.enabled,
         show_quota_five_hour: pulseline.segments.quota.show_five_hour,
         show_quota_seven_day: pulseline.segments.quota.show_seven_day,
+        quota_absolute_reset: pulseline.segments.quota.absolute_reset,
+        show_quota_projection: pulseline.segments.quota.show_projection,
+        show_quota_model_buckets: pulseline.segments.quota.show_model_buckets,
+        show_quota_extra_usage: pulseline.segments.quota.show_extra_usage,
         // Activity
         max_tool_lines: pulseline.segments.tools.max_lines,
         max_completed_tools: pulseline.segments.tools.max_completed,
@@ -728,6 +1359,12 @@ pub fn build_render_config(pulseline: &PulselineConfig) -> RenderConfig {
         show_tools: pulseline.segments.tools.enabled,
         show_agents: pulseline.segments.agents.enabled,
         show_todo: pulseline.segments.todo.enabled,
+        custom_segments: pulseline.segments.custom.clone(),
+        alerts: pulseline
+            .alerts
+            .iter()
+            .filter_map(|alert| AlertRule::from_config(alert).ok())
+            .collect(),
         ..RenderConfig::default()
     }
 }
@@ -1,4 +1,6 @@
+pub mod alerts;
 pub mod config;
+pub mod messages;
 pub mod providers;
 pub mod render;
 pub mod state;
@@ -9,24 +11,115 @@ use std::collections::HashMap;
 use config::RenderConfig;
 use providers::{
     quota::{CachedFileQuotaCollector, QuotaCollector},
-    EnvCollector, EnvSnapshot, FileSystemEnvCollector, FileTranscriptCollector, GitCollector,
-    GitSnapshot, LocalGitCollector, TranscriptCollector, TranscriptSnapshot,
+    ClaudeConfigDir, EnvCollector, EnvSnapshot, FileSystemEnvCollector, FileTranscriptCollector,
+    GitCollector, GitSnapshot, LocalGitCollector, TranscriptCollector, TranscriptSnapshot,
 };
-use state::cache;
+use render::segment::SegmentRegistry;
+use state::cache::{self, CacheStore, FileCacheStore};
 use state::SessionState;
-use types::{RenderFrame, StdinPayload};
+use types::{HookPayload, RenderFrame, StdinPayload, WorkspaceDirStatus};
 
-#[derive(Debug, Default)]
+/// Renders statusline frames, keeping per-session state between calls.
+///
+/// `PulseLineRunner::default()` uses the filesystem/git/transcript collectors,
+/// the quota cache file, a temp-dir session cache and the built-in segment
+/// registry. Embedders can swap any of them with the `with_*` builder methods.
 pub struct PulseLineRunner {
     sessions: HashMap<String, SessionState>,
-    env_collector: FileSystemEnvCollector,
-    git_collector: LocalGitCollector,
-    transcript_collector: FileTranscriptCollector,
+    /// Injected env collector; `default_env` (home and config root set by
+    /// the builders) when None.
+    env_collector: Option<Box<dyn EnvCollector>>,
+    default_env: FileSystemEnvCollector,
+    git_collector: Box<dyn GitCollector>,
+    transcript_collector: Box<dyn TranscriptCollector>,
+    /// Injected quota collector; `default_quota` when None.
+    quota_collector: Option<Box<dyn QuotaCollector>>,
+    default_quota: CachedFileQuotaCollector,
+    cache_store: Option<Box<dyn CacheStore>>,
+    segments: SegmentRegistry,
+}
+
+impl Default for PulseLineRunner {
+    fn default() -> Self {
+        Self {
+            sessions: HashMap::new(),
+            env_collector: None,
+            default_env: FileSystemEnvCollector::default(),
+            git_collector: Box::new(LocalGitCollector),
+            transcript_collector: Box::new(FileTranscriptCollector),
+            quota_collector: None,
+            default_quota: CachedFileQuotaCollector::default(),
+            cache_store: Some(Box::new(FileCacheStore::default())),
+            segments: SegmentRegistry::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for PulseLineRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PulseLineRunner")
+            .field("sessions", &self.sessions.len())
+            .field("persistent", &self.cache_store.is_some())
+            .field("segments", &self.segments)
+            .finish_non_exhaustive()
+    }
 }
 
 impl PulseLineRunner {
+    /// Scan a different home directory with the default env collector.
+    /// No effect on a collector set with `with_env_collector`.
     pub fn with_user_home(mut self, home: std::path::PathBuf) -> Self {
-        self.env_collector.user_home_override = Some(home);
+        self.default_env.user_home_override = Some(home);
+        self
+    }
+
+    /// Read Claude Code's files from `claude_dir` (one account's
+    /// `CLAUDE_CONFIG_DIR`): env counts and the quota cache. Applies to the
+    /// default env and quota collectors, whatever the builder order.
+    pub fn with_claude_config_dir(mut self, claude_dir: ClaudeConfigDir) -> Self {
+        self.default_env.claude_dir = Some(claude_dir.clone());
+        self.default_quota.claude_dir = Some(claude_dir);
+        self
+    }
+
+    pub fn with_env_collector(mut self, collector: impl EnvCollector + 'static) -> Self {
+        self.env_collector = Some(Box::new(collector));
+        self
+    }
+
+    pub fn with_git_collector(mut self, collector: impl GitCollector + 'static) -> Self {
+        self.git_collector = Box::new(collector);
+        self
+    }
+
+    pub fn with_transcript_collector(
+        mut self,
+        collector: impl TranscriptCollector + 'static,
+    ) -> Self {
+        self.transcript_collector = Box::new(collector);
+        self
+    }
+
+    pub fn with_quota_collector(mut self, collector: impl QuotaCollector + 'static) -> Self {
+        self.quota_collector = Some(Box::new(collector));
+        self
+    }
+
+    /// Persist session state through `store` instead of the temp dir.
+    pub fn with_cache_store(mut self, store: impl CacheStore + 'static) -> Self {
+        self.cache_store = Some(Box::new(store));
+        self
+    }
+
+    /// Keep session state in memory only; nothing is read from or written to disk.
+    pub fn without_cache_store(mut self) -> Self {
+        self.cache_store = None;
+        self
+    }
+
+    /// Render L1-L3 from `segments` instead of the built-in registry.
+    pub fn with_segments(mut self, segments: SegmentRegistry) -> Self {
+        self.segments = segments;
         self
     }
 
@@ -49,11 +142,10 @@ impl PulseLineRunner {
         let is_fresh = !self.sessions.contains_key(&session_key);
         let state = self.sessions.entry(session_key.clone()).or_default();
 
-        // Load disk cache on first encounter of this session
-        if is_fresh {
-            if let Some(disk_cache) = cache::load_cache(&session_key) {
-                state.load_from_cache(disk_cache);
-            }
+        // Held until the session is saved, so a `--hook` run cannot save in between
+        let _lock = self.cache_store.as_ref().and_then(|s| s.lock(&session_key));
+        if let Some(store) = &self.cache_store {
+            load_session(store.as_ref(), &session_key, state, is_fresh);
         }
 
         let transcript_snapshot = self
@@ -63,12 +155,42 @@ impl PulseLineRunner {
         let project_path = payload
             .resolve_project_path()
             .unwrap_or_else(|| "unknown".to_string());
-        let env_snapshot = collect_env_snapshot(&self.env_collector, state, &project_path);
-        let git_snapshot = collect_git_snapshot(&self.git_collector, state, &project_path);
+        let env_collector = self.env_collector.as_deref().unwrap_or(&self.default_env);
+        let env_snapshot = collect_env_snapshot(env_collector, state, &project_path);
+        let git_snapshot = collect_git_snapshot(self.git_collector.as_ref(), state, &project_path);
 
         let mut frame =
             build_render_frame(payload, &env_snapshot, &git_snapshot, transcript_snapshot);
 
+        if config.show_workspace_dirs {
+            frame.line1.extra_dirs = payload
+                .added_dirs()
+                .iter()
+                .map(|dir| collect_workspace_dir_status(self.git_collector.as_ref(), state, dir))
+                .collect();
+        }
+
+        if config.show_phase {
+            frame.line1.session_phase = state.session_phase(cache::now_epoch_ms());
+        }
+
+        // Custom commands hand results back through the store's directory;
+        // without one (memory-only runners) they are not run at all
+        let result_dir = self
+            .cache_store
+            .as_ref()
+            .and_then(|store| store.result_dir())
+            .filter(|_| !config.custom_segments.is_empty());
+        if let Some(result_dir) = result_dir {
+            frame.custom = providers::custom::collect_custom_segments(
+                state,
+                &session_key,
+                &config.custom_segments,
+                result_dir,
+                payload.resolve_project_root().as_deref(),
+            );
+        }
+
         // All-or-nothing L3 cache: if payload has no L3 data at all, use cached;
         // otherwise trust the payload entirely (no field-by-field merge).
         if frame.line3.has_data() {
@@ -87,23 +209,106 @@ impl PulseLineRunner {
             frame.line3.output_speed_toks_per_sec = state.update_output_speed(output_tokens);
         }
 
-        // Quota: single cache file read (no network I/O in render path)
+        // Sparklines: sample L3 into the session's rolling history
+        if config.show_sparklines {
+            state.record_history(&frame.line3, cache::now_epoch_ms());
+            frame.history = state.history.clone();
+        }
+
+        // Quota: collector reads cached data only (no network I/O in render path)
         if config.show_quota {
-            let (snapshot, is_stale) = CachedFileQuotaCollector.collect_quota();
+            let quota_collector = self
+                .quota_collector
+                .as_deref()
+                .unwrap_or(&self.default_quota);
+            let (snapshot, is_stale) = quota_collector.collect_quota();
             frame.quota = types::QuotaMetrics::from_snapshot(&snapshot, cache::now_epoch_ms());
+            if config.quota_absolute_reset {
+                frame.quota = frame
+                    .quota
+                    .with_local_resets(&snapshot, &providers::timezone::TimeZone::local());
+            }
 
             if is_stale && state.should_spawn_quota_fetch(providers::quota::QUOTA_FAILURE_TTL_MS) {
-                providers::quota::spawn_background_fetch();
+                quota_collector.refresh_quota();
             }
         }
 
-        let lines = render::layout::render_frame(&frame, &config);
+        // Alerts: debounce against the session state; actions run after rendering
+        let fired = if config.alerts.is_empty() {
+            Vec::new()
+        } else {
+            alerts::evaluate_alerts(state, &frame, &config, cache::now_epoch_ms())
+        };
+
+        let mut lines = render::layout::render_frame_with(&frame, &config, &self.segments);
 
-        // Save cache to disk
-        cache::save_cache(&session_key, &state.to_cache());
+        if !fired.is_empty() {
+            alerts::spawn_alert_commands(&fired, payload.resolve_project_root().as_deref());
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&alerts::terminal_sequences(&fired));
+            }
+        }
+
+        if let Some(store) = &self.cache_store {
+            save_session(store.as_ref(), &session_key, state);
+        }
 
         Ok(lines)
     }
+
+    /// Apply a Claude Code hook event (`--hook` mode) to the session cache the
+    /// statusline renders from.
+    pub fn ingest_hook_from_str(&mut self, input: &str) -> Result<(), String> {
+        let hook: HookPayload =
+            serde_json::from_str(input).map_err(|error| format!("invalid hook JSON: {error}"))?;
+        self.ingest_hook(&hook);
+        Ok(())
+    }
+
+    pub fn ingest_hook(&mut self, hook: &HookPayload) {
+        let session_key = session_key(&hook.session_payload());
+        let is_fresh = !self.sessions.contains_key(&session_key);
+        let state = self.sessions.entry(session_key.clone()).or_default();
+
+        let _lock = self.cache_store.as_ref().and_then(|s| s.lock(&session_key));
+        if let Some(store) = &self.cache_store {
+            load_session(store.as_ref(), &session_key, state, is_fresh);
+        }
+
+        providers::hook::apply_hook_event(state, hook);
+        if let Some(store) = &self.cache_store {
+            save_session(store.as_ref(), &session_key, state);
+        }
+    }
+}
+
+/// Load the disk cache on first encounter of a session, or again when another
+/// process (a `--hook` run, another render) saved it since this runner did.
+fn load_session(
+    store: &dyn CacheStore,
+    session_key: &str,
+    state: &mut SessionState,
+    is_fresh: bool,
+) {
+    if let Some(disk_cache) = store.load(session_key) {
+        if is_fresh || disk_cache.revision != state.cache_revision {
+            state.load_from_cache(disk_cache);
+        }
+    }
+}
+
+/// Save the session cache under the next revision. A save that slipped in
+/// since loading (its writer gave up waiting for the lock) is merged first.
+fn save_session(store: &dyn CacheStore, session_key: &str, state: &mut SessionState) {
+    if let Some(disk_cache) = store.load(session_key) {
+        if disk_cache.revision != state.cache_revision {
+            state.cache_revision = state.cache_revision.max(disk_cache.revision);
+            state.merge_concurrent(disk_cache);
+        }
+    }
+    state.cache_revision += 1;
+    store.save(session_key, &state.to_cache());
 }
 
 pub fn run_from_str(input: &str, config: RenderConfig) -> Result<Vec<String>, String> {
@@ -111,7 +316,7 @@ pub fn run_from_str(input: &str, config: RenderConfig) -> Result<Vec<String>, St
 }
 
 fn collect_env_snapshot(
-    collector: &FileSystemEnvCollector,
+    collector: &dyn EnvCollector,
     state: &mut SessionState,
     project_path: &str,
 ) -> EnvSnapshot {
@@ -130,7 +335,7 @@ fn collect_env_snapshot(
 }
 
 fn collect_git_snapshot(
-    collector: &LocalGitCollector,
+    collector: &dyn GitCollector,
     state: &mut SessionState,
     project_path: &str,
 ) -> GitSnapshot {
@@ -148,6 +353,34 @@ fn collect_git_snapshot(
     snapshot
 }
 
+fn collect_workspace_dir_status(
+    collector: &dyn GitCollector,
+    state: &mut SessionState,
+    dir: &str,
+) -> WorkspaceDirStatus {
+    let snapshot = match state.cached_workspace_git_for(dir) {
+        Some(snapshot) => snapshot,
+        None => {
+            let snapshot = collector.collect_git(dir);
+            state.set_cached_workspace_git(dir.to_string(), snapshot.clone());
+            snapshot
+        }
+    };
+
+    let name = std::path::Path::new(dir.trim_end_matches('/'))
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(dir)
+        .to_string();
+    let branch = (snapshot.branch != "unknown").then_some(snapshot.branch);
+
+    WorkspaceDirStatus {
+        name,
+        branch,
+        dirty: snapshot.dirty,
+    }
+}
+
 fn build_render_frame(
     payload: &StdinPayload,
     env_snapshot: &EnvSnapshot,
@@ -164,6 +397,7 @@ fn build_render_frame(
     frame.line1.git_added = git_snapshot.added_count;
     frame.line1.git_deleted = git_snapshot.deleted_count;
     frame.line1.git_untracked = git_snapshot.untracked_count;
+    frame.line1.git_remote_url = git_snapshot.remote_url.clone();
 
     frame.line2.claude_md_count = env_snapshot.claude_md_count;
     frame.line2.rules_count = env_snapshot.rules_count;
@@ -2,9 +2,11 @@ use std::io::{self, Read};
 
 use cc_pulseline::{
     config::{
-        build_render_config, check_configs, config_path, default_config_toml,
-        default_project_config_toml, load_merged_config, project_config_path,
+        build_render_config_with_width, check_configs, claude_config_dir, config_path,
+        default_config_toml, default_project_config_toml, load_merged_config, project_config_path,
     },
+    providers::quota_fetch::{check_credentials, CredentialOptions},
+    render::terminal,
     types::StdinPayload,
     PulseLineRunner,
 };
@@ -29,6 +31,18 @@ fn main() {
         return;
     }
 
+    // Hidden internal flag — background custom segment command
+    if let Some(pos) = args.iter().position(|a| a == "--run-custom") {
+        cc_pulseline::providers::custom::run_custom_command(&args[pos + 1..]);
+        return;
+    }
+
+    // Hook mode: never block Claude Code — errors go to stderr, exit stays 0
+    if args.iter().any(|a| a == "--hook") {
+        run_hook();
+        return;
+    }
+
     let has_init = args.iter().any(|a| a == "--init");
     let has_project = args.iter().any(|a| a == "--project");
     let has_check = args.iter().any(|a| a == "--check");
@@ -77,11 +91,15 @@ fn main() {
         }
     };
 
-    let project_root = payload.resolve_project_path();
+    let project_root = payload.resolve_project_root();
     let pulseline_config = load_merged_config(project_root.as_deref());
-    let render_config = build_render_config(&pulseline_config);
+    let render_config = build_render_config_with_width(&pulseline_config, terminal::detect_width());
 
-    let lines = match PulseLineRunner::default().run_from_payload(&payload, render_config) {
+    let mut runner = PulseLineRunner::default();
+    if let Some(claude_dir) = claude_config_dir(&pulseline_config) {
+        runner = runner.with_claude_config_dir(claude_dir);
+    }
+    let lines = match runner.run_from_payload(&payload, render_config) {
         Ok(lines) => lines,
         Err(err) => {
             eprintln!("{err}");
@@ -92,6 +110,17 @@ fn main() {
     println!("{}", lines.join("\n"));
 }
 
+fn run_hook() {
+    let mut input = String::new();
+    if let Err(err) = io::stdin().read_to_string(&mut input) {
+        eprintln!("failed to read stdin: {err}");
+        return;
+    }
+    if let Err(err) = PulseLineRunner::default().ingest_hook_from_str(&input) {
+        eprintln!("{err}");
+    }
+}
+
 fn init_config() {
     write_init_file(&config_path(), default_config_toml());
 }
@@ -149,6 +178,30 @@ fn check_config(project_root: Option<&str>) {
         }
     }
 
+    // Files referenced from the config (e.g. display.messages)
+    for (path, err) in errors.iter().filter(|(p, _)| !paths_to_check.contains(p)) {
+        eprintln!("FAIL {}: {err}", path.display());
+    }
+
+    // Claude config root and the quota fetch's credential chain: problems
+    // only hide counts or the quota line, so they do not fail the check
+    let config = load_merged_config(project_root);
+    let claude_dir = claude_config_dir(&config);
+    match &claude_dir {
+        Some(dir) if dir.dir.is_dir() => println!("OK   claude config dir: {}", dir.dir.display()),
+        Some(dir) => eprintln!("WARN claude config dir not found: {}", dir.dir.display()),
+        None => eprintln!("WARN claude config dir: cannot determine HOME"),
+    }
+
+    let quota = config.segments.quota;
+    if quota.enabled {
+        let options = CredentialOptions::from_config(&quota, claude_dir);
+        match check_credentials(&options) {
+            Ok(source) => println!("OK   quota credentials: {source}"),
+            Err(err) => eprintln!("WARN quota credentials: {err}"),
+        }
+    }
+
     if !errors.is_empty() {
         std::process::exit(1);
     }
@@ -156,9 +209,39 @@ fn check_config(project_root: Option<&str>) {
 
 fn print_config(project_root: Option<&str>) {
     let config = load_merged_config(project_root);
+    if let Some(dir) = &config.claude_config_dir {
+        println!("claude_config_dir = {dir:?}");
+        println!();
+    }
     println!("[display]");
     println!("theme = {:?}", config.display.theme);
     println!("icons = {}", config.display.icons);
+    println!("style = {:?}", config.display.style);
+    if let Some(width) = config.display.width {
+        println!("width = {width}");
+    }
+    println!("width_offset = {}", config.display.width_offset);
+    println!("bar = {:?}", config.display.bar);
+    println!("bar_width = {}", config.display.bar_width);
+    println!("hyperlinks = {}", config.display.hyperlinks);
+    if let Some(template) = &config.display.editor_url {
+        println!("editor_url = {template:?}");
+    }
+    println!("locale = {:?}", config.display.locale);
+    if let Some(path) = &config.display.messages {
+        println!("messages = {path:?}");
+    }
+    println!("numbers = {:?}", config.display.numbers);
+    println!("decimal_separator = {:?}", config.display.decimal_separator);
+    println!(
+        "thousands_separator = {:?}",
+        config.display.thousands_separator
+    );
+    println!();
+    println!("[display.currency]");
+    println!("symbol = {:?}", config.display.currency.symbol);
+    println!("rate = {}", config.display.currency.rate);
+    println!("position = {:?}", config.display.currency.position);
     println!();
     println!("[segments.identity]");
     println!("show_model = {}", config.segments.identity.show_model);
@@ -170,6 +253,11 @@ fn print_config(project_root: Option<&str>) {
         "show_git_stats = {}",
         config.segments.identity.show_git_stats
     );
+    println!(
+        "show_workspace_dirs = {}",
+        config.segments.identity.show_workspace_dirs
+    );
+    println!("show_phase = {}", config.segments.identity.show_phase);
     println!();
     println!("[segments.config]");
     println!("show_claude_md = {}", config.segments.config.show_claude_md);
@@ -185,11 +273,42 @@ fn print_config(project_root: Option<&str>) {
     println!("show_tokens = {}", config.segments.budget.show_tokens);
     println!("show_cost = {}", config.segments.budget.show_cost);
     println!("show_speed = {}", config.segments.budget.show_speed);
+    println!(
+        "show_lines_changed = {}",
+        config.segments.budget.show_lines_changed
+    );
+    println!("show_api_time = {}", config.segments.budget.show_api_time);
+    println!(
+        "show_sparklines = {}",
+        config.segments.budget.show_sparklines
+    );
     println!();
     println!("[segments.quota]");
     println!("enabled = {}", config.segments.quota.enabled);
     println!("show_five_hour = {}", config.segments.quota.show_five_hour);
     println!("show_seven_day = {}", config.segments.quota.show_seven_day);
+    println!("absolute_reset = {}", config.segments.quota.absolute_reset);
+    println!(
+        "show_projection = {}",
+        config.segments.quota.show_projection
+    );
+    println!(
+        "show_model_buckets = {}",
+        config.segments.quota.show_model_buckets
+    );
+    println!(
+        "show_extra_usage = {}",
+        config.segments.quota.show_extra_usage
+    );
+    if let Some(endpoint) = &config.segments.quota.endpoint {
+        println!("endpoint = {endpoint:?}");
+    }
+    if let Some(path) = &config.segments.quota.credentials_file {
+        println!("credentials_file = {path:?}");
+    }
+    if let Some(command) = &config.segments.quota.credential_command {
+        println!("credential_command = {command:?}");
+    }
     println!();
     println!("[segments.tools]");
     println!("enabled = {}", config.segments.tools.enabled);
@@ -203,6 +322,38 @@ fn print_config(project_root: Option<&str>) {
     println!("[segments.todo]");
     println!("enabled = {}", config.segments.todo.enabled);
     println!("max_lines = {}", config.segments.todo.max_lines);
+    for custom in &config.segments.custom {
+        println!();
+        println!("[[segments.custom]]");
+        println!("name = {:?}", custom.name);
+        println!("command = {:?}", custom.command);
+        println!("line = {}", custom.line);
+        if let Some(color) = &custom.color {
+            println!("color = {color:?}");
+        }
+        if let Some(label) = &custom.label {
+            println!("label = {label:?}");
+        }
+        println!("ttl_secs = {}", custom.ttl_secs);
+        println!("timeout_ms = {}", custom.timeout_ms);
+    }
+
+    for alert in &config.alerts {
+        println!();
+        println!("[[alerts]]");
+        println!("name = {:?}", alert.name);
+        println!("metric = {:?}", alert.metric);
+        if let Some(threshold) = alert.threshold {
+            println!("threshold = {threshold}");
+        }
+        println!("action = {:?}", alert.action);
+        if let Some(command) = &alert.command {
+            println!("command = {command:?}");
+        }
+        if let Some(message) = &alert.message {
+            println!("message = {message:?}");
+        }
+    }
 }
 
 fn print_help() {
@@ -220,10 +371,13 @@ OPTIONS:
     --init --project Create project config (.claude/pulseline.toml)
     --check          Validate config files
     --print          Show effective merged config
+    --hook           Ingest a Claude Code hook event from stdin
 
 RUNTIME:
     Reads Claude Code statusline JSON from stdin, outputs formatted lines.
     Empty stdin defaults to {{}}.
+    With --hook, reads hook JSON (PreToolUse, PostToolUse, SubagentStop,
+    Notification, Stop) and updates the session cache; prints nothing.
 
 CONFIG FILES:
     User:    ~/.claude/pulseline/config.toml
@@ -231,6 +385,6 @@ CONFIG FILES:
 
 ENVIRONMENT:
     NO_COLOR    Disable color output
-    COLUMNS     Terminal width for layout degradation"
+    COLUMNS     Width fallback when no controlling terminal is found"
     );
 }
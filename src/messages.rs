@@ -0,0 +1,303 @@
+//! Message catalog for user-visible labels and messages.
+//!
+//! Each locale is a table of `key → template`; `{name}` placeholders are
+//! filled by [`Messages::format`]. Locales missing a key fall back to English,
+//! and a user catalog file (`display.messages`) can override single strings.
+
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+/// English catalog: the reference set of keys.
+const EN: &[(&str, &str)] = &[
+    ("phase.working", "working"),
+    ("phase.waiting_permission", "waiting for permission"),
+    ("phase.idle", "idle for {duration}"),
+    ("config.claude_md", "CLAUDE.md"),
+    ("config.rules", "rules"),
+    ("config.memories", "memories"),
+    ("config.hooks", "hooks"),
+    ("config.mcps", "MCPs"),
+    ("config.skills", "skills"),
+    ("quota.resets", "resets {duration}"),
+    ("quota.resets_at", "resets {time}"),
+    ("quota.limit_reached", "Limit reached"),
+    ("quota.limit_in", "limit in ~{duration}"),
+    ("quota.on_pace", "on pace: {pct} at reset"),
+    ("quota.extra_usage", "Extra"),
+    ("quota.extra_usage_off", "off"),
+    ("quota.stale", "(stale)"),
+    ("alert.context", "Context at {value}"),
+    ("alert.burn_rate", "Burn rate {value}/h"),
+    ("alert.quota", "Quota at {value}"),
+    ("alert.cost", "Session cost reached {value}"),
+    ("alert.agent_done", "Agent done: {value}"),
+    ("alert.todos_done", "All todos complete"),
+    ("agent.done", "[done]"),
+    ("todo.all_complete", "All todos complete"),
+    (
+        "todo.progress",
+        "{completed}/{total} done, {pending} pending",
+    ),
+    ("todo.tasks", "{total} tasks"),
+    ("todo.active", "{count} active"),
+    ("weekday.sun", "Sun"),
+    ("weekday.mon", "Mon"),
+    ("weekday.tue", "Tue"),
+    ("weekday.wed", "Wed"),
+    ("weekday.thu", "Thu"),
+    ("weekday.fri", "Fri"),
+    ("weekday.sat", "Sat"),
+];
+
+/// Traditional Chinese (`zh-TW`).
+const ZH_TW: &[(&str, &str)] = &[
+    ("phase.working", "工作中"),
+    ("phase.waiting_permission", "等待授權"),
+    ("phase.idle", "閒置 {duration}"),
+    ("config.rules", "規則"),
+    ("config.memories", "記憶"),
+    ("config.hooks", "鉤子"),
+    ("config.mcps", "MCP"),
+    ("config.skills", "技能"),
+    ("quota.resets", "{duration} 後重置"),
+    ("quota.resets_at", "{time} 重置"),
+    ("quota.limit_reached", "已達上限"),
+    ("quota.limit_in", "約 {duration} 後達上限"),
+    ("quota.on_pace", "照目前速度，重置時 {pct}"),
+    ("quota.extra_usage", "額外用量"),
+    ("quota.extra_usage_off", "關閉"),
+    ("quota.stale", "(舊資料)"),
+    ("alert.context", "上下文已達 {value}"),
+    ("alert.burn_rate", "消耗速度 {value}/h"),
+    ("alert.quota", "配額已達 {value}"),
+    ("alert.cost", "工作階段費用已達 {value}"),
+    ("alert.agent_done", "代理完成：{value}"),
+    ("alert.todos_done", "待辦事項全部完成"),
+    ("agent.done", "[完成]"),
+    ("todo.all_complete", "待辦事項全部完成"),
+    (
+        "todo.progress",
+        "已完成 {completed}/{total}，待辦 {pending}",
+    ),
+    ("todo.tasks", "{total} 項任務"),
+    ("todo.active", "{count} 項進行中"),
+    ("weekday.sun", "週日"),
+    ("weekday.mon", "週一"),
+    ("weekday.tue", "週二"),
+    ("weekday.wed", "週三"),
+    ("weekday.thu", "週四"),
+    ("weekday.fri", "週五"),
+    ("weekday.sat", "週六"),
+];
+
+/// Japanese (`ja`).
+const JA: &[(&str, &str)] = &[
+    ("phase.working", "作業中"),
+    ("phase.waiting_permission", "許可待ち"),
+    ("phase.idle", "{duration} アイドル"),
+    ("config.rules", "ルール"),
+    ("config.memories", "メモリ"),
+    ("config.hooks", "フック"),
+    ("config.mcps", "MCP"),
+    ("config.skills", "スキル"),
+    ("quota.resets", "{duration} 後にリセット"),
+    ("quota.resets_at", "{time} にリセット"),
+    ("quota.limit_reached", "上限に到達"),
+    ("quota.limit_in", "約 {duration} で上限"),
+    ("quota.on_pace", "現ペースでリセット時 {pct}"),
+    ("quota.extra_usage", "追加使用"),
+    ("quota.extra_usage_off", "オフ"),
+    ("quota.stale", "(古いデータ)"),
+    ("alert.context", "コンテキスト {value}"),
+    ("alert.burn_rate", "消費ペース {value}/h"),
+    ("alert.quota", "クォータ {value}"),
+    ("alert.cost", "セッション費用が {value} に到達"),
+    ("alert.agent_done", "エージェント完了: {value}"),
+    ("alert.todos_done", "すべての TODO が完了"),
+    ("agent.done", "[完了]"),
+    ("todo.all_complete", "すべての TODO が完了"),
+    ("todo.progress", "{completed}/{total} 完了、残り {pending}"),
+    ("todo.tasks", "{total} 件のタスク"),
+    ("todo.active", "{count} 件実行中"),
+    ("weekday.sun", "日"),
+    ("weekday.mon", "月"),
+    ("weekday.tue", "火"),
+    ("weekday.wed", "水"),
+    ("weekday.thu", "木"),
+    ("weekday.fri", "金"),
+    ("weekday.sat", "土"),
+];
+
+/// Locales with a shipped catalog, as accepted by `display.locale`.
+pub const LOCALES: &[&str] = &["en", "zh-TW", "ja"];
+
+/// Whether `locale` selects a shipped catalog.
+pub fn is_supported_locale(locale: &str) -> bool {
+    translations(locale).is_some()
+}
+
+/// Translated entries for `locale`: empty for English, None when unknown.
+fn translations(locale: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    match locale.to_ascii_lowercase().replace('_', "-").as_str() {
+        "en" | "en-us" | "en-gb" => Some(&[]),
+        "zh-tw" | "zh-hant" | "zh-hk" => Some(ZH_TW),
+        "ja" | "ja-jp" => Some(JA),
+        _ => None,
+    }
+}
+
+/// Resolved message catalog for one locale plus user overrides.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Messages {
+    table: BTreeMap<String, String>,
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self::for_locale("en")
+    }
+}
+
+impl Messages {
+    /// Catalog for `locale` (`zh-TW`, `zh_tw`, `ja-JP`, ...). Unknown locales
+    /// and keys a locale does not translate use English.
+    pub fn for_locale(locale: &str) -> Self {
+        let mut table: BTreeMap<String, String> = EN
+            .iter()
+            .map(|&(key, text)| (key.to_string(), text.to_string()))
+            .collect();
+        for &(key, text) in translations(locale).unwrap_or_default() {
+            table.insert(key.to_string(), text.to_string());
+        }
+        Self { table }
+    }
+
+    /// Replace individual strings. Keys the catalog does not define are ignored.
+    pub fn with_overrides(mut self, overrides: BTreeMap<String, String>) -> Self {
+        for (key, text) in overrides {
+            if let Some(entry) = self.table.get_mut(&key) {
+                *entry = text;
+            }
+        }
+        self
+    }
+
+    /// Template for `key`; the key itself when the catalog has no such entry.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        debug_assert!(self.table.contains_key(key), "unknown message key {key}");
+        self.table.get(key).map_or(key, String::as_str)
+    }
+
+    /// Fill the `{name}` placeholders of `key`'s template.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter()
+            .fold(self.get(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), &value.to_string())
+            })
+    }
+}
+
+/// Read a user catalog: a TOML file of `key = "text"` entries, where tables
+/// nest keys (`[quota] resets = "..."` is `quota.resets`). Returns the keys
+/// that are not in the catalog alongside the parsed strings.
+pub fn load_catalog(path: &Path) -> Result<(BTreeMap<String, String>, Vec<String>), String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+    let table: toml::Table = toml::from_str(&contents).map_err(|error| error.to_string())?;
+
+    let mut strings = BTreeMap::new();
+    flatten(&table, "", &mut strings)?;
+    let unknown = strings
+        .keys()
+        .filter(|key| !EN.iter().any(|(known, _)| known == key))
+        .cloned()
+        .collect();
+    Ok((strings, unknown))
+}
+
+fn flatten(
+    table: &toml::Table,
+    prefix: &str,
+    out: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            toml::Value::String(text) => {
+                out.insert(key, text.clone());
+            }
+            toml::Value::Table(nested) => flatten(nested, &key, out)?,
+            _ => return Err(format!("{key}: expected a string")),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_catalogs_only_use_english_keys() {
+        for catalog in [ZH_TW, JA] {
+            for (key, _) in catalog {
+                assert!(EN.iter().any(|(known, _)| known == key), "{key}");
+            }
+        }
+    }
+
+    #[test]
+    fn locale_lookup_falls_back_to_english() {
+        let zh = Messages::for_locale("zh_TW");
+        assert_eq!(zh.get("quota.limit_reached"), "已達上限");
+        assert_eq!(zh.get("config.claude_md"), "CLAUDE.md");
+        assert_eq!(
+            Messages::for_locale("fr").get("quota.limit_reached"),
+            "Limit reached"
+        );
+        assert!(is_supported_locale("ja-JP"));
+        assert!(!is_supported_locale("fr"));
+    }
+
+    #[test]
+    fn format_fills_placeholders() {
+        let text = Messages::default().format(
+            "todo.progress",
+            &[("completed", &1), ("total", &3), ("pending", &2)],
+        );
+        assert_eq!(text, "1/3 done, 2 pending");
+    }
+
+    #[test]
+    fn overrides_replace_known_keys_only() {
+        let overrides = BTreeMap::from([
+            ("agent.done".to_string(), "[ok]".to_string()),
+            ("nope".to_string(), "x".to_string()),
+        ]);
+        let messages = Messages::for_locale("ja").with_overrides(overrides);
+        assert_eq!(messages.get("agent.done"), "[ok]");
+        assert_eq!(messages.get("todo.tasks"), "{total} 件のタスク");
+        assert!(!messages.table.contains_key("nope"));
+    }
+
+    #[test]
+    fn load_catalog_flattens_tables() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("messages.toml");
+        std::fs::write(
+            &path,
+            "\"agent.done\" = \"[fin]\"\n[quota]\nresets = \"reset in {duration}\"\ntypo = \"x\"\n",
+        )
+        .unwrap();
+        let (strings, unknown) = load_catalog(&path).unwrap();
+        assert_eq!(strings["agent.done"], "[fin]");
+        assert_eq!(strings["quota.resets"], "reset in {duration}");
+        assert_eq!(unknown, vec!["quota.typo".to_string()]);
+
+        std::fs::write(&path, "[quota]\nresets = 3\n").unwrap();
+        assert!(load_catalog(&path).unwrap_err().contains("quota.resets"));
+    }
+}
@@ -0,0 +1,96 @@
+//! Claude Code's config root.
+//!
+//! Settings, rules, skills, plugins, project memory and credentials live in
+//! `~/.claude`, or in `$CLAUDE_CONFIG_DIR` when set (one directory per
+//! account). The global state file `.claude.json` sits in the home directory
+//! for the default root and inside the config root otherwise.
+
+use std::path::{Path, PathBuf};
+
+/// Env var Claude Code reads its config root from.
+pub const CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClaudeConfigDir {
+    /// The config root (`~/.claude` by default).
+    pub dir: PathBuf,
+    /// `.claude.json`: user-scope MCP servers and other global state.
+    pub state_file: PathBuf,
+}
+
+impl ClaudeConfigDir {
+    /// The default layout: `~/.claude` plus `~/.claude.json`.
+    pub fn under_home(home: &Path) -> Self {
+        Self {
+            dir: home.join(".claude"),
+            state_file: home.join(".claude.json"),
+        }
+    }
+
+    /// A custom root, as set by `CLAUDE_CONFIG_DIR`.
+    pub fn custom(dir: PathBuf) -> Self {
+        Self {
+            state_file: dir.join(".claude.json"),
+            dir,
+        }
+    }
+
+    /// `$CLAUDE_CONFIG_DIR` if set, then `configured` (pulseline's
+    /// `claude_config_dir`), then `~/.claude`. The env var wins because it is
+    /// what the running Claude Code uses. None without any home directory.
+    pub fn resolve(configured: Option<&Path>) -> Option<Self> {
+        if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+            return Some(Self::custom(PathBuf::from(dir)));
+        }
+        if let Some(dir) = configured {
+            return Some(Self::custom(dir.to_path_buf()));
+        }
+        std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .ok()
+            .map(|home| Self::under_home(Path::new(&home)))
+    }
+
+    /// `path` inside the config root.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.dir.join(path)
+    }
+
+    /// Claude Code's plaintext credentials file.
+    pub fn credentials_file(&self) -> PathBuf {
+        self.join(".credentials.json")
+    }
+
+    /// Whether this root was set explicitly rather than being `~/.claude`.
+    pub fn is_custom(&self) -> bool {
+        self.state_file.parent() == Some(self.dir.as_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_and_custom_layouts() {
+        let home = Path::new("/home/u");
+        let default = ClaudeConfigDir::under_home(home);
+        assert_eq!(
+            default.join("settings.json"),
+            home.join(".claude/settings.json")
+        );
+        assert_eq!(default.state_file, home.join(".claude.json"));
+        assert!(!default.is_custom());
+
+        let work = ClaudeConfigDir::custom(PathBuf::from("/home/u/.claude-work"));
+        assert_eq!(
+            work.credentials_file(),
+            Path::new("/home/u/.claude-work/.credentials.json")
+        );
+        assert_eq!(
+            work.state_file,
+            Path::new("/home/u/.claude-work/.claude.json")
+        );
+        assert!(work.is_custom());
+    }
+}
@@ -0,0 +1,396 @@
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::CustomSegmentConfig,
+    render::color::strip_ansi,
+    state::{cache, SessionState},
+    types::{CustomSegmentEntry, CustomSegmentValue},
+};
+
+/// Longest value kept from a command's output.
+const MAX_VALUE_CHARS: usize = 60;
+/// Slack past `timeout_ms` before a refresh that never reported back is retried.
+const REFRESH_GRACE_MS: u64 = 2_000;
+
+/// Result handed from the `--run-custom` subprocess back to the render path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomResultFile {
+    pub command: String,
+    pub finished_at_ms: u64,
+    pub value: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Result file path in `dir` for one custom segment of one session.
+pub fn custom_result_path(dir: &Path, session_key: &str, name: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    session_key.hash(&mut hasher);
+    name.hash(&mut hasher);
+    let hash = hasher.finish();
+    dir.join(format!("cc-pulseline-custom-{hash:x}.json"))
+}
+
+/// Resolve custom segment values from the session cache without blocking.
+///
+/// Finished background runs (result files in `result_dir`, the cache store's
+/// directory) are folded into the cache first; stale or missing entries then
+/// get a background refresh while the last known value (if any) is rendered.
+pub fn collect_custom_segments(
+    state: &mut SessionState,
+    session_key: &str,
+    segments: &[CustomSegmentConfig],
+    result_dir: &Path,
+    cwd: Option<&str>,
+) -> Vec<CustomSegmentValue> {
+    let now = cache::now_epoch_ms();
+    state
+        .custom_segments
+        .retain(|entry| segments.iter().any(|s| s.name == entry.name));
+
+    let mut values = Vec::new();
+    for segment in segments {
+        let result_path = custom_result_path(result_dir, session_key, &segment.name);
+        let result = take_result(&result_path);
+        let entry = custom_entry_mut(state, segment);
+
+        if let Some(result) = result.filter(|r| r.command == segment.command) {
+            if result.error.is_none() {
+                entry.value = result.value;
+            }
+            // Failures keep the stale value and wait a full TTL before retrying
+            entry.fetched_at_ms = Some(result.finished_at_ms);
+            entry.refresh_spawned_ms = None;
+        }
+
+        let ttl_ms = segment.ttl_secs.saturating_mul(1000);
+        let is_stale = entry
+            .fetched_at_ms
+            .map_or(true, |at| now.saturating_sub(at) >= ttl_ms);
+        let is_refreshing = entry
+            .refresh_spawned_ms
+            .is_some_and(|at| now.saturating_sub(at) < segment.timeout_ms + REFRESH_GRACE_MS);
+        if is_stale && !is_refreshing {
+            entry.refresh_spawned_ms = Some(now);
+            spawn_background_refresh(&result_path, segment, cwd);
+        }
+
+        if let Some(text) = &entry.value {
+            values.push(CustomSegmentValue {
+                name: segment.name.clone(),
+                text: text.clone(),
+            });
+        }
+    }
+    values
+}
+
+/// Find the cache entry for a segment, resetting it when the command changed.
+fn custom_entry_mut<'a>(
+    state: &'a mut SessionState,
+    segment: &CustomSegmentConfig,
+) -> &'a mut CustomSegmentEntry {
+    let position = match state
+        .custom_segments
+        .iter()
+        .position(|entry| entry.name == segment.name)
+    {
+        Some(position) => position,
+        None => {
+            state.custom_segments.push(CustomSegmentEntry {
+                name: segment.name.clone(),
+                ..Default::default()
+            });
+            state.custom_segments.len() - 1
+        }
+    };
+
+    let entry = &mut state.custom_segments[position];
+    if entry.command != segment.command {
+        *entry = CustomSegmentEntry {
+            name: segment.name.clone(),
+            command: segment.command.clone(),
+            ..Default::default()
+        };
+    }
+    entry
+}
+
+/// Read and remove a finished result file. Returns None if absent or invalid.
+fn take_result(path: &Path) -> Option<CustomResultFile> {
+    let contents = fs::read_to_string(path).ok()?;
+    let _ = fs::remove_file(path);
+    serde_json::from_str(&contents).ok()
+}
+
+/// Spawn `--run-custom` as a detached child process.
+/// Fire-and-forget — errors are silently ignored.
+fn spawn_background_refresh(result_path: &Path, segment: &CustomSegmentConfig, cwd: Option<&str>) {
+    let exe = match std::env::current_exe() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    let _ = Command::new(exe)
+        .arg("--run-custom")
+        .arg(result_path)
+        .arg(segment.timeout_ms.to_string())
+        .arg(cwd.unwrap_or_default())
+        .arg(&segment.command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+/// Entry point for the hidden `--run-custom <result_path> <timeout_ms> <cwd> <command>` flag.
+pub fn run_custom_command(args: &[String]) {
+    let [result_path, timeout_ms, cwd, command] = args else {
+        return;
+    };
+    let timeout = Duration::from_millis(timeout_ms.parse().unwrap_or(1000));
+    let cwd = (!cwd.is_empty()).then(|| Path::new(cwd.as_str()));
+
+    let (value, error) = match run_with_timeout(command, cwd, timeout) {
+        Ok(value) => (value, None),
+        Err(err) => (None, Some(err)),
+    };
+    let result = CustomResultFile {
+        command: command.clone(),
+        finished_at_ms: cache::now_epoch_ms(),
+        value,
+        error,
+    };
+
+    let contents = match serde_json::to_string(&result) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let path = Path::new(result_path);
+    let tmp_path = path.with_extension("tmp");
+    if fs::write(&tmp_path, contents).is_ok() {
+        let _ = fs::rename(&tmp_path, path);
+    }
+}
+
+/// Run a shell command, killing it after `timeout`. Returns the first non-empty
+/// output line (ANSI stripped, capped at `MAX_VALUE_CHARS`), or None if the
+/// command printed nothing.
+pub fn run_with_timeout(
+    command: &str,
+    cwd: Option<&Path>,
+    timeout: Duration,
+) -> Result<Option<String>, String> {
+    run_for_output(command, cwd, timeout).map(|output| first_output_line(&output))
+}
+
+/// Run a shell command, killing it after `timeout`, and return its whole stdout.
+pub fn run_for_output(
+    command: &str,
+    cwd: Option<&Path>,
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut shell = shell_command(command);
+    if let Some(dir) = cwd {
+        shell.current_dir(dir);
+    }
+    let mut child = shell
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("spawn command: {e}"))?;
+
+    // Drain stdout on a thread so a chatty command cannot stall on a full pipe
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| "no stdout pipe".to_string())?;
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(status)) => return Err(format!("command failed: {status}")),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("command timed out after {}ms", timeout.as_millis()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(format!("wait for command: {e}")),
+        }
+    }
+
+    Ok(reader.join().unwrap_or_default())
+}
+
+pub(crate) fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+fn first_output_line(output: &str) -> Option<String> {
+    let line = output
+        .lines()
+        .map(|line| strip_ansi(line).trim().to_string())
+        .find(|line| !line.is_empty())?;
+    let clean: String = line.chars().filter(|c| !c.is_control()).collect();
+    Some(clean.chars().take(MAX_VALUE_CHARS).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(name: &str, command: &str) -> CustomSegmentConfig {
+        CustomSegmentConfig {
+            name: name.to_string(),
+            command: command.to_string(),
+            line: 1,
+            color: None,
+            label: None,
+            ttl_secs: 30,
+            timeout_ms: 1000,
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_with_timeout_returns_first_line() {
+        let value = run_with_timeout(
+            "printf '\\n  prod-cluster  \\nsecond\\n'",
+            None,
+            Duration::from_secs(5),
+        );
+        assert_eq!(value, Ok(Some("prod-cluster".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_with_timeout_kills_slow_command() {
+        let started = Instant::now();
+        let result = run_with_timeout("sleep 5", None, Duration::from_millis(100));
+        assert!(result.unwrap_err().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_with_timeout_reports_failure() {
+        let result = run_with_timeout("exit 3", None, Duration::from_secs(5));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn first_output_line_strips_ansi_and_caps_length() {
+        assert_eq!(
+            first_output_line("\x1b[32mok\x1b[0m\n"),
+            Some("ok".to_string())
+        );
+        let long = "x".repeat(200);
+        assert_eq!(
+            first_output_line(&long).map(|v| v.chars().count()),
+            Some(MAX_VALUE_CHARS)
+        );
+        assert_eq!(first_output_line("  \n\n"), None);
+    }
+
+    #[test]
+    fn collect_applies_finished_result_and_keeps_fresh_value() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let session_key = "custom-collect-unit-test";
+        let k8s = segment("k8s", "kubectl config current-context");
+        let result_path = custom_result_path(dir.path(), session_key, "k8s");
+        let result = CustomResultFile {
+            command: k8s.command.clone(),
+            finished_at_ms: cache::now_epoch_ms(),
+            value: Some("prod".to_string()),
+            error: None,
+        };
+        fs::write(&result_path, serde_json::to_string(&result).unwrap()).unwrap();
+
+        let mut state = SessionState {
+            custom_segments: vec![CustomSegmentEntry {
+                name: "k8s".to_string(),
+                command: k8s.command.clone(),
+                value: Some("staging".to_string()),
+                fetched_at_ms: Some(0),
+                refresh_spawned_ms: Some(cache::now_epoch_ms()),
+            }],
+            ..Default::default()
+        };
+
+        let values = collect_custom_segments(&mut state, session_key, &[k8s], dir.path(), None);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].text, "prod");
+        assert!(!result_path.exists(), "result file should be consumed");
+        assert!(state.custom_segments[0].refresh_spawned_ms.is_none());
+    }
+
+    #[test]
+    fn collect_keeps_stale_value_on_failed_refresh() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let session_key = "custom-collect-failure-unit-test";
+        let ci = segment("ci", "cat .ci-status");
+        let result_path = custom_result_path(dir.path(), session_key, "ci");
+        let result = CustomResultFile {
+            command: ci.command.clone(),
+            finished_at_ms: cache::now_epoch_ms(),
+            value: None,
+            error: Some("command timed out after 1000ms".to_string()),
+        };
+        fs::write(&result_path, serde_json::to_string(&result).unwrap()).unwrap();
+
+        let mut state = SessionState {
+            custom_segments: vec![CustomSegmentEntry {
+                name: "ci".to_string(),
+                command: ci.command.clone(),
+                value: Some("passing".to_string()),
+                fetched_at_ms: Some(0),
+                refresh_spawned_ms: None,
+            }],
+            ..Default::default()
+        };
+
+        let values = collect_custom_segments(&mut state, session_key, &[ci], dir.path(), None);
+        assert_eq!(values[0].text, "passing");
+    }
+
+    #[test]
+    fn command_change_invalidates_cached_value() {
+        let mut state = SessionState {
+            custom_segments: vec![CustomSegmentEntry {
+                name: "k8s".to_string(),
+                command: "old".to_string(),
+                value: Some("stale".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let entry = custom_entry_mut(&mut state, &segment("k8s", "new"));
+        assert!(entry.value.is_none());
+        assert_eq!(entry.command, "new");
+    }
+}
@@ -4,6 +4,8 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use super::claude_dir::ClaudeConfigDir;
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct EnvSnapshot {
     pub claude_md_count: u32,
@@ -14,13 +16,28 @@ pub struct EnvSnapshot {
     pub skills_count: u32,
 }
 
-pub trait EnvCollector {
+pub trait EnvCollector: Send + Sync {
     fn collect_env(&self, cwd: &str) -> EnvSnapshot;
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct FileSystemEnvCollector {
     pub user_home_override: Option<PathBuf>,
+    /// Claude config root; resolved from `CLAUDE_CONFIG_DIR` or the home
+    /// directory when unset.
+    pub claude_dir: Option<ClaudeConfigDir>,
+}
+
+impl FileSystemEnvCollector {
+    fn resolve_claude_dir(&self) -> Option<ClaudeConfigDir> {
+        if let Some(dir) = &self.claude_dir {
+            return Some(dir.clone());
+        }
+        match &self.user_home_override {
+            Some(home) => Some(ClaudeConfigDir::under_home(home)),
+            None => ClaudeConfigDir::resolve(None),
+        }
+    }
 }
 
 impl EnvCollector for FileSystemEnvCollector {
@@ -30,47 +47,34 @@ impl EnvCollector for FileSystemEnvCollector {
             return EnvSnapshot::default();
         }
 
-        let user_home = self.user_home_override.clone().or_else(|| {
-            std::env::var("HOME")
-                .or_else(|_| std::env::var("USERPROFILE"))
-                .ok()
-                .map(PathBuf::from)
-        });
+        let claude_dir = self.resolve_claude_dir();
+        let claude_dir = claude_dir.as_ref();
 
-        let mcp_count = count_mcp_servers_scoped(root, user_home.as_deref());
+        let mcp_count = count_mcp_servers_scoped(root, claude_dir);
 
         let rules_count = count_md_files_recursive(&root.join(".claude/rules"))
-            + user_home
-                .as_ref()
-                .map(|home| count_md_files_recursive(&home.join(".claude/rules")))
+            + claude_dir
+                .map(|dir| count_md_files_recursive(&dir.join("rules")))
                 .unwrap_or(0);
 
         let skills_count = count_skill_dirs(&root.join(".claude/skills"))
-            + user_home
-                .as_ref()
-                .map(|home| count_skill_dirs(&home.join(".claude/skills")))
+            + claude_dir
+                .map(|dir| count_skill_dirs(&dir.join("skills")))
                 .unwrap_or(0)
-            + user_home
-                .as_ref()
-                .map(|home| count_plugin_skills(home))
-                .unwrap_or(0);
+            + claude_dir.map(count_plugin_skills).unwrap_or(0);
 
-        let memory_count = count_memory_files(user_home.as_deref(), cwd);
+        let memory_count = count_memory_files(claude_dir, cwd);
 
         EnvSnapshot {
-            claude_md_count: count_claude_md(root, user_home.as_deref()),
+            claude_md_count: count_claude_md(root, claude_dir),
             rules_count,
             memory_count,
             hooks_count: count_hooks_in_json(&root.join(".claude/settings.json"))
                 + count_hooks_in_json(&root.join(".claude/settings.local.json"))
-                + user_home
-                    .as_ref()
-                    .map(|h| count_hooks_in_json(&h.join(".claude/settings.json")))
+                + claude_dir
+                    .map(|dir| count_hooks_in_json(&dir.join("settings.json")))
                     .unwrap_or(0)
-                + user_home
-                    .as_ref()
-                    .map(|h| count_plugin_hooks(h))
-                    .unwrap_or(0),
+                + claude_dir.map(count_plugin_hooks).unwrap_or(0),
             mcp_count,
             skills_count,
         }
@@ -86,7 +90,7 @@ impl EnvCollector for StubEnvCollector {
     }
 }
 
-fn count_claude_md(root: &Path, user_home: Option<&Path>) -> u32 {
+fn count_claude_md(root: &Path, claude_dir: Option<&ClaudeConfigDir>) -> u32 {
     let mut paths = vec![
         root.join("CLAUDE.md"),
         root.join("CLAUDE.local.md"),
@@ -94,8 +98,8 @@ fn count_claude_md(root: &Path, user_home: Option<&Path>) -> u32 {
         root.join(".claude/CLAUDE.local.md"),
     ];
 
-    if let Some(home) = user_home {
-        paths.push(home.join(".claude/CLAUDE.md"));
+    if let Some(dir) = claude_dir {
+        paths.push(dir.join("CLAUDE.md"));
     }
 
     paths.iter().filter(|path| path.is_file()).count() as u32
@@ -177,19 +181,19 @@ fn get_disabled_mcp_servers(path: &Path, key: &str) -> HashSet<String> {
 }
 
 /// Count MCP servers across user and project scopes with dedup + disabled filtering.
-fn count_mcp_servers_scoped(root: &Path, user_home: Option<&Path>) -> u32 {
+fn count_mcp_servers_scoped(root: &Path, claude_dir: Option<&ClaudeConfigDir>) -> u32 {
     let mut user_set = HashSet::new();
     let mut project_set = HashSet::new();
 
     // === User scope ===
-    if let Some(home) = user_home {
+    if let Some(dir) = claude_dir {
         // ~/.claude/settings.json → mcpServers
-        for name in get_mcp_server_names(&home.join(".claude/settings.json")) {
+        for name in get_mcp_server_names(&dir.join("settings.json")) {
             user_set.insert(name);
         }
 
         // ~/.claude.json → mcpServers + disabledMcpServers (single read)
-        if let Some(claude_json) = read_json_file(&home.join(".claude.json")) {
+        if let Some(claude_json) = read_json_file(&dir.state_file) {
             for name in mcp_server_names_from(&claude_json) {
                 user_set.insert(name);
             }
@@ -267,9 +271,9 @@ fn count_hooks_in_json(path: &Path) -> u32 {
 ///
 /// Reads `installed_plugins.json` for plugin entries, cross-references `enabledPlugins`
 /// in `settings.json`, and returns the `installPath` of each enabled plugin.
-fn get_enabled_plugin_paths(user_home: &Path) -> Vec<PathBuf> {
-    let plugins_path = user_home.join(".claude/plugins/installed_plugins.json");
-    let settings_path = user_home.join(".claude/settings.json");
+fn get_enabled_plugin_paths(claude_dir: &ClaudeConfigDir) -> Vec<PathBuf> {
+    let plugins_path = claude_dir.join("plugins/installed_plugins.json");
+    let settings_path = claude_dir.join("settings.json");
 
     let plugins_text = match fs::read_to_string(&plugins_path) {
         Ok(t) => t,
@@ -315,8 +319,8 @@ fn get_enabled_plugin_paths(user_home: &Path) -> Vec<PathBuf> {
 }
 
 /// Count skills from enabled plugins by reading installed_plugins.json + settings.json.
-fn count_plugin_skills(user_home: &Path) -> u32 {
-    get_enabled_plugin_paths(user_home)
+fn count_plugin_skills(claude_dir: &ClaudeConfigDir) -> u32 {
+    get_enabled_plugin_paths(claude_dir)
         .iter()
         .map(|path| count_skill_dirs(&path.join("skills")))
         .sum()
@@ -325,8 +329,8 @@ fn count_plugin_skills(user_home: &Path) -> u32 {
 /// Count hook handlers from enabled plugins.
 ///
 /// Each plugin may have `hooks/hooks.json` or `hooks/hook.json` (singular fallback).
-fn count_plugin_hooks(user_home: &Path) -> u32 {
-    get_enabled_plugin_paths(user_home)
+fn count_plugin_hooks(claude_dir: &ClaudeConfigDir) -> u32 {
+    get_enabled_plugin_paths(claude_dir)
         .iter()
         .map(|path| {
             let hooks_file = path.join("hooks/hooks.json");
@@ -349,14 +353,14 @@ pub fn encode_project_path(path: &str) -> String {
 }
 
 /// Count `.md` files in the project's memory directory (flat scan, no recursion).
-fn count_memory_files(user_home: Option<&Path>, project_path: &str) -> u32 {
-    let home = match user_home {
-        Some(h) => h,
+fn count_memory_files(claude_dir: Option<&ClaudeConfigDir>, project_path: &str) -> u32 {
+    let claude_dir = match claude_dir {
+        Some(dir) => dir,
         None => return 0,
     };
 
     let encoded = encode_project_path(project_path);
-    let memory_dir = home.join(".claude/projects").join(encoded).join("memory");
+    let memory_dir = claude_dir.join("projects").join(encoded).join("memory");
 
     let entries = match fs::read_dir(&memory_dir) {
         Ok(entries) => entries,
@@ -408,7 +412,10 @@ mod tests {
         fs::write(root.join(".claude/CLAUDE.local.md"), "").unwrap();
         fs::write(home.join(".claude/CLAUDE.md"), "").unwrap();
 
-        assert_eq!(count_claude_md(&root, Some(&home)), 5);
+        assert_eq!(
+            count_claude_md(&root, Some(&ClaudeConfigDir::under_home(&home))),
+            5
+        );
     }
 
     #[test]
@@ -517,7 +524,10 @@ mod tests {
         .unwrap();
 
         // user_mcp=1 (user-extra disabled), project=1 (proj-disabled removed) → total 2
-        assert_eq!(count_mcp_servers_scoped(&root, Some(&home)), 2);
+        assert_eq!(
+            count_mcp_servers_scoped(&root, Some(&ClaudeConfigDir::under_home(&home))),
+            2
+        );
     }
 
     #[test]
@@ -541,7 +551,10 @@ mod tests {
         )
         .unwrap();
 
-        assert_eq!(count_mcp_servers_scoped(&root, Some(&home)), 1);
+        assert_eq!(
+            count_mcp_servers_scoped(&root, Some(&ClaudeConfigDir::under_home(&home))),
+            1
+        );
     }
 
     #[test]
@@ -575,14 +588,14 @@ mod tests {
         .unwrap();
 
         // plugin-a has 2 skill dirs, plugin-b disabled → total 2
-        assert_eq!(count_plugin_skills(&home), 2);
+        assert_eq!(count_plugin_skills(&ClaudeConfigDir::under_home(&home)), 2);
     }
 
     #[test]
     fn plugin_skills_missing_files() {
         let tmp = TempDir::new().unwrap();
         let home = tmp.path().join("nonexistent_home");
-        assert_eq!(count_plugin_skills(&home), 0);
+        assert_eq!(count_plugin_skills(&ClaudeConfigDir::under_home(&home)), 0);
     }
 
     #[test]
@@ -618,12 +631,55 @@ mod tests {
 
         let collector = FileSystemEnvCollector {
             user_home_override: Some(home),
+            ..Default::default()
         };
         let snapshot = collector.collect_env(root.to_str().unwrap());
         // 1 project + 1 user + 2 plugin = 4
         assert_eq!(snapshot.skills_count, 4);
     }
 
+    #[test]
+    fn custom_claude_dir_replaces_home_layout() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("project");
+        let home = tmp.path().join("home");
+        let work = tmp.path().join("claude-work");
+        fs::create_dir_all(&root).unwrap();
+
+        // The default root has content that must be ignored
+        fs::create_dir_all(home.join(".claude/rules")).unwrap();
+        fs::write(home.join(".claude/rules/personal.md"), "").unwrap();
+        fs::write(home.join(".claude/CLAUDE.md"), "").unwrap();
+
+        fs::create_dir_all(work.join("rules")).unwrap();
+        fs::write(work.join("rules/a.md"), "").unwrap();
+        fs::write(work.join("rules/b.md"), "").unwrap();
+        fs::write(work.join("CLAUDE.md"), "").unwrap();
+        fs::create_dir_all(work.join("skills/deploy")).unwrap();
+        fs::write(
+            work.join(".claude.json"),
+            r#"{"mcpServers":{"jira":{},"slack":{}}}"#,
+        )
+        .unwrap();
+        let memory_dir = work
+            .join("projects")
+            .join(encode_project_path(root.to_str().unwrap()))
+            .join("memory");
+        fs::create_dir_all(&memory_dir).unwrap();
+        fs::write(memory_dir.join("notes.md"), "").unwrap();
+
+        let collector = FileSystemEnvCollector {
+            user_home_override: Some(home),
+            claude_dir: Some(ClaudeConfigDir::custom(work)),
+        };
+        let snapshot = collector.collect_env(root.to_str().unwrap());
+        assert_eq!(snapshot.rules_count, 2);
+        assert_eq!(snapshot.claude_md_count, 1);
+        assert_eq!(snapshot.skills_count, 1);
+        assert_eq!(snapshot.mcp_count, 2);
+        assert_eq!(snapshot.memory_count, 1);
+    }
+
     #[test]
     fn hooks_counts_multiple_handlers_per_group() {
         let tmp = TempDir::new().unwrap();
@@ -690,7 +746,7 @@ mod tests {
         .unwrap();
 
         // Only plugin-a is enabled → 2 handlers
-        assert_eq!(count_plugin_hooks(&home), 2);
+        assert_eq!(count_plugin_hooks(&ClaudeConfigDir::under_home(&home)), 2);
     }
 
     #[test]
@@ -721,7 +777,7 @@ mod tests {
         )
         .unwrap();
 
-        assert_eq!(count_plugin_hooks(&home), 1);
+        assert_eq!(count_plugin_hooks(&ClaudeConfigDir::under_home(&home)), 1);
     }
 
     #[test]
@@ -747,7 +803,7 @@ mod tests {
         )
         .unwrap();
 
-        assert_eq!(count_plugin_hooks(&home), 0);
+        assert_eq!(count_plugin_hooks(&ClaudeConfigDir::under_home(&home)), 0);
     }
 
     // ── Memory file counting tests ──────────────────────────────────
@@ -791,7 +847,10 @@ mod tests {
         fs::write(memory_dir.join("notes.txt"), "not counted").unwrap();
         fs::write(memory_dir.join("data.json"), "{}").unwrap();
 
-        assert_eq!(count_memory_files(Some(&home), project_path), 3);
+        assert_eq!(
+            count_memory_files(Some(&ClaudeConfigDir::under_home(&home)), project_path),
+            3
+        );
     }
 
     #[test]
@@ -803,7 +862,10 @@ mod tests {
         let memory_dir = home.join(".claude/projects").join(&encoded).join("memory");
         fs::create_dir_all(&memory_dir).unwrap();
 
-        assert_eq!(count_memory_files(Some(&home), project_path), 0);
+        assert_eq!(
+            count_memory_files(Some(&ClaudeConfigDir::under_home(&home)), project_path),
+            0
+        );
     }
 
     #[test]
@@ -811,7 +873,10 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let home = tmp.path().join("home");
         assert_eq!(
-            count_memory_files(Some(&home), "/Users/gregho/nonexistent"),
+            count_memory_files(
+                Some(&ClaudeConfigDir::under_home(&home)),
+                "/Users/gregho/nonexistent"
+            ),
             0
         );
     }
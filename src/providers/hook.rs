@@ -0,0 +1,165 @@
+use serde_json::{json, Value};
+
+use crate::{
+    providers::transcript::apply_content_block,
+    state::{cache, SessionState},
+    types::HookPayload,
+};
+
+/// Apply one Claude Code hook event to session state.
+///
+/// Tool hooks are translated into the same `tool_use` / `tool_result` blocks the
+/// transcript parser consumes, keyed by the real `tool_use_id`, and the id is
+/// remembered so the transcript fallback skips it later. Events without a
+/// `tool_use_id` are left to transcript parsing.
+pub fn apply_hook_event(state: &mut SessionState, hook: &HookPayload) {
+    let Some(event) = hook.hook_event_name.as_deref() else {
+        return;
+    };
+    let now = cache::now_epoch_ms();
+
+    if let Some(transcript_path) = hook.transcript_path.as_deref() {
+        state.reset_transcript_if_path_changed(transcript_path);
+    }
+    state.record_hook_event(event, hook.message.as_deref(), now);
+
+    match event {
+        "PreToolUse" => {
+            if let Some(id) = hook.tool_use_id.as_deref() {
+                apply_tool_start(state, hook, id, now);
+            }
+        }
+        "PostToolUse" => {
+            if let Some(id) = hook.tool_use_id.as_deref() {
+                // PostToolUse without a prior PreToolUse (hook registered mid-tool)
+                // still counts as a start + finish.
+                if !state.is_hook_tool(id) {
+                    apply_tool_start(state, hook, id, now);
+                }
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": id,
+                    "content": hook.tool_response.clone().unwrap_or(Value::Null),
+                });
+                apply_content_block(state, &block, Some(now));
+            }
+        }
+        "SubagentStop" => {
+            if let Some(agent_id) = hook.agent_id.as_deref() {
+                state.remove_agent(agent_id);
+            }
+        }
+        // Notification / Stop only record the event for session-phase display
+        _ => {}
+    }
+}
+
+fn apply_tool_start(state: &mut SessionState, hook: &HookPayload, id: &str, now: u64) {
+    state.mark_hook_tool(id);
+    let block = json!({
+        "type": "tool_use",
+        "id": id,
+        "name": hook.tool_name.as_deref().unwrap_or("unknown"),
+        "input": hook.tool_input.clone().unwrap_or(Value::Null),
+    });
+    apply_content_block(state, &block, Some(now));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_hook(event: &str, name: &str, id: &str, input: Value) -> HookPayload {
+        HookPayload {
+            hook_event_name: Some(event.to_string()),
+            tool_name: Some(name.to_string()),
+            tool_use_id: Some(id.to_string()),
+            tool_input: Some(input),
+            transcript_path: Some("/tmp/t.jsonl".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pre_and_post_tool_use_track_exact_completion() {
+        let mut state = SessionState::default();
+        let input = json!({"file_path": "/repo/src/main.rs"});
+
+        apply_hook_event(
+            &mut state,
+            &tool_hook("PreToolUse", "Read", "toolu_1", input.clone()),
+        );
+        assert_eq!(state.active_tools.len(), 1);
+        assert!(state.active_tools[0]
+            .target
+            .as_deref()
+            .is_some_and(|t| t.ends_with("main.rs")));
+
+        apply_hook_event(
+            &mut state,
+            &tool_hook("PostToolUse", "Read", "toolu_1", input),
+        );
+        assert!(state.active_tools.is_empty());
+        assert_eq!(state.completed_tool_counts.get("Read"), Some(&1));
+        assert!(state.is_hook_tool("toolu_1"));
+    }
+
+    #[test]
+    fn post_tool_use_without_pre_still_counts_once() {
+        let mut state = SessionState::default();
+        apply_hook_event(
+            &mut state,
+            &tool_hook("PostToolUse", "Bash", "toolu_2", json!({"command": "ls"})),
+        );
+        assert!(state.active_tools.is_empty());
+        assert_eq!(state.completed_tool_counts.get("Bash"), Some(&1));
+    }
+
+    #[test]
+    fn events_without_tool_use_id_are_left_to_transcript() {
+        let mut state = SessionState::default();
+        let mut hook = tool_hook("PreToolUse", "Read", "", json!({}));
+        hook.tool_use_id = None;
+        apply_hook_event(&mut state, &hook);
+        assert!(state.active_tools.is_empty());
+        assert_eq!(state.last_hook_event.as_deref(), Some("PreToolUse"));
+    }
+
+    #[test]
+    fn agent_tool_hooks_complete_the_agent() {
+        let mut state = SessionState::default();
+        let input = json!({"description": "Explore code", "subagent_type": "Explore"});
+        apply_hook_event(
+            &mut state,
+            &tool_hook("PreToolUse", "Agent", "toolu_a", input.clone()),
+        );
+        assert_eq!(state.pending_tasks.len(), 1);
+
+        apply_hook_event(
+            &mut state,
+            &tool_hook("PostToolUse", "Agent", "toolu_a", input),
+        );
+        assert!(state.pending_tasks.is_empty());
+        assert_eq!(state.completed_agents.len(), 1);
+        assert_eq!(state.completed_agents[0].description, "Explore code");
+    }
+
+    #[test]
+    fn subagent_stop_completes_agent_by_id() {
+        let mut state = SessionState {
+            last_transcript_path: Some("/tmp/t.jsonl".to_string()),
+            ..Default::default()
+        };
+        state.upsert_agent("agent-1".into(), "Review".into(), None, None, None);
+
+        let hook = HookPayload {
+            hook_event_name: Some("SubagentStop".to_string()),
+            agent_id: Some("agent-1".to_string()),
+            transcript_path: Some("/tmp/t.jsonl".to_string()),
+            ..Default::default()
+        };
+        apply_hook_event(&mut state, &hook);
+        assert!(state.active_agents.is_empty());
+        assert_eq!(state.completed_agents.len(), 1);
+    }
+}
@@ -0,0 +1,404 @@
+//! Minimal HTTP transport for the quota fetcher.
+//!
+//! [`HttpTransport`] abstracts one request/response exchange so the fetcher
+//! can be pointed at a stand-in server or driven by a test double.
+//! [`DefaultTransport`] speaks plain `http://` itself for loopback hosts (no
+//! curl needed for a local server), refuses cleartext to any other host since
+//! requests carry the OAuth token, and hands `https://` to curl, which also
+//! honours the usual `HTTPS_PROXY` / `NO_PROXY` variables.
+
+use std::{
+    io::{Read, Write},
+    net::{IpAddr, TcpStream, ToSocketAddrs},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// Default timeout for one request, connect included.
+pub const HTTP_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl HttpRequest {
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: "GET",
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn post(url: &str, body: String) -> Self {
+        Self {
+            method: "POST",
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: Some(body),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// First value of header `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends one HTTP request. `Err` means no response was received; HTTP error
+/// statuses are returned as `Ok` for the caller to interpret.
+pub trait HttpTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String>;
+}
+
+/// Plain `http://` for loopback servers, curl for `https://`. Cleartext to
+/// any other host is refused.
+#[derive(Debug, Default)]
+pub struct DefaultTransport;
+
+impl HttpTransport for DefaultTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let Some(rest) = request.url.strip_prefix("http://") else {
+            return CurlTransport::default().send(request);
+        };
+        let authority = rest.split('/').next().unwrap_or_default();
+        if is_loopback_authority(authority) {
+            PlainHttpTransport::default().send(request)
+        } else {
+            Err(format!(
+                "refusing cleartext http:// to {authority}; use https:// for non-local endpoints"
+            ))
+        }
+    }
+}
+
+/// `localhost`, `127.0.0.0/8` or `[::1]`, with or without a port.
+fn is_loopback_authority(authority: &str) -> bool {
+    let host = match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+// ── curl ────────────────────────────────────────────────────────────
+
+/// Runs `curl`. The URL, headers and body are written to curl's stdin as a
+/// config file, so tokens never show up in `ps`.
+#[derive(Debug)]
+pub struct CurlTransport {
+    pub timeout_secs: u64,
+}
+
+impl Default for CurlTransport {
+    fn default() -> Self {
+        Self {
+            timeout_secs: HTTP_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl HttpTransport for CurlTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let mut child = Command::new("curl")
+            .args([
+                "-s",
+                "-i",
+                "--max-time",
+                &self.timeout_secs.to_string(),
+                "--config",
+                "-",
+            ])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("spawn curl: {e}"))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(curl_config(request).as_bytes())
+                .map_err(|e| format!("write to curl stdin: {e}"))?;
+            // stdin drops here, closing the pipe
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("curl wait: {e}"))?;
+        if !output.status.success() {
+            return Err(format!("curl exit code: {:?}", output.status.code()));
+        }
+
+        let (status, headers, body) = parse_response_head(&output.stdout)?;
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(body).into_owned(),
+        })
+    }
+}
+
+/// curl `--config` text for `request`.
+fn curl_config(request: &HttpRequest) -> String {
+    let quote = |value: &str| {
+        let escaped = value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        format!("\"{escaped}\"")
+    };
+    let mut config = format!(
+        "url = {}\nrequest = {}\n",
+        quote(&request.url),
+        request.method
+    );
+    for (name, value) in &request.headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            quote(&format!("{name}: {value}"))
+        ));
+    }
+    if let Some(body) = &request.body {
+        config.push_str(&format!("data-binary = {}\n", quote(body)));
+    }
+    config
+}
+
+// ── Plain HTTP ──────────────────────────────────────────────────────
+
+/// HTTP/1.1 over a plain TCP socket. Only `http://` URLs; no TLS, no proxy.
+#[derive(Debug)]
+pub struct PlainHttpTransport {
+    pub timeout: Duration,
+}
+
+impl Default for PlainHttpTransport {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(HTTP_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl HttpTransport for PlainHttpTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let rest = request
+            .url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("unsupported URL (http:// only): {}", request.url))?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, "/"),
+        };
+        let address = if authority.contains(':') {
+            authority.to_string()
+        } else {
+            format!("{authority}:80")
+        };
+
+        let socket_addr = address
+            .to_socket_addrs()
+            .map_err(|e| format!("resolve {authority}: {e}"))?
+            .next()
+            .ok_or_else(|| format!("resolve {authority}: no address"))?;
+        let mut stream = TcpStream::connect_timeout(&socket_addr, self.timeout)
+            .map_err(|e| format!("connect {authority}: {e}"))?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+            .map_err(|e| format!("socket: {e}"))?;
+
+        let body = request.body.as_deref().unwrap_or_default();
+        let mut head = format!(
+            "{} {path} HTTP/1.1\r\nHost: {authority}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            request.method,
+            body.len()
+        );
+        for (name, value) in &request.headers {
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        head.push_str("\r\n");
+        stream
+            .write_all(head.as_bytes())
+            .and_then(|_| stream.write_all(body.as_bytes()))
+            .map_err(|e| format!("send request: {e}"))?;
+
+        let mut raw = Vec::new();
+        stream
+            .read_to_end(&mut raw)
+            .map_err(|e| format!("read response: {e}"))?;
+
+        let (status, headers, body) = parse_response_head(&raw)?;
+        let chunked = headers.iter().any(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding") && value.eq_ignore_ascii_case("chunked")
+        });
+        let body = if chunked {
+            decode_chunked(body)?
+        } else {
+            body.to_vec()
+        };
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        })
+    }
+}
+
+type ResponseHead<'a> = (u16, Vec<(String, String)>, &'a [u8]);
+
+/// Split a raw response into status, headers and body. Interim blocks
+/// (`100 Continue`, a proxy's `200 Connection established`) are skipped.
+fn parse_response_head(raw: &[u8]) -> Result<ResponseHead<'_>, String> {
+    let mut rest = raw;
+    loop {
+        let end = rest
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| "malformed HTTP response".to_string())?;
+        let head = String::from_utf8_lossy(&rest[..end]);
+        let body = &rest[end + 4..];
+
+        let mut lines = head.split("\r\n");
+        let status: u16 = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| "malformed HTTP status line".to_string())?;
+        if (100..200).contains(&status) || body.starts_with(b"HTTP/") {
+            rest = body;
+            continue;
+        }
+
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        return Ok((status, headers, body));
+    }
+}
+
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| "malformed chunked body".to_string())?;
+        let size_text = String::from_utf8_lossy(&data[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size_text, 16).map_err(|_| "malformed chunk size".to_string())?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        let chunk = data
+            .get(..size)
+            .ok_or_else(|| "truncated chunked body".to_string())?;
+        body.extend_from_slice(chunk);
+        data = data.get(size + 2..).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curl_config_quotes_values() {
+        let request = HttpRequest::post("https://example.com/token", r#"{"a":"b\c"}"#.into())
+            .header("Authorization", "Bearer t\"k");
+        assert_eq!(
+            curl_config(&request),
+            concat!(
+                "url = \"https://example.com/token\"\n",
+                "request = POST\n",
+                "header = \"Authorization: Bearer t\\\"k\"\n",
+                "data-binary = \"{\\\"a\\\":\\\"b\\\\c\\\"}\"\n",
+            )
+        );
+    }
+
+    #[test]
+    fn parse_response_head_skips_interim_blocks() {
+        let raw = b"HTTP/1.1 200 Connection established\r\n\r\nHTTP/1.1 100 Continue\r\n\r\nHTTP/2 429 \r\nretry-after: 30\r\ncontent-type: text/plain\r\n\r\nslow down";
+        let (status, headers, body) = parse_response_head(raw).unwrap();
+        assert_eq!(status, 429);
+        let response = HttpResponse {
+            status,
+            headers,
+            body: String::from_utf8_lossy(body).into_owned(),
+        };
+        assert_eq!(response.header("Retry-After"), Some("30"));
+        assert_eq!(response.body, "slow down");
+        assert!(parse_response_head(b"garbage").is_err());
+    }
+
+    #[test]
+    fn decode_chunked_joins_chunks() {
+        assert_eq!(
+            decode_chunked(b"4\r\nWiki\r\n5;ext=1\r\npedia\r\n0\r\n\r\n").unwrap(),
+            b"Wikipedia"
+        );
+        assert!(decode_chunked(b"zz\r\n").is_err());
+    }
+
+    #[test]
+    fn default_transport_keeps_cleartext_on_loopback() {
+        for authority in [
+            "127.0.0.1:8080",
+            "localhost",
+            "LOCALHOST:9",
+            "[::1]:443",
+            "127.1.2.3",
+        ] {
+            assert!(is_loopback_authority(authority), "{authority}");
+        }
+        for authority in [
+            "example.com",
+            "10.0.0.1:80",
+            "localhost.example.com",
+            "[::2]",
+        ] {
+            assert!(!is_loopback_authority(authority), "{authority}");
+        }
+
+        let err = DefaultTransport
+            .send(&HttpRequest::get("http://example.com/api/oauth/usage"))
+            .unwrap_err();
+        assert!(err.contains("refusing cleartext"), "{err}");
+    }
+
+    #[test]
+    fn plain_transport_rejects_https() {
+        let err = PlainHttpTransport::default()
+            .send(&HttpRequest::get("https://example.com/"))
+            .unwrap_err();
+        assert!(err.contains("http:// only"), "{err}");
+    }
+}
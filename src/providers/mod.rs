@@ -1,9 +1,15 @@
+pub mod claude_dir;
+pub mod custom;
 pub mod env;
 pub mod git;
+pub mod hook;
+pub mod http;
 pub mod quota;
 pub mod quota_fetch;
+pub mod timezone;
 pub mod transcript;
 
+pub use claude_dir::ClaudeConfigDir;
 pub use env::{EnvCollector, EnvSnapshot, FileSystemEnvCollector, StubEnvCollector};
 pub use git::{GitCollector, GitSnapshot, LocalGitCollector, StubGitCollector};
 pub use quota::{CachedFileQuotaCollector, QuotaCollector, QuotaSnapshot, StubQuotaCollector};
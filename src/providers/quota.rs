@@ -5,6 +5,8 @@ use std::{
     path::PathBuf,
 };
 
+use super::claude_dir::{ClaudeConfigDir, CONFIG_DIR_ENV};
+
 // ── Quota Snapshot ──────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -20,24 +22,78 @@ pub struct QuotaSnapshot {
     /// Uses a much longer TTL to avoid periodic refetch attempts.
     #[serde(default)]
     pub terminal: bool,
+    /// Utilisation slope within the current 5h window, in percent per hour,
+    /// from the samples in [`QuotaCacheFile::history`].
+    #[serde(default)]
+    pub five_hour_pct_per_hour: Option<f64>,
+    /// Same for the current 7d window.
+    #[serde(default)]
+    pub seven_day_pct_per_hour: Option<f64>,
+    /// Model-specific weekly windows (`seven_day_opus`, `seven_day_sonnet`, ...).
+    #[serde(default)]
+    pub model_buckets: Vec<ModelQuotaBucket>,
+    /// Extra-usage (overage credit) status, when the API reports it.
+    #[serde(default)]
+    pub extra_usage: Option<ExtraUsage>,
+    /// The values are from the last successful fetch; the latest one could
+    /// not run (see `error`), e.g. while the access token is expired.
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// A weekly window that only counts usage of one model family.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelQuotaBucket {
+    /// Lowercase model family, e.g. `opus`.
+    pub model: String,
+    pub pct: f64,
+    pub reset_at: Option<u64>,
+}
+
+/// Overage credits billed after the plan's windows are exhausted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtraUsage {
+    pub enabled: bool,
+    /// Credits used this month, in USD.
+    pub used_usd: Option<f64>,
+    /// Monthly spending cap, in USD.
+    pub limit_usd: Option<f64>,
+    pub pct: Option<f64>,
 }
 
 // ── Quota Collector Trait ───────────────────────────────────────────
 
-pub trait QuotaCollector {
+pub trait QuotaCollector: Send + Sync {
     /// Returns `(snapshot, is_stale)` — stale means a background fetch should be triggered.
     fn collect_quota(&self) -> (QuotaSnapshot, bool);
+
+    /// Kick off a refresh after `collect_quota` reported stale data.
+    /// Must not block the render path. Default: no-op.
+    fn refresh_quota(&self) {}
 }
 
 // ── Cached File Collector (production) ──────────────────────────────
 
 /// Reads quota data from a cache file. Never performs network I/O.
 #[derive(Debug, Default)]
-pub struct CachedFileQuotaCollector;
+pub struct CachedFileQuotaCollector {
+    /// Claude config root whose cache to read; resolved from
+    /// `CLAUDE_CONFIG_DIR` or the home directory when unset.
+    pub claude_dir: Option<ClaudeConfigDir>,
+}
+
+impl CachedFileQuotaCollector {
+    fn resolved_claude_dir(&self) -> Option<ClaudeConfigDir> {
+        self.claude_dir
+            .clone()
+            .or_else(|| ClaudeConfigDir::resolve(None))
+    }
+}
 
 impl QuotaCollector for CachedFileQuotaCollector {
     fn collect_quota(&self) -> (QuotaSnapshot, bool) {
-        let path = quota_cache_path();
+        let claude_dir = self.resolved_claude_dir();
+        let path = quota_cache_path(claude_dir.as_ref());
         let contents = match fs::read_to_string(&path) {
             Ok(c) => c,
             Err(_) => return (QuotaSnapshot::default(), true), // no cache → stale
@@ -55,6 +111,11 @@ impl QuotaCollector for CachedFileQuotaCollector {
             return (cache.snapshot, false);
         }
 
+        // Rate limited — wait for the server's Retry-After
+        if cache.retry_at_ms.is_some_and(|retry_at| retry_at > now) {
+            return (cache.snapshot, false);
+        }
+
         // Check TTL based on success/failure
         let ttl = if cache.snapshot.error.is_some() {
             QUOTA_FAILURE_TTL_MS
@@ -71,6 +132,10 @@ impl QuotaCollector for CachedFileQuotaCollector {
 
         (cache.snapshot, false)
     }
+
+    fn refresh_quota(&self) {
+        spawn_background_fetch(self.resolved_claude_dir().as_ref());
+    }
 }
 
 // ── Stub Collector (testing) ────────────────────────────────────────
@@ -91,39 +156,155 @@ impl QuotaCollector for StubQuotaCollector {
 pub const QUOTA_SUCCESS_TTL_MS: u64 = 60_000;
 pub const QUOTA_FAILURE_TTL_MS: u64 = 15_000;
 
+/// Minimum spacing between history samples.
+pub const QUOTA_SAMPLE_INTERVAL_MS: u64 = 5 * 60_000;
+/// Samples kept in the cache file (8h of continuous use at one per 5 min).
+pub const QUOTA_HISTORY_CAPACITY: usize = 96;
+/// Reset timestamps within this distance belong to the same window; the API
+/// reports them with sub-second jitter between fetches.
+const SAME_WINDOW_TOLERANCE_MS: u64 = 5 * 60_000;
+/// Samples must span at least this long before a slope is reported.
+const MIN_SLOPE_SPAN_MS: u64 = 10 * 60_000;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuotaCacheFile {
     pub fetched_at_ms: u64,
     pub snapshot: QuotaSnapshot,
+    /// Successful snapshots, oldest first, for the burn-rate projection.
+    #[serde(default)]
+    pub history: Vec<QuotaSample>,
+    /// No fetch before this time (from a 429 `Retry-After`).
+    #[serde(default)]
+    pub retry_at_ms: Option<u64>,
+}
+
+/// Utilisation of both windows at one fetch.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaSample {
+    pub at_ms: u64,
+    pub five_hour_pct: Option<f64>,
+    pub five_hour_reset_at: Option<u64>,
+    pub seven_day_pct: Option<f64>,
+    pub seven_day_reset_at: Option<u64>,
 }
 
-/// Compute the quota cache file path (per-user, based on HOME hash).
-pub fn quota_cache_path() -> PathBuf {
-    let home = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .unwrap_or_default();
+/// Append `snapshot` to `history` (samples at least
+/// [`QUOTA_SAMPLE_INTERVAL_MS`] apart) and drop samples from expired 7d windows.
+/// Failed and unavailable snapshots leave the history unchanged.
+pub fn record_quota_sample(history: &mut Vec<QuotaSample>, snapshot: &QuotaSnapshot, now_ms: u64) {
+    if !snapshot.available || snapshot.error.is_some() {
+        return;
+    }
+    let sample = QuotaSample {
+        at_ms: now_ms,
+        five_hour_pct: snapshot.five_hour_pct,
+        five_hour_reset_at: snapshot.five_hour_reset_at,
+        seven_day_pct: snapshot.seven_day_pct,
+        seven_day_reset_at: snapshot.seven_day_reset_at,
+    };
+    // The newest sample always reflects the latest fetch; it only becomes a
+    // fixed point once it is an interval past the one before it.
+    match history.as_slice() {
+        [.., before, last]
+            if last.at_ms.saturating_sub(before.at_ms) < QUOTA_SAMPLE_INTERVAL_MS =>
+        {
+            *history.last_mut().unwrap() = sample;
+        }
+        _ => history.push(sample),
+    }
+    history.retain(|s| {
+        s.seven_day_reset_at.map_or(true, |reset| reset > now_ms)
+            && now_ms.saturating_sub(s.at_ms) <= 7 * 24 * 3_600_000
+    });
+    let excess = history.len().saturating_sub(QUOTA_HISTORY_CAPACITY);
+    history.drain(..excess);
+}
+
+/// Set the snapshot's per-window slopes from the history samples that share
+/// its current reset times.
+pub fn apply_quota_slopes(snapshot: &mut QuotaSnapshot, history: &[QuotaSample]) {
+    snapshot.five_hour_pct_per_hour = window_slope(history, snapshot.five_hour_reset_at, |s| {
+        (s.five_hour_pct, s.five_hour_reset_at)
+    });
+    snapshot.seven_day_pct_per_hour = window_slope(history, snapshot.seven_day_reset_at, |s| {
+        (s.seven_day_pct, s.seven_day_reset_at)
+    });
+}
+
+/// Least-squares slope (percent per hour) of the samples in the window ending
+/// at `reset_at`.
+fn window_slope(
+    history: &[QuotaSample],
+    reset_at: Option<u64>,
+    window: impl Fn(&QuotaSample) -> (Option<f64>, Option<u64>),
+) -> Option<f64> {
+    let reset_at = reset_at?;
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .filter_map(|sample| match window(sample) {
+            (Some(pct), Some(reset)) if reset.abs_diff(reset_at) <= SAME_WINDOW_TOLERANCE_MS => {
+                Some((sample.at_ms as f64 / 3_600_000.0, pct))
+            }
+            _ => None,
+        })
+        .collect();
+    let (first, last) = (points.first()?, points.last()?);
+    if (last.0 - first.0) * 3_600_000.0 < MIN_SLOPE_SPAN_MS as f64 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_pct = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let (cov, var) = points.iter().fold((0.0, 0.0), |(cov, var), (t, pct)| {
+        let dt = t - mean_t;
+        (cov + dt * (pct - mean_pct), var + dt * dt)
+    });
+    (var > 0.0).then(|| cov / var)
+}
+
+/// Compute the quota cache file path, keyed by a hash of the Claude config
+/// root so each account (`CLAUDE_CONFIG_DIR`) keeps its own quota.
+pub fn quota_cache_path(claude_dir: Option<&ClaudeConfigDir>) -> PathBuf {
     let mut hasher = DefaultHasher::new();
-    home.hash(&mut hasher);
+    claude_dir.map(|dir| &dir.dir).hash(&mut hasher);
     let hash = hasher.finish();
     std::env::temp_dir().join(format!("cc-pulseline-quota-{hash:x}.json"))
 }
 
 /// Spawn the background quota fetch as a detached child process.
 /// Fire-and-forget — errors are silently ignored.
-pub fn spawn_background_fetch() {
+pub fn spawn_background_fetch(claude_dir: Option<&ClaudeConfigDir>) {
     let exe = match std::env::current_exe() {
         Ok(p) => p,
         Err(_) => return,
     };
 
-    let _ = std::process::Command::new(exe)
-        .arg("--fetch-quota")
+    let mut command = std::process::Command::new(exe);
+    command.arg("--fetch-quota");
+    apply_claude_dir_env(&mut command, claude_dir);
+    let _ = command
         .stdin(std::process::Stdio::null())
         .stdout(std::process::Stdio::null())
         .stderr(std::process::Stdio::null())
         .spawn();
 }
 
+/// Make the `--fetch-quota` child resolve the same config root as this
+/// process (and so write the cache file this process reads): a custom root is
+/// handed over in `CLAUDE_CONFIG_DIR`, and the default root clears it.
+fn apply_claude_dir_env(command: &mut std::process::Command, claude_dir: Option<&ClaudeConfigDir>) {
+    match claude_dir {
+        Some(dir) if dir.is_custom() => {
+            command.env(CONFIG_DIR_ENV, &dir.dir);
+        }
+        Some(_) => {
+            command.env_remove(CONFIG_DIR_ENV);
+        }
+        None => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,16 +344,149 @@ mod tests {
         let cache = QuotaCacheFile {
             fetched_at_ms: 1, // ancient timestamp
             snapshot: snap,
+            history: Vec::new(),
+            retry_at_ms: None,
         };
-        let path = quota_cache_path();
+        // A root of its own, so the test cannot touch a real account's cache
+        let dir = tempfile::TempDir::new().unwrap();
+        let claude_dir = ClaudeConfigDir::custom(dir.path().to_path_buf());
+        let path = quota_cache_path(Some(&claude_dir));
         let contents = serde_json::to_string(&cache).unwrap();
         std::fs::write(&path, contents).unwrap();
 
-        let (result, is_stale) = CachedFileQuotaCollector.collect_quota();
+        let collector = CachedFileQuotaCollector {
+            claude_dir: Some(claude_dir),
+        };
+        let (result, is_stale) = collector.collect_quota();
         assert!(result.terminal, "should preserve terminal flag");
         assert!(!is_stale, "terminal snapshot should never be stale");
 
         // Clean up
         let _ = std::fs::remove_file(&path);
     }
+
+    #[test]
+    fn fetch_child_gets_the_resolved_config_root() {
+        let env_of = |claude_dir: Option<&ClaudeConfigDir>| {
+            let mut command = std::process::Command::new("true");
+            apply_claude_dir_env(&mut command, claude_dir);
+            command
+                .get_envs()
+                .find(|(key, _)| *key == CONFIG_DIR_ENV)
+                .map(|(_, value)| value.map(PathBuf::from))
+        };
+
+        let work = ClaudeConfigDir::custom(PathBuf::from("/home/u/.claude-work"));
+        assert_eq!(env_of(Some(&work)), Some(Some(work.dir.clone())));
+        let default = ClaudeConfigDir::under_home(std::path::Path::new("/home/u"));
+        assert_eq!(env_of(Some(&default)), Some(None), "default root clears it");
+        assert_eq!(env_of(None), None);
+    }
+
+    #[test]
+    fn cache_path_is_keyed_per_config_root() {
+        let work = ClaudeConfigDir::custom(PathBuf::from("/home/u/.claude-work"));
+        let personal = ClaudeConfigDir::under_home(std::path::Path::new("/home/u"));
+        assert_ne!(
+            quota_cache_path(Some(&work)),
+            quota_cache_path(Some(&personal))
+        );
+        assert_eq!(
+            quota_cache_path(Some(&work)),
+            quota_cache_path(Some(&work.clone()))
+        );
+    }
+
+    fn sample_snapshot(five_hour_pct: f64) -> QuotaSnapshot {
+        QuotaSnapshot {
+            five_hour_pct: Some(five_hour_pct),
+            five_hour_reset_at: Some(10 * 3_600_000),
+            seven_day_pct: Some(20.0),
+            seven_day_reset_at: Some(100 * 3_600_000),
+            available: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn record_quota_sample_spaces_samples_and_keeps_latest() {
+        let mut history = Vec::new();
+        let minute = 60_000;
+        for (at, pct) in [
+            (0, 10.0),
+            (minute, 11.0),
+            (3 * minute, 12.0),
+            (6 * minute, 13.0),
+        ] {
+            record_quota_sample(&mut history, &sample_snapshot(pct), at);
+        }
+        let times: Vec<u64> = history.iter().map(|s| s.at_ms / minute).collect();
+        assert_eq!(
+            times,
+            vec![0, 6],
+            "tail replaced until an interval past the first"
+        );
+        record_quota_sample(&mut history, &sample_snapshot(14.0), 7 * minute);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].five_hour_pct, Some(14.0));
+
+        let failed = QuotaSnapshot {
+            error: Some("boom".to_string()),
+            ..sample_snapshot(50.0)
+        };
+        record_quota_sample(&mut history, &failed, 20 * minute);
+        assert_eq!(history.len(), 3, "failed fetches are not sampled");
+
+        // Past the 7d reset every sample belongs to an expired window
+        record_quota_sample(&mut history, &sample_snapshot(1.0), 101 * 3_600_000);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn slopes_use_only_the_current_window() {
+        let hour = 3_600_000;
+        let mut history: Vec<QuotaSample> = (0..3)
+            .map(|h| QuotaSample {
+                at_ms: h * hour,
+                five_hour_pct: Some(10.0 + 15.0 * h as f64),
+                five_hour_reset_at: Some(5 * hour + 500),
+                seven_day_pct: Some(20.0 + h as f64),
+                seven_day_reset_at: Some(100 * hour),
+            })
+            .collect();
+        // A sample from the previous 5h window must not flatten the slope
+        history.insert(
+            0,
+            QuotaSample {
+                at_ms: 0,
+                five_hour_pct: Some(90.0),
+                five_hour_reset_at: Some(0),
+                ..history[0].clone()
+            },
+        );
+
+        let mut snapshot = sample_snapshot(40.0);
+        snapshot.five_hour_reset_at = Some(5 * hour);
+        apply_quota_slopes(&mut snapshot, &history);
+        assert_eq!(snapshot.five_hour_pct_per_hour, Some(15.0));
+        let weekly = snapshot.seven_day_pct_per_hour.unwrap();
+        assert!((weekly - 1.0).abs() < 1e-9, "{weekly}");
+
+        // Too short a span gives no slope
+        apply_quota_slopes(&mut snapshot, &history[..1]);
+        assert_eq!(snapshot.five_hour_pct_per_hour, None);
+    }
+
+    #[test]
+    fn cache_files_with_the_old_backoff_field_still_load() {
+        let cache: QuotaCacheFile = serde_json::from_str(
+            r#"{"fetched_at_ms": 5, "snapshot": {"plan_type": null, "five_hour_pct": 40.0,
+                "five_hour_reset_at": null, "seven_day_pct": null, "seven_day_reset_at": null,
+                "available": true, "error": null},
+                "refresh_backoff": {"failures": 2, "retry_at_ms": 9, "last_error": null}}"#,
+        )
+        .unwrap();
+        assert_eq!(cache.snapshot.five_hour_pct, Some(40.0));
+        assert!(!cache.snapshot.stale);
+    }
 }
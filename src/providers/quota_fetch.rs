@@ -3,46 +3,126 @@
 //! Reads OAuth credentials, calls the Anthropic usage API, and writes
 //! a quota cache file. This module is designed to run as a short-lived
 //! subprocess — it does network I/O and then exits.
+//!
+//! An expired access token is never refreshed here: the refresh token
+//! belongs to Claude Code, and using it would rotate the pair out from under
+//! Claude Code. The fetcher keeps the last good values (marked stale) and
+//! re-reads the credentials on every fetch until Claude Code has refreshed
+//! the token.
 
 use std::{
+    collections::BTreeMap,
     fs,
-    io::Write,
-    path::PathBuf,
-    process::{Command, Stdio},
+    path::{Path, PathBuf},
+    time::Duration,
 };
 
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
 use serde::Deserialize;
 
-use super::quota::{quota_cache_path, QuotaCacheFile, QuotaSnapshot};
+use super::claude_dir::ClaudeConfigDir;
+use super::custom::run_for_output;
+use super::http::{DefaultTransport, HttpRequest, HttpTransport};
+use super::quota::{
+    apply_quota_slopes, quota_cache_path, record_quota_sample, ExtraUsage, ModelQuotaBucket,
+    QuotaCacheFile, QuotaSample, QuotaSnapshot,
+};
+use super::timezone::days_from_civil;
 
 #[cfg(target_os = "macos")]
 const KEYCHAIN_BACKOFF_SECS: u64 = 60;
-const CURL_TIMEOUT_SECS: u64 = 10;
+/// Usage API URL; `segments.quota.endpoint` overrides it.
+pub const DEFAULT_USAGE_ENDPOINT: &str = "https://api.anthropic.com/api/oauth/usage";
+/// Treat a token as expired this long before `expires_at` so the usage call
+/// cannot race it.
+const EXPIRY_MARGIN_MS: u64 = 60_000;
 
 /// Entry point for `--fetch-quota`. Runs synchronously and exits.
 pub fn run_fetch_quota() {
-    let snapshot = match fetch_quota_snapshot() {
-        Ok(s) => s,
-        Err(err) => QuotaSnapshot {
-            error: Some(err),
+    let config = crate::config::load_config();
+    let claude_dir = crate::config::claude_config_dir(&config);
+    let path = quota_cache_path(claude_dir.as_ref());
+    let quota_config = config.segments.quota;
+    let credentials = CredentialOptions::from_config(&quota_config, claude_dir);
+    let endpoint = quota_config
+        .endpoint
+        .unwrap_or_else(|| DEFAULT_USAGE_ENDPOINT.to_string());
+    let previous = fs::read_to_string(&path)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<QuotaCacheFile>(&contents).ok());
+    let now = crate::state::cache::now_epoch_ms();
+
+    // The server asked us to wait (429 Retry-After): keep the cache as is
+    if previous
+        .as_ref()
+        .and_then(|cache| cache.retry_at_ms)
+        .is_some_and(|retry_at| retry_at > now)
+    {
+        return;
+    }
+    let (history, last_snapshot) = previous
+        .map(|cache| (cache.history, Some(cache.snapshot)))
+        .unwrap_or_default();
+
+    let (snapshot, retry_at_ms) =
+        match fetch_quota_snapshot(&DefaultTransport, &endpoint, &credentials, now) {
+            Ok(s) => (s, None),
+            Err(err) => (failed_snapshot(&err, last_snapshot), err.retry_at_ms),
+        };
+    write_quota_cache(&path, snapshot, history, retry_at_ms);
+}
+
+/// Snapshot to cache after a failed fetch. While the access token is expired
+/// the last good values stay visible, marked stale; other failures are
+/// reported on their own.
+fn failed_snapshot(err: &FetchError, last: Option<QuotaSnapshot>) -> QuotaSnapshot {
+    match last {
+        Some(last) if err.token_expired && last.available && !last.terminal => QuotaSnapshot {
+            error: Some(err.message.clone()),
+            stale: true,
+            ..last
+        },
+        _ => QuotaSnapshot {
+            error: Some(err.message.clone()),
             ..Default::default()
         },
-    };
-    write_quota_cache(&snapshot);
+    }
+}
+
+/// A failed fetch. `retry_at_ms` is set when the server asked for a pause
+/// (HTTP 429 with `Retry-After`); `token_expired` when the stored access
+/// token had expired and no request was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchError {
+    pub message: String,
+    pub retry_at_ms: Option<u64>,
+    pub token_expired: bool,
+}
+
+impl From<String> for FetchError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            retry_at_ms: None,
+            token_expired: false,
+        }
+    }
 }
 
-fn fetch_quota_snapshot() -> Result<QuotaSnapshot, String> {
-    let creds = read_credentials()?;
+fn fetch_quota_snapshot(
+    transport: &dyn HttpTransport,
+    endpoint: &str,
+    credentials: &CredentialOptions,
+    now_ms: u64,
+) -> Result<QuotaSnapshot, FetchError> {
+    let (creds, _) = read_credentials(credentials)?;
     let oauth = creds
         .claude_ai_oauth
         .as_ref()
         .ok_or_else(|| "no OAuth credentials found".to_string())?;
 
-    let access_token = oauth
-        .access_token
-        .clone()
-        .ok_or_else(|| "no access token found".to_string())?;
-
     // Skip API users — they don't have subscription quotas.
     // Mark as terminal so the cache never expires and we stop re-fetching.
     if oauth.subscription_type.as_deref() == Some("api") {
@@ -52,12 +132,7 @@ fn fetch_quota_snapshot() -> Result<QuotaSnapshot, String> {
         });
     }
 
-    // Check token expiry
-    if let Some(expires_at) = oauth.expires_at {
-        if crate::state::cache::now_epoch_ms() > expires_at {
-            return Err("access token expired".to_string());
-        }
-    }
+    let access_token = usable_access_token(oauth, now_ms)?;
 
     let plan_type = oauth
         .subscription_type
@@ -65,12 +140,49 @@ fn fetch_quota_snapshot() -> Result<QuotaSnapshot, String> {
         .map(normalize_plan_type)
         .map(String::from);
 
-    let response_json = call_usage_api(&access_token)?;
+    Ok(QuotaSnapshot {
+        plan_type,
+        ..fetch_usage(transport, endpoint, &access_token, now_ms)?
+    })
+}
+
+/// Call the usage API at `endpoint` and convert the response into a
+/// snapshot (without `plan_type`, which comes from the credentials).
+pub fn fetch_usage(
+    transport: &dyn HttpTransport,
+    endpoint: &str,
+    access_token: &str,
+    now_ms: u64,
+) -> Result<QuotaSnapshot, FetchError> {
+    let request = HttpRequest::get(endpoint)
+        .header("anthropic-beta", "oauth-2025-04-20")
+        .header("Authorization", &format!("Bearer {access_token}"));
+    let response = transport.send(&request)?;
+
+    match response.status {
+        200 => {}
+        401 => {
+            return Err("usage API rejected the access token (HTTP 401)"
+                .to_string()
+                .into())
+        }
+        429 => {
+            return Err(FetchError {
+                message: "usage API rate limited (HTTP 429)".to_string(),
+                retry_at_ms: response
+                    .header("Retry-After")
+                    .and_then(|value| parse_retry_after(value, now_ms)),
+                token_expired: false,
+            })
+        }
+        status => return Err(format!("usage API returned HTTP {status}").into()),
+    }
+
     let usage: UsageApiResponse =
-        serde_json::from_str(&response_json).map_err(|e| format!("parse usage response: {e}"))?;
+        serde_json::from_str(&response.body).map_err(|e| format!("parse usage response: {e}"))?;
 
     Ok(QuotaSnapshot {
-        plan_type,
+        plan_type: None,
         five_hour_pct: usage.five_hour.as_ref().map(|h| h.utilization),
         five_hour_reset_at: usage
             .five_hour
@@ -86,9 +198,42 @@ fn fetch_quota_snapshot() -> Result<QuotaSnapshot, String> {
         available: true,
         error: None,
         terminal: false,
+        five_hour_pct_per_hour: None,
+        seven_day_pct_per_hour: None,
+        model_buckets: usage.model_buckets(),
+        extra_usage: usage.extra_usage.map(ExtraUsageResponse::into_extra_usage),
+        stale: false,
     })
 }
 
+/// `Retry-After` as an absolute time: delay seconds or an HTTP date.
+fn parse_retry_after(value: &str, now_ms: u64) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(now_ms.saturating_add(seconds.saturating_mul(1000)));
+    }
+    // IMF-fixdate: "Wed, 21 Oct 2015 07:28:00 GMT"
+    let mut parts = value.split_whitespace().skip(1);
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month_name)? as i64 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let time: Vec<i64> = parts
+        .next()?
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    let [hour, min, sec] = time[..] else {
+        return None;
+    };
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + min * 60 + sec;
+    u64::try_from(secs).ok().map(|secs| secs * 1000)
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
 // ── Credential Reading ──────────────────────────────────────────────
 
 #[derive(Debug, Default, Deserialize)]
@@ -101,26 +246,161 @@ struct Credentials {
 struct OAuthCredentials {
     #[serde(rename = "accessToken")]
     access_token: Option<String>,
-    #[allow(dead_code)]
-    #[serde(rename = "refreshToken")]
-    refresh_token: Option<String>,
     #[serde(rename = "subscriptionType")]
     subscription_type: Option<String>,
     #[serde(rename = "expiresAt")]
     expires_at: Option<u64>,
 }
 
-fn read_credentials() -> Result<Credentials, String> {
-    // Try macOS Keychain first (only on macOS)
+/// Env var holding a bare OAuth access token (as printed by `claude setup-token`).
+pub const OAUTH_TOKEN_ENV: &str = "CLAUDE_CODE_OAUTH_TOKEN";
+const CREDENTIAL_COMMAND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// User-configured credential sources, tried before the default locations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CredentialOptions {
+    /// `segments.quota.credentials_file`, resolved.
+    pub file: Option<PathBuf>,
+    /// `segments.quota.credential_command`: stdout is the credentials JSON.
+    pub command: Option<String>,
+    /// Claude config root holding the default credentials file.
+    pub claude_dir: Option<ClaudeConfigDir>,
+}
+
+impl CredentialOptions {
+    pub fn from_config(
+        quota: &crate::config::QuotaSegmentConfig,
+        claude_dir: Option<ClaudeConfigDir>,
+    ) -> Self {
+        Self {
+            file: quota
+                .credentials_file
+                .as_deref()
+                .map(crate::config::config_relative_path),
+            command: quota.credential_command.clone(),
+            claude_dir,
+        }
+    }
+}
+
+/// Where the credentials in use came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CredentialSource {
+    File(PathBuf),
+    Command(String),
+    Env(&'static str),
+    Keychain,
+    DefaultFile(PathBuf),
+}
+
+impl std::fmt::Display for CredentialSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "credentials_file {}", path.display()),
+            Self::Command(command) => write!(f, "credential_command `{command}`"),
+            Self::Env(name) => write!(f, "env {name}"),
+            Self::Keychain => write!(f, "macOS Keychain"),
+            Self::DefaultFile(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Find usable credentials, for `--check`. Returns the source that succeeded,
+/// or every source's error.
+pub fn check_credentials(options: &CredentialOptions) -> Result<CredentialSource, String> {
+    read_credentials(options).map(|(_, source)| source)
+}
+
+/// Try, in order: the configured file, the configured command,
+/// [`OAUTH_TOKEN_ENV`], the macOS Keychain and `.credentials.json` in the
+/// Claude config root. The first source yielding an OAuth entry wins. The
+/// Keychain entry belongs to the default root, so a custom root skips it.
+fn read_credentials(
+    options: &CredentialOptions,
+) -> Result<(Credentials, CredentialSource), String> {
+    let mut errors = Vec::new();
+    let mut attempt = |source: CredentialSource, result: Result<Credentials, String>| match result {
+        Ok(creds) if creds.claude_ai_oauth.is_some() => Some((creds, source)),
+        Ok(_) => {
+            errors.push(format!("{source}: no OAuth credentials"));
+            None
+        }
+        Err(err) => {
+            errors.push(format!("{source}: {err}"));
+            None
+        }
+    };
+
+    if let Some(path) = &options.file {
+        let result = read_credentials_file(path);
+        if let Some(found) = attempt(CredentialSource::File(path.clone()), result) {
+            return Ok(found);
+        }
+    }
+
+    if let Some(command) = &options.command {
+        let result = run_for_output(command, None, CREDENTIAL_COMMAND_TIMEOUT)
+            .and_then(|output| parse_credentials(&output));
+        if let Some(found) = attempt(CredentialSource::Command(command.clone()), result) {
+            return Ok(found);
+        }
+    }
+
+    if let Some(token) = std::env::var(OAUTH_TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+    {
+        let creds = Credentials {
+            claude_ai_oauth: Some(OAuthCredentials {
+                access_token: Some(token.trim().to_string()),
+                ..Default::default()
+            }),
+        };
+        return Ok((creds, CredentialSource::Env(OAUTH_TOKEN_ENV)));
+    }
+
+    // macOS Keychain (only on macOS)
     #[cfg(target_os = "macos")]
     {
-        if let Some(creds) = try_keychain_credentials() {
-            return Ok(creds);
+        let custom_root = options
+            .claude_dir
+            .as_ref()
+            .is_some_and(ClaudeConfigDir::is_custom);
+        if let Some(creds) = (!custom_root).then(try_keychain_credentials).flatten() {
+            return Ok((creds, CredentialSource::Keychain));
         }
     }
 
-    // Fallback to credentials file
-    try_file_credentials()
+    let default_path = options
+        .claude_dir
+        .as_ref()
+        .map(ClaudeConfigDir::credentials_file)
+        .ok_or_else(|| "cannot determine HOME".to_string())?;
+    let result = read_credentials_file(&default_path);
+    if let Some(found) = attempt(CredentialSource::DefaultFile(default_path), result) {
+        return Ok(found);
+    }
+    Err(errors.join("; "))
+}
+
+/// Parse credentials JSON: Claude Code's `{"claudeAiOauth": {...}}` file format,
+/// or the bare OAuth object (`{"accessToken": ...}`).
+fn parse_credentials(json: &str) -> Result<Credentials, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(json.trim()).map_err(|e| format!("parse credentials: {e}"))?;
+    let creds = if value.get("claudeAiOauth").is_some() {
+        serde_json::from_value(value)
+    } else {
+        serde_json::from_value(value).map(|oauth: OAuthCredentials| Credentials {
+            claude_ai_oauth: Some(oauth),
+        })
+    };
+    creds.map_err(|e| format!("parse credentials: {e}"))
+}
+
+fn read_credentials_file(path: &Path) -> Result<Credentials, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("read credentials: {e}"))?;
+    parse_credentials(&contents)
 }
 
 #[cfg(target_os = "macos")]
@@ -159,58 +439,26 @@ fn try_keychain_credentials() -> Option<Credentials> {
     serde_json::from_str(json_str.trim()).ok()
 }
 
-fn try_file_credentials() -> Result<Credentials, String> {
-    let home = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .map_err(|_| "cannot determine HOME".to_string())?;
-
-    let cred_path = PathBuf::from(&home)
-        .join(".claude")
-        .join(".credentials.json");
-
-    let contents = fs::read_to_string(&cred_path).map_err(|e| format!("read credentials: {e}"))?;
-    serde_json::from_str(&contents).map_err(|e| format!("parse credentials: {e}"))
-}
-
-// ── API Call ────────────────────────────────────────────────────────
-
-fn call_usage_api(access_token: &str) -> Result<String, String> {
-    // Use curl with auth header piped via stdin to avoid credential leakage in ps
-    let mut child = Command::new("curl")
-        .args([
-            "-s",
-            "--max-time",
-            &CURL_TIMEOUT_SECS.to_string(),
-            "-H",
-            "anthropic-beta: oauth-2025-04-20",
-            "-H",
-            "@-", // read additional header from stdin
-            "https://api.anthropic.com/api/oauth/usage",
-        ])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::null())
-        .spawn()
-        .map_err(|e| format!("spawn curl: {e}"))?;
-
-    // Write Bearer token header to stdin
-    if let Some(mut stdin) = child.stdin.take() {
-        let header = format!("Authorization: Bearer {access_token}");
-        stdin
-            .write_all(header.as_bytes())
-            .map_err(|e| format!("write to curl stdin: {e}"))?;
-        // stdin drops here, closing the pipe
-    }
-
-    let output = child
-        .wait_with_output()
-        .map_err(|e| format!("curl wait: {e}"))?;
-
-    if !output.status.success() {
-        return Err(format!("curl exit code: {:?}", output.status.code()));
+// ── Token Expiry ────────────────────────────────────────────────────
+
+/// The access token to call the usage API with. An expired token (or one about
+/// to expire) is an error: Claude Code refreshes it on its next request, and
+/// the next fetch re-reads the credentials.
+fn usable_access_token(oauth: &OAuthCredentials, now_ms: u64) -> Result<String, FetchError> {
+    let expired = oauth
+        .expires_at
+        .is_some_and(|expires_at| now_ms.saturating_add(EXPIRY_MARGIN_MS) > expires_at);
+    if expired {
+        return Err(FetchError {
+            message: "access token expired; waiting for Claude Code to refresh it".to_string(),
+            retry_at_ms: None,
+            token_expired: true,
+        });
     }
-
-    String::from_utf8(output.stdout).map_err(|e| format!("curl output encoding: {e}"))
+    oauth
+        .access_token
+        .clone()
+        .ok_or_else(|| "no access token found".to_string().into())
 }
 
 // ── API Response Parsing ────────────────────────────────────────────
@@ -219,7 +467,55 @@ fn call_usage_api(access_token: &str) -> Result<String, String> {
 struct UsageApiResponse {
     five_hour: Option<UsagePeriod>,
     seven_day: Option<UsagePeriod>,
-    // seven_day_opus is ignored for now
+    extra_usage: Option<ExtraUsageResponse>,
+    /// Model-specific windows arrive as `seven_day_<model>` keys.
+    #[serde(flatten)]
+    other: BTreeMap<String, serde_json::Value>,
+}
+
+/// `seven_day_*` keys that are not a model family.
+const NON_MODEL_BUCKETS: &[&str] = &["oauth_apps"];
+
+impl UsageApiResponse {
+    /// Model-specific weekly windows, sorted by model name. Null entries are skipped.
+    fn model_buckets(&self) -> Vec<ModelQuotaBucket> {
+        self.other
+            .iter()
+            .filter_map(|(key, value)| {
+                let model = key.strip_prefix("seven_day_")?;
+                if NON_MODEL_BUCKETS.contains(&model) {
+                    return None;
+                }
+                let period: UsagePeriod = serde_json::from_value(value.clone()).ok()?;
+                Some(ModelQuotaBucket {
+                    model: model.to_string(),
+                    pct: period.utilization,
+                    reset_at: period.resets_at.as_deref().and_then(parse_iso_to_epoch_ms),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Credit amounts are reported in cents.
+#[derive(Debug, Deserialize)]
+struct ExtraUsageResponse {
+    #[serde(default)]
+    is_enabled: bool,
+    monthly_limit: Option<f64>,
+    used_credits: Option<f64>,
+    utilization: Option<f64>,
+}
+
+impl ExtraUsageResponse {
+    fn into_extra_usage(self) -> ExtraUsage {
+        ExtraUsage {
+            enabled: self.is_enabled,
+            used_usd: self.used_credits.map(|cents| cents / 100.0),
+            limit_usd: self.monthly_limit.map(|cents| cents / 100.0),
+            pct: self.utilization,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -299,23 +595,23 @@ fn parse_iso_to_epoch_ms(iso: &str) -> Option<u64> {
     Some(utc_secs as u64 * 1000)
 }
 
-/// Days from civil date (year, month, day) to Unix epoch.
-/// Algorithm from Howard Hinnant's date library.
-fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
-    let y = if m <= 2 { y - 1 } else { y };
-    let era = if y >= 0 { y } else { y - 399 } / 400;
-    let yoe = (y - era * 400) as u64;
-    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) as u64 + 2) / 5 + d as u64 - 1;
-    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
-    era * 146097 + doe as i64 - 719468
-}
-
 // ── Cache Writing ───────────────────────────────────────────────────
 
-fn write_quota_cache(snapshot: &QuotaSnapshot) {
+fn write_quota_cache(
+    path: &Path,
+    mut snapshot: QuotaSnapshot,
+    mut history: Vec<QuotaSample>,
+    retry_at_ms: Option<u64>,
+) {
+    let now = crate::state::cache::now_epoch_ms();
+    record_quota_sample(&mut history, &snapshot, now);
+    apply_quota_slopes(&mut snapshot, &history);
+
     let cache = QuotaCacheFile {
-        fetched_at_ms: crate::state::cache::now_epoch_ms(),
-        snapshot: snapshot.clone(),
+        fetched_at_ms: now,
+        snapshot,
+        history,
+        retry_at_ms,
     };
 
     let contents = match serde_json::to_string(&cache) {
@@ -323,10 +619,9 @@ fn write_quota_cache(snapshot: &QuotaSnapshot) {
         Err(_) => return,
     };
 
-    let path = quota_cache_path();
     let tmp_path = path.with_extension("tmp");
     if fs::write(&tmp_path, contents).is_ok() {
-        let _ = fs::rename(&tmp_path, &path);
+        let _ = fs::rename(&tmp_path, path);
     }
 }
 
@@ -367,6 +662,43 @@ mod tests {
             Some("2025-11-04T04:59:59Z")
         );
         assert_eq!(response.seven_day.as_ref().unwrap().utilization, 35.0);
+        assert_eq!(
+            response.model_buckets(),
+            vec![ModelQuotaBucket {
+                model: "opus".to_string(),
+                pct: 0.0,
+                reset_at: None,
+            }]
+        );
+        assert!(response.extra_usage.is_none());
+    }
+
+    #[test]
+    fn parse_model_buckets_and_extra_usage() {
+        let json = r#"{
+            "five_hour": {"utilization": 25.0, "resets_at": "2025-11-04T04:59:59Z"},
+            "seven_day": {"utilization": 35.0, "resets_at": "2025-11-06T03:59:59Z"},
+            "seven_day_oauth_apps": null,
+            "seven_day_opus": null,
+            "seven_day_sonnet": {"utilization": 12.0, "resets_at": "2025-11-06T03:59:59Z"},
+            "extra_usage": {"is_enabled": true, "monthly_limit": 5000, "used_credits": 1234, "utilization": 24.68}
+        }"#;
+
+        let response: UsageApiResponse = serde_json::from_str(json).expect("should parse");
+        let buckets = response.model_buckets();
+        assert_eq!(buckets.len(), 1, "null and non-model buckets are skipped");
+        assert_eq!(buckets[0].model, "sonnet");
+        assert_eq!(buckets[0].pct, 12.0);
+        assert_eq!(
+            buckets[0].reset_at,
+            parse_iso_to_epoch_ms("2025-11-06T03:59:59Z")
+        );
+
+        let extra = response.extra_usage.unwrap().into_extra_usage();
+        assert!(extra.enabled);
+        assert_eq!(extra.used_usd, Some(12.34));
+        assert_eq!(extra.limit_usd, Some(50.0));
+        assert_eq!(extra.pct, Some(24.68));
     }
 
     #[test]
@@ -431,4 +763,80 @@ mod tests {
         assert!(parse_iso_to_epoch_ms("not-a-date").is_none());
         assert!(parse_iso_to_epoch_ms("").is_none());
     }
+
+    fn expiring_oauth(expires_at: u64) -> OAuthCredentials {
+        OAuthCredentials {
+            access_token: Some("old".to_string()),
+            subscription_type: Some("claude_max".to_string()),
+            expires_at: Some(expires_at),
+        }
+    }
+
+    #[test]
+    fn valid_token_is_used() {
+        let token = usable_access_token(&expiring_oauth(10_000_000), 0);
+        assert_eq!(token.map_err(|e| e.message).as_deref(), Ok("old"));
+    }
+
+    #[test]
+    fn expired_token_waits_for_claude_code() {
+        let err = usable_access_token(&expiring_oauth(1_000), 2_000).unwrap_err();
+        assert!(err.token_expired);
+        assert!(err.message.contains("waiting for Claude Code"), "{err:?}");
+    }
+
+    #[test]
+    fn expired_token_keeps_the_last_good_values_as_stale() {
+        let expired = usable_access_token(&expiring_oauth(1_000), 2_000).unwrap_err();
+        let last = QuotaSnapshot {
+            plan_type: Some("max".to_string()),
+            five_hour_pct: Some(40.0),
+            available: true,
+            ..Default::default()
+        };
+
+        let kept = failed_snapshot(&expired, Some(last.clone()));
+        assert!(kept.stale && kept.available);
+        assert_eq!(kept.five_hour_pct, Some(40.0));
+        assert_eq!(kept.error, Some(expired.message.clone()));
+
+        // Other failures, or nothing good to keep, report the error alone
+        let network = FetchError::from("connect: refused".to_string());
+        assert!(!failed_snapshot(&network, Some(last)).available);
+        assert!(!failed_snapshot(&expired, None).stale);
+    }
+
+    #[test]
+    fn parse_credentials_accepts_file_and_bare_formats() {
+        let wrapped = parse_credentials(r#"{"claudeAiOauth": {"accessToken": "a"}}"#).unwrap();
+        assert_eq!(
+            wrapped.claude_ai_oauth.unwrap().access_token.as_deref(),
+            Some("a")
+        );
+        let bare = parse_credentials("  {\"accessToken\": \"b\", \"expiresAt\": 5}\n").unwrap();
+        let oauth = bare.claude_ai_oauth.unwrap();
+        assert_eq!(oauth.access_token.as_deref(), Some("b"));
+        assert_eq!(oauth.expires_at, Some(5));
+        assert!(parse_credentials("not json")
+            .unwrap_err()
+            .starts_with("parse credentials:"));
+    }
+
+    #[test]
+    fn configured_file_is_tried_first() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("creds.json");
+        std::fs::write(&path, r#"{"claudeAiOauth": {"accessToken": "from-file"}}"#).unwrap();
+        let options = CredentialOptions {
+            file: Some(path.clone()),
+            command: Some("exit 1".to_string()),
+            claude_dir: None,
+        };
+        let (creds, source) = read_credentials(&options).unwrap();
+        assert_eq!(source, CredentialSource::File(path));
+        assert_eq!(
+            creds.claude_ai_oauth.unwrap().access_token.as_deref(),
+            Some("from-file")
+        );
+    }
 }
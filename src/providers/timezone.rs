@@ -0,0 +1,561 @@
+//! Local timezone resolution without a date crate.
+//!
+//! Reads the TZif data behind `TZ` (`:Europe/Berlin`, a file path or a POSIX
+//! rule such as `CET-1CEST,M3.5.0,M10.5.0/3`) or `/etc/localtime`, and maps
+//! UTC instants to local wall-clock times. Falls back to UTC.
+
+use std::path::Path;
+
+const ZONEINFO_DIRS: &[&str] = &["/usr/share/zoneinfo", "/usr/lib/zoneinfo", "/etc/zoneinfo"];
+
+/// Wall-clock time in a timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    /// Day of the week, 0 = Sunday.
+    pub weekday: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+/// UTC offsets for a zone: explicit transitions plus a POSIX rule for
+/// instants past the last one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeZone {
+    /// (transition time in UTC seconds, offset in seconds from then on)
+    transitions: Vec<(i64, i32)>,
+    /// Offset before the first transition (or always, without transitions).
+    initial_offset: i32,
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    /// UTC, used when no zone data can be found.
+    pub fn utc() -> Self {
+        Self::default()
+    }
+
+    /// The process's local zone, from `TZ` or `/etc/localtime`.
+    pub fn local() -> Self {
+        Self::from_tz(
+            std::env::var("TZ").ok().as_deref(),
+            Path::new("/etc/localtime"),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Resolve a `TZ` value, falling back to the zone file at `localtime`
+    /// when `tz` is unset or empty.
+    pub fn from_tz(tz: Option<&str>, localtime: &Path) -> Option<Self> {
+        let Some(tz) = tz.filter(|tz| !tz.is_empty()) else {
+            return Self::from_file(localtime);
+        };
+        let name = tz.strip_prefix(':').unwrap_or(tz);
+        if name.starts_with('/') {
+            return Self::from_file(Path::new(name));
+        }
+        if !name.contains("..") {
+            if let Some(zone) = ZONEINFO_DIRS
+                .iter()
+                .find_map(|dir| Self::from_file(&Path::new(dir).join(name)))
+            {
+                return Some(zone);
+            }
+        }
+        PosixRule::parse(name).map(Self::from_rule)
+    }
+
+    /// Parse a TZif file.
+    pub fn from_file(path: &Path) -> Option<Self> {
+        Self::from_tzif(&std::fs::read(path).ok()?)
+    }
+
+    /// Parse TZif data (RFC 8536), preferring the 64-bit v2+ block and its
+    /// POSIX footer.
+    pub fn from_tzif(data: &[u8]) -> Option<Self> {
+        let v1 = TzifHeader::parse(data)?;
+        if v1.version < b'2' {
+            return v1.read_block(data.get(44..)?, 4);
+        }
+        let second = data.get(44usize.checked_add(v1.block_len(4)?)?..)?;
+        let v2 = TzifHeader::parse(second)?;
+        let block = second.get(44..)?;
+        let mut zone = v2.read_block(block, 8)?;
+
+        let footer = block.get(v2.block_len(8)?..)?;
+        zone.rule = std::str::from_utf8(footer)
+            .ok()
+            .and_then(|text| text.strip_prefix('\n'))
+            .and_then(|text| text.split('\n').next())
+            .and_then(PosixRule::parse);
+        Some(zone)
+    }
+
+    fn from_rule(rule: PosixRule) -> Self {
+        Self {
+            transitions: Vec::new(),
+            initial_offset: rule.std_offset,
+            rule: Some(rule),
+        }
+    }
+
+    /// Offset from UTC in seconds at `utc_secs`.
+    pub fn offset_at(&self, utc_secs: i64) -> i32 {
+        match self.transitions.partition_point(|&(at, _)| at <= utc_secs) {
+            0 => match (&self.rule, self.transitions.is_empty()) {
+                (Some(rule), true) => rule.offset_at(utc_secs),
+                _ => self.initial_offset,
+            },
+            n if n == self.transitions.len() => self
+                .rule
+                .as_ref()
+                .map_or(self.transitions[n - 1].1, |rule| rule.offset_at(utc_secs)),
+            n => self.transitions[n - 1].1,
+        }
+    }
+
+    /// Local wall-clock time of a UTC instant in milliseconds.
+    pub fn local_time(&self, utc_ms: u64) -> LocalTime {
+        let utc_secs = (utc_ms / 1000) as i64;
+        let local = utc_secs + i64::from(self.offset_at(utc_secs));
+        let days = local.div_euclid(86_400);
+        let secs = local.rem_euclid(86_400);
+        LocalTime {
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u8,
+            hour: (secs / 3600) as u8,
+            minute: (secs % 3600 / 60) as u8,
+        }
+    }
+}
+
+struct TzifHeader {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl TzifHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < 44 || &data[..4] != b"TZif" {
+            return None;
+        }
+        let count = |index: usize| {
+            let at = 20 + index * 4;
+            u32::from_be_bytes(data[at..at + 4].try_into().unwrap()) as usize
+        };
+        Some(Self {
+            version: data[4],
+            isutcnt: count(0),
+            isstdcnt: count(1),
+            leapcnt: count(2),
+            timecnt: count(3),
+            typecnt: count(4),
+            charcnt: count(5),
+        })
+    }
+
+    /// Length of the data block with `time_size`-byte times. None when the
+    /// header's counts overflow, which only a corrupt file can produce.
+    fn block_len(&self, time_size: usize) -> Option<usize> {
+        [
+            self.timecnt.checked_mul(time_size + 1)?,
+            self.typecnt.checked_mul(6)?,
+            self.charcnt,
+            self.leapcnt.checked_mul(time_size + 4)?,
+            self.isstdcnt,
+            self.isutcnt,
+        ]
+        .into_iter()
+        .try_fold(0usize, usize::checked_add)
+    }
+
+    fn read_block(&self, block: &[u8], time_size: usize) -> Option<TimeZone> {
+        if block.len() < self.block_len(time_size)? || self.typecnt == 0 {
+            return None;
+        }
+        let (times, rest) = block.split_at(self.timecnt * time_size);
+        let (indices, rest) = rest.split_at(self.timecnt);
+        let types: Vec<i32> = rest[..self.typecnt * 6]
+            .chunks_exact(6)
+            .map(|info| i32::from_be_bytes(info[..4].try_into().unwrap()))
+            .collect();
+
+        let transitions = times
+            .chunks_exact(time_size)
+            .zip(indices)
+            .map(|(time, &index)| {
+                let at = if time_size == 8 {
+                    i64::from_be_bytes(time.try_into().unwrap())
+                } else {
+                    i64::from(i32::from_be_bytes(time.try_into().unwrap()))
+                };
+                Some((at, *types.get(index as usize)?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(TimeZone {
+            transitions,
+            initial_offset: types[0],
+            rule: None,
+        })
+    }
+}
+
+/// A POSIX TZ rule: `std offset [dst [offset] [,start[/time],end[/time]]]`.
+#[derive(Debug, Clone, PartialEq)]
+struct PosixRule {
+    std_offset: i32,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DstRule {
+    offset: i32,
+    start: (TransitionDate, i32),
+    end: (TransitionDate, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransitionDate {
+    /// `Mm.w.d`: day `d` (0 = Sunday) of week `w` (5 = last) of month `m`.
+    MonthWeekDay(u8, u8, u8),
+    /// `Jn`: day 1-365, never counting February 29.
+    Julian(u16),
+    /// `n`: zero-based day of the year, counting February 29.
+    Zero(u16),
+}
+
+impl PosixRule {
+    fn parse(text: &str) -> Option<Self> {
+        let mut rest = text;
+        parse_name(&mut rest)?;
+        // POSIX offsets are west-positive: `CET-1` is UTC+1
+        let std_offset = -parse_offset(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Self {
+                std_offset,
+                dst: None,
+            });
+        }
+
+        parse_name(&mut rest)?;
+        let dst_offset = if rest.starts_with(',') || rest.is_empty() {
+            std_offset + 3600
+        } else {
+            -parse_offset(&mut rest)?
+        };
+        // Zones with a DST name but no rule use the US default
+        let (start, end) = match rest.strip_prefix(',') {
+            Some(rules) => {
+                let (start, end) = rules.split_once(',')?;
+                (parse_transition(start)?, parse_transition(end)?)
+            }
+            None if rest.is_empty() => (
+                (TransitionDate::MonthWeekDay(3, 2, 0), 7200),
+                (TransitionDate::MonthWeekDay(11, 1, 0), 7200),
+            ),
+            None => return None,
+        };
+        Some(Self {
+            std_offset,
+            dst: Some(DstRule {
+                offset: dst_offset,
+                start,
+                end,
+            }),
+        })
+    }
+
+    fn offset_at(&self, utc_secs: i64) -> i32 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+        let year = civil_from_days((utc_secs + i64::from(self.std_offset)).div_euclid(86_400)).0;
+        // Start is given in standard local time, end in daylight local time
+        let start =
+            dst.start.0.day_in(year) * 86_400 + i64::from(dst.start.1) - i64::from(self.std_offset);
+        let end = dst.end.0.day_in(year) * 86_400 + i64::from(dst.end.1) - i64::from(dst.offset);
+        let in_dst = if start < end {
+            start <= utc_secs && utc_secs < end
+        } else {
+            // Southern hemisphere: DST spans the new year
+            !(end <= utc_secs && utc_secs < start)
+        };
+        if in_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+impl TransitionDate {
+    /// Days since 1970-01-01 of this date in `year`.
+    fn day_in(self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        match self {
+            Self::Julian(n) => {
+                let n = i64::from(n);
+                jan1 + n - 1 + i64::from(n >= 60 && is_leap(year))
+            }
+            Self::Zero(n) => jan1 + i64::from(n),
+            Self::MonthWeekDay(month, week, weekday) => {
+                let first = days_from_civil(year, month.into(), 1);
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (i64::from(weekday) - first_weekday).rem_euclid(7);
+                day += 7 * (i64::from(week) - 1);
+                let next_month = if month == 12 {
+                    days_from_civil(year + 1, 1, 1)
+                } else {
+                    days_from_civil(year, i64::from(month) + 1, 1)
+                };
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        }
+    }
+}
+
+/// Consume a zone abbreviation: letters, or `<...>` quoted.
+fn parse_name(rest: &mut &str) -> Option<()> {
+    let len = if let Some(quoted) = rest.strip_prefix('<') {
+        quoted.find('>')? + 2
+    } else {
+        rest.find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len())
+    };
+    if len < 3 {
+        return None;
+    }
+    *rest = &rest[len..];
+    Some(())
+}
+
+/// Consume `[+-]hh[:mm[:ss]]` and return seconds.
+fn parse_offset(rest: &mut &str) -> Option<i32> {
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, ':' | '+' | '-')))
+        .unwrap_or(rest.len());
+    let (text, tail) = rest.split_at(end);
+    *rest = tail;
+    parse_time(text)
+}
+
+fn parse_time(text: &str) -> Option<i32> {
+    let (sign, digits) = match text.as_bytes().first()? {
+        b'-' => (-1, &text[1..]),
+        b'+' => (1, &text[1..]),
+        _ => (1, text),
+    };
+    let mut seconds = 0;
+    for (part, scale) in digits.split(':').zip([3600, 60, 1]) {
+        seconds += part.parse::<i32>().ok()? * scale;
+    }
+    Some(sign * seconds)
+}
+
+/// Parse `Mm.w.d[/time]`, `Jn[/time]` or `n[/time]` (time defaults to 02:00).
+fn parse_transition(text: &str) -> Option<(TransitionDate, i32)> {
+    let (date, time) = match text.split_once('/') {
+        Some((date, time)) => (date, parse_time(time)?),
+        None => (text, 7200),
+    };
+    let date = if let Some(mwd) = date.strip_prefix('M') {
+        let mut parts = mwd.split('.').map(|p| p.parse::<u8>().ok());
+        let (month, week, weekday) = (parts.next()??, parts.next()??, parts.next()??);
+        if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+            return None;
+        }
+        TransitionDate::MonthWeekDay(month, week, weekday)
+    } else if let Some(n) = date.strip_prefix('J') {
+        TransitionDate::Julian(n.parse().ok().filter(|n| (1..=365).contains(n))?)
+    } else {
+        TransitionDate::Zero(date.parse().ok().filter(|n| *n <= 365)?)
+    };
+    Some((date, time))
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's
+/// algorithm). Shared by the transcript and quota timestamp parsers.
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// (year, month, day) of a day count since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-07-01T12:00:00Z and 2024-01-15T12:00:00Z.
+    const JULY: i64 = 1_719_835_200;
+    const JANUARY: i64 = 1_705_320_000;
+
+    /// Minimal v2 TZif: two transitions plus a footer.
+    fn tzif(footer: &str) -> Vec<u8> {
+        let header = |timecnt: u32, typecnt: u32, charcnt: u32| {
+            let mut h = b"TZif2".to_vec();
+            h.extend([0u8; 15]);
+            for count in [0, 0, 0, timecnt, typecnt, charcnt] {
+                h.extend(count.to_be_bytes());
+            }
+            h
+        };
+        // v1 block: empty except one type
+        let mut data = header(0, 1, 4);
+        data.extend(0i32.to_be_bytes());
+        data.extend([0, 0]);
+        data.extend(b"UTC\0");
+        // v2 block: CET/CEST transitions in 2024
+        data.extend(header(2, 2, 9));
+        data.extend(1_711_846_800i64.to_be_bytes()); // 2024-03-31 01:00 UTC
+        data.extend(1_729_990_800i64.to_be_bytes()); // 2024-10-27 01:00 UTC
+        data.extend([1, 0]);
+        data.extend(3600i32.to_be_bytes());
+        data.extend([0, 0]);
+        data.extend(7200i32.to_be_bytes());
+        data.extend([1, 4]);
+        data.extend(b"CET\0CEST\0");
+        data.extend(format!("\n{footer}\n").bytes());
+        data
+    }
+
+    #[test]
+    fn civil_conversions_round_trip() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2024, 3, 1), 19_783);
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn posix_rule_switches_on_last_sundays() {
+        let zone = TimeZone::from_rule(PosixRule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap());
+        assert_eq!(zone.offset_at(JANUARY), 3600);
+        assert_eq!(zone.offset_at(JULY), 7200);
+        // 2024-03-31 01:00 UTC is 02:00 CET, the switch
+        assert_eq!(zone.offset_at(1_711_846_799), 3600);
+        assert_eq!(zone.offset_at(1_711_846_800), 7200);
+        // 2024-10-27 01:00 UTC is 03:00 CEST, the switch back
+        assert_eq!(zone.offset_at(1_729_990_799), 7200);
+        assert_eq!(zone.offset_at(1_729_990_800), 3600);
+    }
+
+    #[test]
+    fn posix_rule_handles_fixed_and_southern_zones() {
+        let fixed = PosixRule::parse("<+0530>-5:30").unwrap();
+        assert_eq!(fixed.offset_at(JULY), 19_800);
+
+        let sydney = PosixRule::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+        assert_eq!(sydney.offset_at(JANUARY), 39_600);
+        assert_eq!(sydney.offset_at(JULY), 36_000);
+
+        let new_york = PosixRule::parse("EST5EDT").unwrap();
+        assert_eq!(new_york.offset_at(JULY), -14_400);
+        assert_eq!(new_york.offset_at(JANUARY), -18_000);
+
+        assert!(PosixRule::parse("").is_none());
+        assert!(PosixRule::parse("Europe/Nowhere").is_none());
+    }
+
+    #[test]
+    fn tzif_uses_transitions_then_footer() {
+        let zone = TimeZone::from_tzif(&tzif("CET-1CEST,M3.5.0,M10.5.0/3")).unwrap();
+        assert_eq!(zone.offset_at(JANUARY), 3600, "before the first transition");
+        assert_eq!(zone.offset_at(JULY), 7200);
+        // 2025-07-01, past the last transition: footer rule
+        assert_eq!(zone.offset_at(JULY + 365 * 86_400), 7200);
+        assert_eq!(zone.offset_at(JANUARY + 366 * 86_400), 3600);
+        assert!(TimeZone::from_tzif(b"not a zone").is_none());
+    }
+
+    #[test]
+    fn truncated_or_corrupt_tzif_is_rejected() {
+        let data = tzif("CET-1CEST,M3.5.0,M10.5.0/3");
+        // Cut inside the v1 block, so the v2 header offset is past the end
+        assert!(TimeZone::from_tzif(&data[..50]).is_none());
+        // Cut right after the v2 header
+        assert!(TimeZone::from_tzif(&data[..44 + 10 + 44]).is_none());
+
+        // Counts that overflow the block length
+        let mut huge = data.clone();
+        huge[32..36].copy_from_slice(&u32::MAX.to_be_bytes());
+        huge[36..40].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(TimeZone::from_tzif(&huge).is_none());
+    }
+
+    #[test]
+    fn local_time_applies_offset_and_weekday() {
+        let zone = TimeZone::from_rule(PosixRule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap());
+        // 2024-07-01 (Monday) 12:00 UTC is 14:00 CEST
+        assert_eq!(
+            zone.local_time(JULY as u64 * 1000),
+            LocalTime {
+                weekday: 1,
+                hour: 14,
+                minute: 0
+            }
+        );
+        // Sunday 2024-01-14 23:30 UTC is 00:30 Monday in CET
+        let sunday_late = (JANUARY - 86_400 + 11 * 3600 + 1800) as u64 * 1000;
+        assert_eq!(
+            zone.local_time(sunday_late),
+            LocalTime {
+                weekday: 1,
+                hour: 0,
+                minute: 30
+            }
+        );
+    }
+
+    #[test]
+    fn from_tz_reads_files_rules_and_falls_back() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("localtime");
+        std::fs::write(&path, tzif("CET-1CEST,M3.5.0,M10.5.0/3")).unwrap();
+
+        let from_localtime = TimeZone::from_tz(None, &path).unwrap();
+        assert_eq!(from_localtime.offset_at(JULY), 7200);
+        let explicit = format!(":{}", path.display());
+        assert_eq!(
+            TimeZone::from_tz(Some(&explicit), Path::new("/nonexistent")),
+            Some(from_localtime)
+        );
+        let rule = TimeZone::from_tz(Some("JST-9"), &path).unwrap();
+        assert_eq!(rule.offset_at(JULY), 32_400);
+        assert!(TimeZone::from_tz(None, Path::new("/nonexistent")).is_none());
+    }
+}
@@ -59,36 +59,24 @@ fn parse_iso_timestamp(s: &str) -> Option<u64> {
         0
     };
 
-    // Days from Unix epoch using Howard Hinnant's algorithm
-    let days = days_from_civil(year, month, day)?;
-    let secs = days as u64 * 86400 + hour * 3600 + minute * 60 + second;
-    Some(secs * 1000 + millis)
-}
-
-/// Convert a civil date to days since Unix epoch (1970-01-01).
-fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
     if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
         return None;
     }
-    // Shift year so March is month 0 (simplifies leap year handling)
-    let (y, m) = if month <= 2 {
-        (year - 1, (month + 9) as i64)
-    } else {
-        (year, (month - 3) as i64)
-    };
-    let era = if y >= 0 { y } else { y - 399 } / 400;
-    let yoe = (y - era * 400) as u64;
-    let doy = (153 * m as u64 + 2) / 5 + day as u64 - 1;
-    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
-    Some(era * 146097 + doe as i64 - 719468)
+    let days = u64::try_from(days_from_civil(year, month.into(), day.into())).ok()?;
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    Some(secs * 1000 + millis)
 }
 
 use serde_json::Value;
 
+use super::timezone::days_from_civil;
 use crate::{
     config::RenderConfig,
-    state::SessionState,
-    types::{AgentSummary, CompletedToolCount, StdinPayload, TodoSummary, ToolSummary},
+    render::color::{display_width, take_visible_width},
+    state::{cache, SessionState},
+    types::{
+        AgentSummary, CompletedToolCount, SessionPhase, StdinPayload, TodoSummary, ToolSummary,
+    },
 };
 
 #[derive(Debug, Clone, Default)]
@@ -99,7 +87,7 @@ pub struct TranscriptSnapshot {
     pub todo: Option<TodoSummary>,
 }
 
-pub trait TranscriptCollector {
+pub trait TranscriptCollector: Send + Sync {
     fn collect_transcript(
         &self,
         payload: &StdinPayload,
@@ -232,11 +220,19 @@ fn apply_transcript_event(state: &mut SessionState, raw_event: &Value) {
         .and_then(Value::as_str)
         .and_then(parse_iso_timestamp);
 
+    if let Some(phase) = detect_session_phase(raw_event) {
+        state.set_transcript_phase(phase, event_ts.unwrap_or_else(cache::now_epoch_ms));
+    }
+
     // Path 1: Nested content[] blocks (real Claude Code transcript format)
     // Messages have: { "message": { "role": "assistant", "content": [{...}] } }
     // Or:           { "role": "user", "content": [{...}] }
     if let Some(content_blocks) = extract_content_blocks(raw_event) {
         for block in content_blocks {
+            // Hook events already applied this tool exactly; the transcript is the fallback
+            if is_hook_tracked_block(state, block) {
+                continue;
+            }
             apply_content_block(state, block, event_ts);
         }
         // Defense-in-depth: also check toolUseResult for agent completion signal
@@ -261,6 +257,55 @@ fn apply_transcript_event(state: &mut SessionState, raw_event: &Value) {
     apply_flat_event(state, raw_event, event_ts);
 }
 
+/// Session phase implied by a main-thread message: a pending `tool_use` may be
+/// a permission prompt (`SessionState::session_phase` applies a grace period),
+/// tool results and user prompts mean work is underway, and an assistant reply
+/// without tool calls ends the turn.
+fn detect_session_phase(raw_event: &Value) -> Option<SessionPhase> {
+    if raw_event.get("isSidechain").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    if raw_event.get("type").and_then(Value::as_str) == Some("progress") {
+        return Some(SessionPhase::Working);
+    }
+
+    let message = raw_event.get("message").filter(|m| m.is_object());
+    let role = message
+        .and_then(|m| m.get("role"))
+        .or_else(|| raw_event.get("role"))
+        .and_then(Value::as_str)?;
+    let content = message
+        .and_then(|m| m.get("content"))
+        .or_else(|| raw_event.get("content"))?;
+
+    let blocks = match content {
+        Value::String(_) => &[][..],
+        Value::Array(blocks) => blocks.as_slice(),
+        _ => return None,
+    };
+    let has_block = |kind: &str| {
+        blocks
+            .iter()
+            .any(|block| block.get("type").and_then(Value::as_str) == Some(kind))
+    };
+
+    match role {
+        "user" => Some(SessionPhase::Working),
+        "assistant" if has_block("tool_use") => Some(SessionPhase::WaitingPermission),
+        "assistant" => {
+            let stop_reason = message
+                .and_then(|m| m.get("stop_reason"))
+                .and_then(Value::as_str);
+            if stop_reason == Some("tool_use") || (has_block("thinking") && !has_block("text")) {
+                Some(SessionPhase::Working)
+            } else {
+                Some(SessionPhase::Idle)
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Extract content[] blocks from nested transcript events.
 /// Checks both `raw_event.message.content` and `raw_event.content`.
 fn extract_content_blocks(raw_event: &Value) -> Option<Vec<&Value>> {
@@ -286,8 +331,18 @@ fn extract_content_blocks(raw_event: &Value) -> Option<Vec<&Value>> {
     }
 }
 
+/// True when a tool_use/tool_result block refers to a tool_use id that hook
+/// events have already delivered.
+fn is_hook_tracked_block(state: &SessionState, block: &Value) -> bool {
+    block
+        .get("id")
+        .or_else(|| block.get("tool_use_id"))
+        .and_then(Value::as_str)
+        .is_some_and(|id| state.is_hook_tool(id))
+}
+
 /// Process a single content block from a message's content[] array.
-fn apply_content_block(state: &mut SessionState, block: &Value, event_ts: Option<u64>) {
+pub(crate) fn apply_content_block(state: &mut SessionState, block: &Value, event_ts: Option<u64>) {
     let block_type = match block.get("type").and_then(Value::as_str) {
         Some(t) => t,
         None => return,
@@ -348,7 +403,14 @@ fn apply_content_block(state: &mut SessionState, block: &Value, event_ts: Option
 
             // Extract target from input
             let target = extract_target(&name, block);
-            state.upsert_tool(id, name, target);
+            let (file_path, file_line) = extract_file(block).unzip();
+            state.upsert_tool(ToolSummary {
+                id,
+                name,
+                target,
+                file_path,
+                file_line: file_line.flatten(),
+            });
         }
         "tool_result" => {
             if let Some(id) = block.get("tool_use_id").and_then(Value::as_str) {
@@ -537,35 +599,47 @@ fn extract_target(name: &str, block: &Value) -> Option<String> {
     }
 }
 
+/// Absolute `file_path` of a tool's input plus its start line (Read `offset`),
+/// kept whole for hyperlinks since `target` is truncated.
+fn extract_file(block: &Value) -> Option<(String, Option<u64>)> {
+    let input = block.get("input")?;
+    let path = input
+        .get("file_path")
+        .or_else(|| input.get("notebook_path"))
+        .and_then(Value::as_str)
+        .filter(|path| path.starts_with('/'))?;
+    let line = input.get("offset").and_then(Value::as_u64);
+    Some((path.to_string(), line))
+}
+
 /// Truncate a file path for display: show `.../{filename}` if too long.
-fn truncate_path(path: &str, max_chars: usize) -> String {
-    if path.chars().count() <= max_chars {
+fn truncate_path(path: &str, max_width: usize) -> String {
+    if display_width(path) <= max_width {
         return path.to_string();
     }
 
     // Extract filename from path
     if let Some(filename) = path.rsplit('/').next() {
         let prefix = ".../";
-        if filename.chars().count() + prefix.len() <= max_chars {
+        if display_width(filename) + prefix.len() <= max_width {
             return format!("{prefix}{filename}");
         }
-        return truncate_str(filename, max_chars);
+        return truncate_str(filename, max_width);
     }
 
-    truncate_str(path, max_chars)
+    truncate_str(path, max_width)
 }
 
-/// Truncate a string with ellipsis if too long (char-safe).
-fn truncate_str(s: &str, max_chars: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_chars {
+/// Truncate a string to `max_width` columns with ellipsis if too long
+/// (never splits a character or grapheme cluster).
+fn truncate_str(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
         return s.to_string();
     }
-    if max_chars <= 3 {
-        let truncated: String = s.chars().take(max_chars).collect();
-        return truncated;
+    if max_width <= 3 {
+        return take_visible_width(s, max_width);
     }
-    let truncated: String = s.chars().take(max_chars - 3).collect();
+    let truncated = take_visible_width(s, max_width - 3);
     format!("{truncated}...")
 }
 
@@ -625,7 +699,11 @@ fn handle_flat_tool_use(
             let id = find_string(event, &["id", "tool_use_id", "tool_call_id"])
                 .or_else(|| find_string(raw_event, &["id", "tool_use_id", "tool_call_id"]))
                 .unwrap_or_else(|| format!("{name}-active"));
-            state.upsert_tool(id, name, None);
+            state.upsert_tool(ToolSummary {
+                id,
+                name,
+                ..Default::default()
+            });
         }
     }
 }
@@ -763,7 +841,6 @@ fn extract_todo_summary(value: &Value) -> Option<TodoSummary> {
     }
 
     Some(TodoSummary {
-        text: format!("{completed}/{total} done, {pending} pending"),
         pending,
         completed,
         total,
@@ -829,5 +906,25 @@ mod tests {
         let result = truncate_path(path, 15);
         assert!(!result.is_empty());
         assert!(result.chars().count() <= 15);
+        assert!(display_width(&result) <= 15);
+    }
+
+    #[test]
+    fn truncate_str_counts_wide_characters_as_two_columns() {
+        assert_eq!(truncate_str("工作目錄設定", 9), "工作目...");
+        assert_eq!(truncate_str("工作", 4), "工作");
+    }
+
+    #[test]
+    fn extract_file_keeps_full_path_and_offset() {
+        let read = serde_json::json!({"input": {"file_path": "/repo/src/main.rs", "offset": 40}});
+        assert_eq!(
+            extract_file(&read),
+            Some(("/repo/src/main.rs".to_string(), Some(40)))
+        );
+        let relative = serde_json::json!({"input": {"file_path": "src/main.rs"}});
+        assert_eq!(extract_file(&relative), None);
+        let bash = serde_json::json!({"input": {"command": "ls"}});
+        assert_eq!(extract_file(&bash), None);
     }
 }
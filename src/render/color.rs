@@ -1,6 +1,7 @@
 use crate::config::ColorTheme;
 
 pub const RESET: &str = "\x1b[0m";
+pub const BOLD: &str = "\x1b[1m";
 
 // Structural tier (icons, labels, supporting text)
 pub const STRUCTURAL_DARK: &str = "\x1b[38;5;103m"; // Blue-purple, brighter than old 60
@@ -63,6 +64,45 @@ pub struct EmphasisTier {
     pub secondary: &'static str,
     pub structural: &'static str,
     pub separator: &'static str,
+    /// Segment backgrounds in powerline style.
+    pub backgrounds: SegmentBackgrounds,
+}
+
+/// Family of core-line segments that share a powerline background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentGroup {
+    /// Model, output style, version, phase and session duration.
+    Session,
+    /// Project path, git and workspace directories.
+    Workspace,
+    /// CLAUDE.md, rules, memory, hooks, MCP and skills counts.
+    Config,
+    /// Context, tokens, cost, lines changed and API time.
+    Budget,
+    /// `[[segments.custom]]` values and segments registered by embedders.
+    Custom,
+}
+
+/// 256-color background index for each [`SegmentGroup`] in powerline style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentBackgrounds {
+    pub session: u8,
+    pub workspace: u8,
+    pub config: u8,
+    pub budget: u8,
+    pub custom: u8,
+}
+
+impl SegmentBackgrounds {
+    pub fn get(&self, group: SegmentGroup) -> u8 {
+        match group {
+            SegmentGroup::Session => self.session,
+            SegmentGroup::Workspace => self.workspace,
+            SegmentGroup::Config => self.config,
+            SegmentGroup::Budget => self.budget,
+            SegmentGroup::Custom => self.custom,
+        }
+    }
 }
 
 pub fn emphasis_for_theme(theme: ColorTheme) -> EmphasisTier {
@@ -72,16 +112,40 @@ pub fn emphasis_for_theme(theme: ColorTheme) -> EmphasisTier {
             secondary: "\x1b[38;5;146m", // Tokyo Night secondary text
             structural: STRUCTURAL_DARK, // 103 — blue-purple, brighter
             separator: SEPARATOR_DARK,   // 238
+            backgrounds: SegmentBackgrounds {
+                session: 237,
+                workspace: 235,
+                config: 235,
+                budget: 236,
+                custom: 238,
+            },
         },
         ColorTheme::Light => EmphasisTier {
             primary: "\x1b[38;5;234m",    // Revised: was 236, darker for contrast
             secondary: "\x1b[38;5;240m",  // Revised: was 243, wider gap to structural
             structural: STRUCTURAL_LIGHT, // 245 (revised from 247)
             separator: SEPARATOR_LIGHT,   // 252 (revised from 250)
+            backgrounds: SegmentBackgrounds {
+                session: 253,
+                workspace: 255,
+                config: 255,
+                budget: 254,
+                custom: 252,
+            },
         },
     }
 }
 
+/// Foreground escape for a 256-color index.
+pub fn fg_index(index: u8) -> String {
+    format!("\x1b[38;5;{index}m")
+}
+
+/// Background escape for a 256-color index.
+pub fn bg_index(index: u8) -> String {
+    format!("\x1b[48;5;{index}m")
+}
+
 pub fn colorize(text: &str, color: &str, enabled: bool) -> String {
     if enabled {
         format!("{color}{text}{RESET}")
@@ -90,60 +154,339 @@ pub fn colorize(text: &str, color: &str, enabled: bool) -> String {
     }
 }
 
+/// Resolve a user-facing color name (or 256-color index) to an ANSI escape.
+/// Names map onto the palette above so custom segments blend with built-ins.
+pub fn named_color(name: &str) -> Option<String> {
+    let color = match name.trim().to_ascii_lowercase().as_str() {
+        "blue" => STABLE_BLUE,
+        "green" => STABLE_GREEN,
+        "cyan" => ACTIVE_CYAN,
+        "purple" => ACTIVE_PURPLE,
+        "teal" => ACTIVE_TEAL,
+        "amber" | "yellow" => ACTIVE_AMBER,
+        "coral" => ACTIVE_CORAL,
+        "red" => ALERT_RED,
+        "orange" => ALERT_ORANGE,
+        "magenta" => ALERT_MAGENTA,
+        other => {
+            let index: u8 = other.parse().ok()?;
+            return Some(fg_index(index));
+        }
+    };
+    Some(color.to_string())
+}
+
 pub fn strip_ansi(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
-    let mut chars = s.chars().peekable();
-    while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
-            if chars.peek() == Some(&'[') {
-                chars.next();
-                for next in chars.by_ref() {
-                    if next.is_ascii_alphabetic() {
-                        break;
-                    }
-                }
-            }
-        } else {
-            result.push(ch);
-        }
+    let mut rest = s;
+    while let Some(start) = rest.find('\x1b') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = &rest[escape_len(rest)..];
     }
+    result.push_str(rest);
     result
 }
 
+/// Terminal columns taken by `s`, ignoring ANSI escape sequences (SGR colors
+/// and OSC 8 hyperlinks).
 pub fn visible_width(s: &str) -> usize {
-    strip_ansi(s).chars().count()
+    display_width(&strip_ansi(s))
 }
 
-/// Take the first `count` visible characters from a string, preserving ANSI escape sequences.
-pub fn take_visible_chars(s: &str, count: usize) -> String {
+/// Terminal columns taken by plain text (no ANSI escapes).
+///
+/// Text is split into grapheme clusters and each cluster is measured by its
+/// first character: East Asian Wide/Fullwidth and emoji-presentation characters
+/// take two columns, combining marks and other extenders take none. Nerd Font
+/// icons live in the Private Use Area and count as one column, like `wcwidth`.
+/// That holds even for the double-width "Nerd Font" (non-Mono) variants: the
+/// glyph overflows into the next cell, but the terminal still advances the
+/// cursor by one, so counting two would make every icon line come up short.
+pub fn display_width(s: &str) -> usize {
+    clusters(s).map(|(_, width)| width).sum()
+}
+
+/// Take the first `width` visible columns from a string, preserving ANSI escape
+/// sequences. Grapheme clusters are never split, so a wide character that would
+/// straddle the limit is left out and the result may be one column short.
+pub fn take_visible_width(s: &str, width: usize) -> String {
     let mut result = String::new();
     let mut visible = 0;
-    let mut chars = s.chars().peekable();
-
-    while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
-            result.push(ch);
-            if chars.peek() == Some(&'[') {
-                result.push(chars.next().unwrap());
-                for next in chars.by_ref() {
-                    result.push(next);
-                    if next.is_ascii_alphabetic() {
-                        break;
-                    }
-                }
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        if rest.starts_with('\x1b') {
+            let len = escape_len(rest);
+            result.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+
+        let text_end = rest.find('\x1b').unwrap_or(rest.len());
+        for (cluster, cluster_width) in clusters(&rest[..text_end]) {
+            if visible + cluster_width > width {
+                return result;
             }
-        } else {
-            if visible >= count {
+            result.push_str(cluster);
+            visible += cluster_width;
+        }
+        rest = &rest[text_end..];
+    }
+
+    result
+}
+
+/// Take the first `count` visible characters from a string, preserving ANSI
+/// escape sequences.
+#[deprecated(note = "counts terminal columns now; use `take_visible_width`")]
+pub fn take_visible_chars(s: &str, count: usize) -> String {
+    take_visible_width(s, count)
+}
+
+/// Byte length of the escape sequence at the start of `s`: CSI (`ESC [ ... letter`)
+/// or OSC (`ESC ] ...` ended by BEL or `ESC \`, as used by OSC 8 hyperlinks).
+fn escape_len(s: &str) -> usize {
+    let mut chars = s.char_indices().skip(1);
+    match chars.next() {
+        Some((_, '[')) => chars
+            .find(|(_, c)| c.is_ascii_alphabetic())
+            .map_or(s.len(), |(i, c)| i + c.len_utf8()),
+        Some((_, ']')) => {
+            let body = &s[2..];
+            let bel = body.find('\x07').map(|i| i + 1);
+            let st = body.find("\x1b\\").map(|i| i + 2);
+            [bel, st]
+                .into_iter()
+                .flatten()
+                .min()
+                .map_or(s.len(), |end| 2 + end)
+        }
+        _ => 1,
+    }
+}
+
+const ZWJ: char = '\u{200d}';
+const VS_TEXT: char = '\u{fe0e}';
+const VS_EMOJI: char = '\u{fe0f}';
+
+/// Split plain text into grapheme clusters with their display width.
+///
+/// A simplified form of UAX #29: a base character absorbs following combining
+/// marks, variation selectors, emoji modifiers, tags and ZWJ-joined characters;
+/// two regional indicators form one flag.
+fn clusters(s: &str) -> impl Iterator<Item = (&str, usize)> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let mut chars = rest.char_indices();
+        let (_, base) = chars.next()?;
+        let mut width = char_width(base);
+        let mut end = base.len_utf8();
+        let mut after_zwj = false;
+        let mut pending_flag = is_regional_indicator(base);
+
+        for (index, c) in chars {
+            if after_zwj {
+                after_zwj = false;
+            } else if c == ZWJ {
+                after_zwj = true;
+            } else if c == VS_EMOJI {
+                width = width.max(2);
+            } else if c == VS_TEXT {
+                width = width.min(1);
+            } else if pending_flag && is_regional_indicator(c) {
+                pending_flag = false;
+                width = 2;
+            } else if !is_extender(c) {
                 break;
             }
-            result.push(ch);
-            visible += 1;
+            end = index + c.len_utf8();
         }
+
+        let (cluster, tail) = rest.split_at(end);
+        rest = tail;
+        Some((cluster, width))
+    })
+}
+
+/// Display width of a single character: 0, 1 or 2 columns.
+pub fn char_width(c: char) -> usize {
+    if c.is_control() || in_ranges(c, ZERO_WIDTH) {
+        0
+    } else if in_ranges(c, WIDE) {
+        2
+    } else {
+        1
     }
+}
 
-    result
+fn is_extender(c: char) -> bool {
+    in_ranges(c, ZERO_WIDTH) || ('\u{1f3fb}'..='\u{1f3ff}').contains(&c)
 }
 
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+fn in_ranges(c: char, ranges: &[(u32, u32)]) -> bool {
+    let code = c as u32;
+    ranges
+        .binary_search_by(|&(lo, hi)| {
+            if hi < code {
+                std::cmp::Ordering::Less
+            } else if lo > code {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Combining marks, format characters and other zero-width code points.
+const ZERO_WIDTH: &[(u32, u32)] = &[
+    (0x00AD, 0x00AD),
+    (0x0300, 0x036F),
+    (0x0483, 0x0489),
+    (0x0591, 0x05BD),
+    (0x05BF, 0x05BF),
+    (0x05C1, 0x05C2),
+    (0x05C4, 0x05C5),
+    (0x05C7, 0x05C7),
+    (0x0610, 0x061A),
+    (0x064B, 0x065F),
+    (0x0670, 0x0670),
+    (0x06D6, 0x06DC),
+    (0x06DF, 0x06E4),
+    (0x06E7, 0x06E8),
+    (0x06EA, 0x06ED),
+    (0x0711, 0x0711),
+    (0x0730, 0x074A),
+    (0x07A6, 0x07B0),
+    (0x0900, 0x0902),
+    (0x093A, 0x093A),
+    (0x093C, 0x093C),
+    (0x0941, 0x0948),
+    (0x094D, 0x094D),
+    (0x0951, 0x0957),
+    (0x0962, 0x0963),
+    (0x0E31, 0x0E31),
+    (0x0E34, 0x0E3A),
+    (0x0E47, 0x0E4E),
+    (0x1160, 0x11FF),
+    (0x1AB0, 0x1AFF),
+    (0x1DC0, 0x1DFF),
+    (0x200B, 0x200F),
+    (0x2028, 0x202E),
+    (0x2060, 0x2064),
+    (0x20D0, 0x20FF),
+    (0x302A, 0x302D),
+    (0x3099, 0x309A),
+    (0xD7B0, 0xD7FF),
+    (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F),
+    (0xFEFF, 0xFEFF),
+    (0xE0001, 0xE0001),
+    (0xE0020, 0xE007F),
+    (0xE0100, 0xE01EF),
+];
+
+/// East Asian Wide/Fullwidth blocks and default emoji-presentation characters.
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115F),
+    (0x231A, 0x231B),
+    (0x2329, 0x232A),
+    (0x23E9, 0x23EC),
+    (0x23F0, 0x23F0),
+    (0x23F3, 0x23F3),
+    (0x25FD, 0x25FE),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267F, 0x267F),
+    (0x2693, 0x2693),
+    (0x26A1, 0x26A1),
+    (0x26AA, 0x26AB),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26CE, 0x26CE),
+    (0x26D4, 0x26D4),
+    (0x26EA, 0x26EA),
+    (0x26F2, 0x26F3),
+    (0x26F5, 0x26F5),
+    (0x26FA, 0x26FA),
+    (0x26FD, 0x26FD),
+    (0x2705, 0x2705),
+    (0x270A, 0x270B),
+    (0x2728, 0x2728),
+    (0x274C, 0x274C),
+    (0x274E, 0x274E),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27B0, 0x27B0),
+    (0x27BF, 0x27BF),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55),
+    (0x2E80, 0x303E),
+    (0x3041, 0x33FF),
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xA000, 0xA4CF),
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF),
+    (0xFE10, 0xFE19),
+    (0xFE30, 0xFE6F),
+    (0xFF00, 0xFF60),
+    (0xFFE0, 0xFFE6),
+    (0x16FE0, 0x16FE4),
+    (0x17000, 0x18CFF),
+    (0x1B000, 0x1B2FF),
+    (0x1F004, 0x1F004),
+    (0x1F0CF, 0x1F0CF),
+    (0x1F18E, 0x1F18E),
+    (0x1F191, 0x1F19A),
+    (0x1F200, 0x1F202),
+    (0x1F210, 0x1F23B),
+    (0x1F240, 0x1F248),
+    (0x1F250, 0x1F251),
+    (0x1F260, 0x1F265),
+    (0x1F300, 0x1F320),
+    (0x1F32D, 0x1F335),
+    (0x1F337, 0x1F37C),
+    (0x1F37E, 0x1F393),
+    (0x1F3A0, 0x1F3CA),
+    (0x1F3CF, 0x1F3D3),
+    (0x1F3E0, 0x1F3F0),
+    (0x1F3F4, 0x1F3F4),
+    (0x1F3F8, 0x1F43E),
+    (0x1F440, 0x1F440),
+    (0x1F442, 0x1F4FC),
+    (0x1F4FF, 0x1F53D),
+    (0x1F54B, 0x1F54E),
+    (0x1F550, 0x1F567),
+    (0x1F57A, 0x1F57A),
+    (0x1F595, 0x1F596),
+    (0x1F5A4, 0x1F5A4),
+    (0x1F5FB, 0x1F64F),
+    (0x1F680, 0x1F6C5),
+    (0x1F6CC, 0x1F6CC),
+    (0x1F6D0, 0x1F6D2),
+    (0x1F6D5, 0x1F6D7),
+    (0x1F6DC, 0x1F6DF),
+    (0x1F6EB, 0x1F6EC),
+    (0x1F6F4, 0x1F6FC),
+    (0x1F7E0, 0x1F7EB),
+    (0x1F7F0, 0x1F7F0),
+    (0x1F90C, 0x1F93A),
+    (0x1F93C, 0x1F945),
+    (0x1F947, 0x1F9FF),
+    (0x1FA70, 0x1FAFF),
+    (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -177,16 +520,32 @@ mod tests {
     }
 
     #[test]
-    fn take_visible_chars_preserves_ansi() {
+    fn visible_width_ignores_osc8_hyperlinks() {
+        let st = "\x1b]8;;file:///a/b.rs\x1b\\b.rs\x1b]8;;\x1b\\";
+        let bel = "\x1b]8;;https://x.dev/\x07x.dev\x1b]8;;\x07";
+        assert_eq!(visible_width(st), 4);
+        assert_eq!(strip_ansi(bel), "x.dev");
+        assert_eq!(take_visible_width(st, 2), "\x1b]8;;file:///a/b.rs\x1b\\b.");
+    }
+
+    #[test]
+    fn take_visible_width_preserves_ansi() {
         let colored = format!("{CTX_CRITICAL}hello{RESET}");
-        let taken = take_visible_chars(&colored, 3);
+        let taken = take_visible_width(&colored, 3);
         assert_eq!(visible_width(&taken), 3);
         assert!(taken.contains(CTX_CRITICAL));
     }
 
     #[test]
-    fn take_visible_chars_plain_text() {
+    #[allow(deprecated)]
+    fn take_visible_chars_forwards_to_width() {
         assert_eq!(take_visible_chars("hello world", 5), "hello");
+        assert_eq!(take_visible_chars("日本語", 4), "日本");
+    }
+
+    #[test]
+    fn take_visible_width_plain_text() {
+        assert_eq!(take_visible_width("hello world", 5), "hello");
     }
 
     #[test]
@@ -243,4 +602,39 @@ mod tests {
             "completed check should use steel blue (67)"
         );
     }
+
+    #[test]
+    fn cjk_characters_take_two_columns() {
+        assert_eq!(display_width("專案"), 4);
+        assert_eq!(display_width("~/工作/api"), 10);
+        assert_eq!(display_width("ｶﾀｶﾅ"), 4); // halfwidth katakana stay narrow
+        assert_eq!(display_width("한국어"), 6);
+    }
+
+    #[test]
+    fn emoji_and_clusters_measure_as_rendered() {
+        assert_eq!(display_width("🚀"), 2);
+        assert_eq!(display_width("⚡"), 2);
+        assert_eq!(display_width("❤️"), 2); // text-default heart + VS16
+        assert_eq!(display_width("👍🏽"), 2); // skin tone modifier
+        assert_eq!(display_width("👩‍💻"), 2); // ZWJ sequence
+        assert_eq!(display_width("🇹🇼"), 2); // flag
+        assert_eq!(display_width("e\u{301}"), 1); // e + combining acute
+        assert_eq!(display_width("✓ ↳ ▰"), 5);
+    }
+
+    #[test]
+    fn nerd_font_icons_take_one_column() {
+        assert_eq!(display_width("\u{f0ad}"), 1);
+        assert_eq!(display_width("\u{f024b} "), 2);
+    }
+
+    #[test]
+    fn take_visible_width_never_splits_wide_clusters() {
+        let colored = format!("{STABLE_BLUE}專案目錄{RESET}");
+        let taken = take_visible_width(&colored, 5);
+        assert_eq!(strip_ansi(&taken), "專案");
+        assert_eq!(take_visible_width("a👩‍💻b", 3), "a👩‍💻");
+        assert_eq!(take_visible_width("a👩‍💻b", 2), "a");
+    }
 }
@@ -1,3 +1,5 @@
+use crate::config::BarStyle;
+
 pub fn format_number(n: u64) -> String {
     if n >= 1_000_000 {
         format!("{:.1}M", n as f64 / 1_000_000.0)
@@ -42,6 +44,117 @@ pub fn format_speed(toks_per_sec: f64) -> String {
     }
 }
 
+/// How token counts, speeds and money are written.
+///
+/// Money is converted from USD at `currency_rate` (display units per USD).
+/// Compact numbers use `k`/`M` suffixes; full numbers are grouped with
+/// `thousands_separator`.
+#[derive(Debug, Clone)]
+pub struct NumberFormat {
+    pub decimal_separator: String,
+    pub thousands_separator: String,
+    pub compact: bool,
+    pub currency_symbol: String,
+    pub currency_rate: f64,
+    /// Write `12,50 €` instead of `€12,50`.
+    pub symbol_after: bool,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: ".".to_string(),
+            thousands_separator: String::new(),
+            compact: true,
+            currency_symbol: "$".to_string(),
+            currency_rate: 1.0,
+            symbol_after: false,
+        }
+    }
+}
+
+// The rate is compared by bits so RenderConfig can stay `Eq + Hash`.
+impl PartialEq for NumberFormat {
+    fn eq(&self, other: &Self) -> bool {
+        self.decimal_separator == other.decimal_separator
+            && self.thousands_separator == other.thousands_separator
+            && self.compact == other.compact
+            && self.currency_symbol == other.currency_symbol
+            && self.currency_rate.to_bits() == other.currency_rate.to_bits()
+            && self.symbol_after == other.symbol_after
+    }
+}
+
+impl Eq for NumberFormat {}
+
+impl std::hash::Hash for NumberFormat {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.decimal_separator.hash(state);
+        self.thousands_separator.hash(state);
+        self.compact.hash(state);
+        self.currency_symbol.hash(state);
+        self.currency_rate.to_bits().hash(state);
+        self.symbol_after.hash(state);
+    }
+}
+
+impl NumberFormat {
+    /// A count: `20.0k` compact, `20,000` full.
+    pub fn number(&self, n: u64) -> String {
+        if self.compact {
+            self.with_decimal(&format_number(n))
+        } else {
+            self.group(&n.to_string())
+        }
+    }
+
+    /// Output speed: `↗1.5K/s` compact, `↗1,500/s` full.
+    pub fn speed(&self, toks_per_sec: f64) -> String {
+        if self.compact {
+            self.with_decimal(&format_speed(toks_per_sec))
+        } else {
+            format!("↗{}/s", self.group(&format!("{toks_per_sec:.0}")))
+        }
+    }
+
+    /// A USD amount in the display currency with two decimals: `$1,234.50`, `1.234,50 €`.
+    pub fn money(&self, usd: f64) -> String {
+        let amount = usd * self.currency_rate;
+        let fixed = format!("{:.2}", amount.abs());
+        let (int, frac) = fixed.split_once('.').unwrap_or((&fixed, "00"));
+        let sign = if amount < 0.0 && fixed != "0.00" {
+            "-"
+        } else {
+            ""
+        };
+        let value = format!("{sign}{}{}{frac}", self.group(int), self.decimal_separator);
+        if self.symbol_after {
+            format!("{value} {}", self.currency_symbol)
+        } else {
+            format!("{}{value}", self.currency_symbol)
+        }
+    }
+
+    fn with_decimal(&self, s: &str) -> String {
+        s.replace('.', &self.decimal_separator)
+    }
+
+    /// Insert the thousands separator into a string of ASCII digits.
+    fn group(&self, digits: &str) -> String {
+        if self.thousands_separator.is_empty() {
+            return digits.to_string();
+        }
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, ch) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push_str(&self.thousands_separator);
+            }
+            grouped.push(ch);
+        }
+        grouped
+    }
+}
+
 /// Format elapsed seconds for agent/todo display.
 pub fn format_agent_elapsed(secs: u64) -> String {
     if secs == 0 {
@@ -77,6 +190,82 @@ pub fn format_reset_duration(minutes: u64) -> String {
     }
 }
 
+/// One cell of a progress bar, tagged by role so the caller can color it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarCell {
+    Filled(char),
+    Empty(char),
+    Marker(char),
+}
+
+/// Lay out a `width`-cell bar filled to `pct` (0-100, clamped). `marker_pct`
+/// replaces the cell containing that point (e.g. the auto-compact threshold).
+pub fn bar_cells(pct: f64, width: usize, style: BarStyle, marker_pct: Option<f64>) -> Vec<BarCell> {
+    let ratio = (pct / 100.0).clamp(0.0, 1.0);
+    let mut cells: Vec<BarCell> = match style {
+        BarStyle::Blocks | BarStyle::Ascii => {
+            let (full, empty) = if style == BarStyle::Blocks {
+                ('▰', '▱')
+            } else {
+                ('#', '-')
+            };
+            let filled = (ratio * width as f64).round() as usize;
+            (0..width)
+                .map(|i| {
+                    if i < filled {
+                        BarCell::Filled(full)
+                    } else {
+                        BarCell::Empty(empty)
+                    }
+                })
+                .collect()
+        }
+        BarStyle::Eighths => {
+            const PARTIALS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+            let eighths = (ratio * width as f64 * 8.0).round() as usize;
+            (0..width)
+                .map(|i| match eighths.saturating_sub(i * 8) {
+                    0 => BarCell::Empty('░'),
+                    n if n >= 8 => BarCell::Filled('█'),
+                    n => BarCell::Filled(PARTIALS[n - 1]),
+                })
+                .collect()
+        }
+    };
+
+    if let Some(marker) = marker_pct.filter(|_| width > 0) {
+        let index = ((marker / 100.0).clamp(0.0, 1.0) * width as f64) as usize;
+        let glyph = if style == BarStyle::Ascii { '|' } else { '┃' };
+        cells[index.min(width - 1)] = BarCell::Marker(glyph);
+    }
+    cells
+}
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Render `values` as a sparkline scaled from 0 to `max` (the series maximum
+/// when None). Returns None for fewer than two samples — one point is no trend.
+pub fn sparkline(values: &[f64], max: Option<f64>) -> Option<String> {
+    if values.len() < 2 {
+        return None;
+    }
+    let max = max.unwrap_or_else(|| values.iter().copied().fold(0.0, f64::max));
+    let top = (SPARK_LEVELS.len() - 1) as f64;
+    Some(
+        values
+            .iter()
+            .map(|&v| {
+                let ratio = if max > 0.0 {
+                    (v / max).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                SPARK_LEVELS[(ratio * top).round() as usize]
+            })
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,4 +407,101 @@ mod tests {
         assert_eq!(format_reset_duration(10080), "7d 0h 0m");
         assert_eq!(format_reset_duration(10140), "7d 1h 0m");
     }
+
+    #[test]
+    fn bar_cells_blocks_round_to_nearest_cell() {
+        let cells = bar_cells(44.0, 10, BarStyle::Blocks, None);
+        let text: String = cells
+            .iter()
+            .map(|c| match c {
+                BarCell::Filled(ch) | BarCell::Empty(ch) | BarCell::Marker(ch) => *ch,
+            })
+            .collect();
+        assert_eq!(text, "▰▰▰▰▱▱▱▱▱▱");
+    }
+
+    #[test]
+    fn bar_cells_eighths_show_partial_cell() {
+        // 45% of 4 cells = 14.4 eighths → 1 full cell + 6/8 (14 rounded)
+        let cells = bar_cells(45.0, 4, BarStyle::Eighths, None);
+        assert_eq!(
+            cells,
+            vec![
+                BarCell::Filled('█'),
+                BarCell::Filled('▊'),
+                BarCell::Empty('░'),
+                BarCell::Empty('░'),
+            ]
+        );
+    }
+
+    #[test]
+    fn bar_cells_place_marker_and_clamp() {
+        let cells = bar_cells(150.0, 10, BarStyle::Ascii, Some(80.0));
+        assert_eq!(cells[8], BarCell::Marker('|'));
+        assert!(cells[..8].iter().all(|c| *c == BarCell::Filled('#')));
+        assert_eq!(
+            bar_cells(50.0, 5, BarStyle::Ascii, Some(100.0))[4],
+            BarCell::Marker('|')
+        );
+        assert!(bar_cells(50.0, 0, BarStyle::Blocks, Some(80.0)).is_empty());
+    }
+
+    #[test]
+    fn sparkline_scales_to_max() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0], Some(100.0)).unwrap(), "▁▅█");
+        assert_eq!(sparkline(&[1.0, 2.0, 4.0], None).unwrap(), "▃▅█");
+        assert_eq!(sparkline(&[150.0, -5.0], Some(100.0)).unwrap(), "█▁");
+    }
+
+    #[test]
+    fn sparkline_needs_two_samples() {
+        assert_eq!(sparkline(&[], None), None);
+        assert_eq!(sparkline(&[3.0], None), None);
+        assert_eq!(sparkline(&[0.0, 0.0], None).unwrap(), "▁▁");
+    }
+
+    fn euro_format() -> NumberFormat {
+        NumberFormat {
+            decimal_separator: ",".to_string(),
+            thousands_separator: ".".to_string(),
+            compact: false,
+            currency_symbol: "€".to_string(),
+            currency_rate: 0.5,
+            symbol_after: true,
+        }
+    }
+
+    #[test]
+    fn number_format_default_matches_plain_helpers() {
+        let format = NumberFormat::default();
+        assert_eq!(format.number(20_000), format_number(20_000));
+        assert_eq!(format.speed(1_500.0), format_speed(1_500.0));
+        assert_eq!(format.money(3.5), "$3.50");
+        assert_eq!(format.money(1234.5), "$1234.50");
+    }
+
+    #[test]
+    fn number_format_groups_and_converts() {
+        let format = euro_format();
+        assert_eq!(format.number(1_234_567), "1.234.567");
+        assert_eq!(format.number(999), "999");
+        assert_eq!(format.speed(1_500.0), "↗1.500/s");
+        assert_eq!(format.money(2469.0), "1.234,50 €");
+        assert_eq!(format.money(-0.001), "0,00 €");
+    }
+
+    #[test]
+    fn compact_numbers_use_the_decimal_separator() {
+        let format = NumberFormat {
+            compact: true,
+            symbol_after: false,
+            currency_symbol: "£".to_string(),
+            currency_rate: 1.0,
+            ..euro_format()
+        };
+        assert_eq!(format.number(20_000), "20,0k");
+        assert_eq!(format.speed(1_500.0), "↗1,5K/s");
+        assert_eq!(format.money(12.345), "£12,35");
+    }
 }
@@ -0,0 +1,96 @@
+//! OSC 8 hyperlinks: `ESC ] 8 ; ; URL ESC \ text ESC ] 8 ; ; ESC \`.
+//!
+//! Terminals without OSC 8 support print the text and drop the sequences;
+//! `visible_width` skips them, so links never change width budgeting.
+
+/// Closes the open hyperlink. Harmless when no link is open.
+pub const LINK_END: &str = "\x1b]8;;\x1b\\";
+
+/// Wrap `text` in an OSC 8 hyperlink to `url`.
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}{LINK_END}")
+}
+
+/// `file://` URL for an absolute path.
+pub fn file_url(path: &str) -> String {
+    format!("file://{}", percent_encode(path))
+}
+
+/// URL for a file under an editor template such as `vscode://file{path}:{line}`.
+/// `{path}` is the percent-encoded absolute path; `{line}` defaults to 1.
+pub fn editor_url(template: &str, path: &str, line: Option<u64>) -> String {
+    template
+        .replace("{path}", &percent_encode(path))
+        .replace("{line}", &line.unwrap_or(1).max(1).to_string())
+}
+
+/// Web URL for `branch` under a repository web URL, using the host's layout:
+/// GitLab `/-/tree/`, Bitbucket `/branch/`, otherwise GitHub-style `/tree/`.
+pub fn branch_url(repo_url: &str, branch: &str) -> String {
+    let path = if repo_url.contains("gitlab") {
+        "-/tree"
+    } else if repo_url.contains("bitbucket") {
+        "branch"
+    } else {
+        "tree"
+    };
+    format!("{repo_url}/{path}/{}", percent_encode(branch))
+}
+
+/// Percent-encode everything but unreserved characters and `/`.
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::color::visible_width;
+
+    #[test]
+    fn hyperlink_has_zero_width_escapes() {
+        let link = hyperlink("main.rs", "file:///repo/main.rs");
+        assert_eq!(
+            link,
+            "\x1b]8;;file:///repo/main.rs\x1b\\main.rs\x1b]8;;\x1b\\"
+        );
+        assert_eq!(visible_width(&link), 7);
+    }
+
+    #[test]
+    fn file_and_editor_urls_encode_paths() {
+        assert_eq!(file_url("/my repo/a.rs"), "file:///my%20repo/a.rs");
+        assert_eq!(
+            editor_url("vscode://file{path}:{line}", "/repo/ä.rs", Some(42)),
+            "vscode://file/repo/%C3%A4.rs:42"
+        );
+        assert_eq!(
+            editor_url("idea://open?file={path}&line={line}", "/repo/a.rs", None),
+            "idea://open?file=/repo/a.rs&line=1"
+        );
+    }
+
+    #[test]
+    fn branch_url_follows_host_layout() {
+        assert_eq!(
+            branch_url("https://github.com/o/r", "feat/x"),
+            "https://github.com/o/r/tree/feat/x"
+        );
+        assert_eq!(
+            branch_url("https://gitlab.com/o/r", "main"),
+            "https://gitlab.com/o/r/-/tree/main"
+        );
+        assert_eq!(
+            branch_url("https://bitbucket.org/o/r", "fix#1"),
+            "https://bitbucket.org/o/r/branch/fix%231"
+        );
+    }
+}
@@ -19,6 +19,17 @@ pub const ICON_AGENT_DONE: &str = "✓";
 pub const ICON_TODO: &str = "\u{f0c8}";
 pub const ICON_QUOTA: &str = "\u{f080}"; // nf-fa-bar_chart (usage/quota)
 pub const ICON_SPEED: &str = "\u{f0e4}"; // nf-fa-tachometer (output speed)
+pub const ICON_LINES: &str = "\u{f440}"; // nf-oct-diff (lines changed)
+pub const ICON_API_TIME: &str = "\u{f0ec}"; // nf-fa-exchange (API round-trips)
+pub const ICON_PHASE_WORKING: &str = "\u{f110}"; // nf-fa-spinner
+pub const ICON_PHASE_WAITING: &str = "\u{f256}"; // nf-fa-hand_paper_o (needs you)
+pub const ICON_PHASE_IDLE: &str = "\u{f186}"; // nf-fa-moon_o
+
+// Powerline separators (line_style = powerline / powerline-round)
+pub const ICON_POWERLINE_ARROW: &str = "\u{e0b0}"; // nf-pl-left_hard_divider
+pub const ICON_POWERLINE_ROUND: &str = "\u{e0b4}"; // nf-ple-right_half_circle_thick
+pub const ICON_POWERLINE_ARROW_THIN: &str = "\u{e0b1}"; // nf-pl-left_soft_divider
+pub const ICON_POWERLINE_ROUND_THIN: &str = "\u{e0b5}"; // nf-ple-right_half_circle_thin
 
 // Token type icons
 pub const ICON_TOKEN_INPUT: &str = "\u{f093}";
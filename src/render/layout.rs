@@ -1,21 +1,31 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    cmp::Reverse,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
-    config::{RenderConfig, WidthDegradeStrategy},
-    types::{AgentSummary, Line1Metrics, Line3Metrics, QuotaMetrics, RenderFrame, TodoSummary},
+    config::{BarStyle, GlyphMode, LineStyle, RenderConfig, WidthDegradeStrategy},
+    providers::{quota::ExtraUsage, timezone::LocalTime},
+    types::{
+        AgentSummary, Line1Metrics, Line3Metrics, PhaseSignal, QuotaMetrics, QuotaProjection,
+        RenderFrame, SessionPhase, TodoSummary, WorkspaceDirStatus,
+    },
 };
 
 use super::color::{
-    colorize, emphasis_for_theme, take_visible_chars, visible_width, EmphasisTier, AGENT_PURPLE,
-    COMPLETED_CHECK, COST_BASE, COST_HIGH_RATE, COST_LOW_RATE, COST_MED_RATE, CTX_CRITICAL,
-    CTX_GOOD, CTX_WARN, GIT_ADDED, GIT_AHEAD, GIT_BEHIND, GIT_DELETED, GIT_GREEN, GIT_MODIFIED,
-    INDICATOR_CLAUDE_MD, INDICATOR_DURATION, INDICATOR_HOOKS, INDICATOR_MCP, INDICATOR_MEMORY,
-    INDICATOR_RULES, INDICATOR_SKILLS, RESET, STABLE_BLUE, TODO_TEAL, TOOL_BLUE,
+    bg_index, colorize, display_width, emphasis_for_theme, fg_index, named_color,
+    take_visible_width, visible_width, EmphasisTier, SegmentGroup, ACTIVE_AMBER, AGENT_PURPLE,
+    ALERT_RED, BOLD, COMPLETED_CHECK, COST_BASE, COST_HIGH_RATE, COST_LOW_RATE, COST_MED_RATE,
+    CTX_CRITICAL, CTX_GOOD, CTX_WARN, GIT_ADDED, GIT_AHEAD, GIT_BEHIND, GIT_DELETED, GIT_GREEN,
+    GIT_MODIFIED, INDICATOR_CLAUDE_MD, INDICATOR_DURATION, INDICATOR_HOOKS, INDICATOR_MCP,
+    INDICATOR_MEMORY, INDICATOR_RULES, INDICATOR_SKILLS, RESET, STABLE_BLUE, TODO_TEAL, TOOL_BLUE,
 };
 use super::fmt::{
-    format_agent_elapsed, format_duration, format_number, format_reset_duration, format_speed,
+    bar_cells, format_agent_elapsed, format_duration, format_reset_duration, sparkline, BarCell,
 };
+use super::hyperlink::{branch_url, editor_url, file_url, hyperlink, LINK_END};
 use super::icons::*;
+use super::segment::{SegmentRegistry, StyledSpan};
 
 /// Context usage percentage at which the warning color (ACTIVE_AMBER) activates.
 /// Claude Code triggers auto-compact at ~80%, so 55% gives early heads-up.
@@ -25,23 +35,50 @@ const CTX_WARN_THRESHOLD: u64 = 55;
 /// Set below auto-compact (~80%) so users see red before compaction fires.
 const CTX_CRITICAL_THRESHOLD: u64 = 70;
 
+/// Context usage percentage at which Claude Code auto-compacts, marked on the
+/// context progress bar.
+const AUTO_COMPACT_PCT: f64 = 80.0;
+
 /// Number of core lines (L1 identity, L2 config, L3 budget) that are always rendered.
 /// Used in width degradation to determine what counts as "activity" lines.
-const CORE_LINE_COUNT: usize = 3;
+pub(crate) const CORE_LINE_COUNT: usize = 3;
+
+/// Priority of `[[segments.custom]]` spans (see `StyledSpan::priority`).
+const CUSTOM_SEGMENT_PRIORITY: u8 = 40;
 
 pub fn render_frame(frame: &RenderFrame, config: &RenderConfig) -> Vec<String> {
+    render_frame_with(frame, config, &SegmentRegistry::default())
+}
+
+/// Render a frame with a caller-supplied segment registry for L1-L3.
+pub fn render_frame_with(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    segments: &SegmentRegistry,
+) -> Vec<String> {
     let color = config.color_enabled;
     let tier = emphasis_for_theme(config.color_theme);
 
-    let mut lines = vec![
-        format_line1(frame, config, &tier),
-        format_line2(frame, config, " | ", &tier),
-        format_line3(frame, config, &tier),
-    ];
+    let sep = colorize(" | ", tier.separator, color);
+    let powerline = powerline_for(config, &tier);
+    let core_lines: Vec<CoreLine> = (1..=CORE_LINE_COUNT)
+        .map(|line_no| {
+            let mut spans = segments.render_line(line_no, frame, config, &tier);
+            spans.extend(custom_segment_spans(line_no, frame, config, &tier));
+            CoreLine {
+                spans,
+                separator: sep.clone(),
+                powerline,
+            }
+        })
+        .collect();
+
+    // Quota, tool, agent and todo lines below the core lines
+    let mut lines: Vec<String> = Vec::new();
 
     // Quota line: between L3 and activity lines
     if config.show_quota {
-        if let Some(line) = format_quota_line(&frame.quota, config, &tier) {
+        if let Some(line) = format_quota_line(&frame.quota, &frame.line1.model, config, &tier) {
             lines.push(line);
         }
     }
@@ -71,13 +108,189 @@ pub fn render_frame(frame: &RenderFrame, config: &RenderConfig) -> Vec<String> {
         }
     }
 
-    if let Some(width) = config.terminal_width {
-        let compressed_line2 = format_line2(frame, config, " ", &tier);
-        lines =
-            apply_width_degradation(lines, width, &config.degrade_order, compressed_line2, color);
+    match config.terminal_width {
+        Some(width) => {
+            let compact_separator = colorize(" ", tier.separator, color);
+            apply_width_degradation(
+                core_lines,
+                lines,
+                width,
+                &config.degrade_order,
+                &compact_separator,
+                color,
+            )
+        }
+        None => core_lines.iter().map(CoreLine::join).chain(lines).collect(),
     }
+}
+
+/// A core line kept as segments until width degradation has run, so whole
+/// segments can be dropped by priority instead of cutting the joined string.
+struct CoreLine {
+    spans: Vec<StyledSpan>,
+    separator: String,
+    powerline: Option<Powerline>,
+}
 
-    lines
+/// Powerline join: each segment is padded on its own background, and the
+/// divider glyph is drawn in the left background over the right one.
+/// Neighbours on the same background get the thin divider instead.
+#[derive(Clone, Copy)]
+struct Powerline {
+    divider: &'static str,
+    thin_divider: &'static str,
+    thin_color: &'static str,
+    /// Background of spans that don't set one (embedder segments).
+    fallback: u8,
+}
+
+impl Powerline {
+    fn background(&self, span: &StyledSpan) -> u8 {
+        span.background.unwrap_or(self.fallback)
+    }
+
+    /// Divider after `spans[index]`: thin when the next span shares its
+    /// background, the full glyph otherwise (always after the last span).
+    fn divider_after(&self, spans: &[StyledSpan], index: usize) -> &'static str {
+        match spans.get(index + 1) {
+            Some(next) if self.background(next) == self.background(&spans[index]) => {
+                self.thin_divider
+            }
+            _ => self.divider,
+        }
+    }
+}
+
+/// Powerline settings for `config.line_style`; None for plain style or when
+/// color is disabled (backgrounds are the whole point).
+fn powerline_for(config: &RenderConfig, tier: &EmphasisTier) -> Option<Powerline> {
+    let icons = match config.line_style {
+        LineStyle::Plain => return None,
+        LineStyle::Powerline => (ICON_POWERLINE_ARROW, ICON_POWERLINE_ARROW_THIN),
+        LineStyle::PowerlineRound => (ICON_POWERLINE_ROUND, ICON_POWERLINE_ROUND_THIN),
+    };
+    if !config.color_enabled {
+        return None;
+    }
+    let (divider, thin_divider) = match config.glyph_mode {
+        GlyphMode::Icon => icons,
+        GlyphMode::Ascii => ("", "|"),
+    };
+    Some(Powerline {
+        divider,
+        thin_divider,
+        thin_color: tier.structural,
+        fallback: tier.backgrounds.get(SegmentGroup::Custom),
+    })
+}
+
+impl CoreLine {
+    fn width(&self) -> usize {
+        let content: usize = self.spans.iter().map(|span| span.width).sum();
+        match self.powerline {
+            Some(powerline) => {
+                let dividers: usize = (0..self.spans.len())
+                    .map(|index| display_width(powerline.divider_after(&self.spans, index)))
+                    .sum();
+                content + self.spans.len() * 2 + dividers
+            }
+            None => content + self.spans.len().saturating_sub(1) * visible_width(&self.separator),
+        }
+    }
+
+    fn join(&self) -> String {
+        if let Some(powerline) = self.powerline {
+            return join_powerline(&self.spans, powerline);
+        }
+        self.spans
+            .iter()
+            .map(|span| span.text.as_str())
+            .collect::<Vec<_>>()
+            .join(&self.separator)
+    }
+
+    /// Drop the lowest-priority segment (rightmost on ties) until the line fits
+    /// `width` or a single segment is left.
+    fn drop_segments_to_fit(&mut self, width: usize) {
+        while self.width() > width && self.spans.len() > 1 {
+            let lowest = self
+                .spans
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, span)| (span.priority, Reverse(*index)))
+                .map(|(index, _)| index);
+            if let Some(index) = lowest {
+                self.spans.remove(index);
+            }
+        }
+    }
+}
+
+/// Join spans powerline-style: ` text ` on each span's background, with each
+/// divider taking the left segment's background as its foreground and the
+/// right segment's as its background (the terminal default after the last).
+/// Neighbours on the same background are split by the thin divider instead.
+fn join_powerline(spans: &[StyledSpan], powerline: Powerline) -> String {
+    let mut line = String::new();
+    for (index, span) in spans.iter().enumerate() {
+        let bg = powerline.background(span);
+        let bg_code = bg_index(bg);
+        // Re-apply the background after every reset inside the span
+        let text = span.text.replace(RESET, &format!("{RESET}{bg_code}"));
+        line.push_str(&format!("{bg_code} {text} "));
+
+        let divider = colorize(powerline.divider, &fg_index(bg), true);
+        match spans.get(index + 1).map(|next| powerline.background(next)) {
+            Some(next_bg) if next_bg == bg => {
+                line.push_str(&colorize(
+                    powerline.thin_divider,
+                    powerline.thin_color,
+                    true,
+                ));
+            }
+            Some(next_bg) => line.push_str(&format!("{}{divider}", bg_index(next_bg))),
+            None => line.push_str(&format!("{RESET}{divider}")),
+        }
+    }
+    line
+}
+
+/// Spans for `[[segments.custom]]` values configured for core line `line_no`
+/// (1-3, out-of-range values clamp), in config order.
+fn custom_segment_spans(
+    line_no: usize,
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Vec<StyledSpan> {
+    let color = config.color_enabled;
+    let mut spans = Vec::new();
+
+    for segment in &config.custom_segments {
+        if segment.line.clamp(1, CORE_LINE_COUNT) != line_no {
+            continue;
+        }
+        let Some(value) = frame.custom.iter().find(|v| v.name == segment.name) else {
+            continue;
+        };
+        let value_color = segment
+            .color
+            .as_deref()
+            .and_then(named_color)
+            .unwrap_or_else(|| tier.secondary.to_string());
+        let label = segment
+            .label
+            .as_deref()
+            .map(|label| colorize(label, tier.structural, color))
+            .unwrap_or_default();
+        let text = format!("{label}{}", colorize(&value.text, &value_color, color));
+        spans.push(
+            StyledSpan::new(text, CUSTOM_SEGMENT_PRIORITY)
+                .with_background(tier.backgrounds.get(SegmentGroup::Custom)),
+        );
+    }
+
+    spans
 }
 
 /// Format the completed tool counts line.
@@ -123,8 +336,16 @@ fn format_recent_tool_line(
             let prefix = colorize(&glyph(mode, ICON_TOOL, "T:"), TOOL_BLUE, color);
             let name_str = colorize(&tool.name, TOOL_BLUE, color);
             if let Some(target) = &tool.target {
-                let target_str = colorize(&format!(": {target}"), tier.secondary, color);
-                format!("{prefix}{name_str}{target_str}")
+                let colon = colorize(": ", tier.secondary, color);
+                let target_url = tool
+                    .file_path
+                    .as_deref()
+                    .map(|path| match &config.editor_url {
+                        Some(template) => editor_url(template, path, tool.file_line),
+                        None => file_url(path),
+                    });
+                let target_str = link(colorize(target, tier.secondary, color), target_url, config);
+                format!("{prefix}{name_str}{colon}{target_str}")
             } else {
                 format!("{prefix}{name_str}")
             }
@@ -134,13 +355,21 @@ fn format_recent_tool_line(
     parts.join(&sep)
 }
 
-/// Max visible chars for activity line text (agent descriptions, todo task text).
-const ACTIVITY_TEXT_MAX_CHARS: usize = 40;
+/// Wrap `text` in an OSC 8 hyperlink to `url` when hyperlinks are enabled.
+fn link(text: String, url: Option<String>, config: &RenderConfig) -> String {
+    match url {
+        Some(url) if config.hyperlinks => hyperlink(&text, &url),
+        _ => text,
+    }
+}
+
+/// Max visible columns for activity line text (agent descriptions, todo task text).
+const ACTIVITY_TEXT_MAX_WIDTH: usize = 40;
 
-/// Truncate text to `max_chars`, appending ellipsis if needed.
-fn truncate_text(text: &str, max_chars: usize) -> String {
-    if text.chars().count() > max_chars {
-        let truncated: String = text.chars().take(max_chars).collect();
+/// Truncate text to `max_width` columns, appending ellipsis if needed.
+fn truncate_text(text: &str, max_width: usize) -> String {
+    if display_width(text) > max_width {
+        let truncated = take_visible_width(text, max_width);
         format!("{truncated}…")
     } else {
         text.to_string()
@@ -172,7 +401,11 @@ fn format_todo_lines(
     // All done: celebration line
     if todo.all_done {
         let check = colorize("✓", COMPLETED_CHECK, color);
-        let text = colorize(" All todos complete", COMPLETED_CHECK, color);
+        let text = colorize(
+            &format!(" {}", config.messages.get("todo.all_complete")),
+            COMPLETED_CHECK,
+            color,
+        );
         let progress = format_progress_count(todo.completed, todo.total, tier, color);
         return vec![format!("{check}{text}{progress}")];
     }
@@ -196,7 +429,7 @@ fn format_todo_lines(
             let prefix = colorize(&glyph(mode, ICON_TODO, "TODO:"), TODO_TEAL, color);
 
             let text_str = colorize(
-                &truncate_text(&item.text, ACTIVITY_TEXT_MAX_CHARS),
+                &truncate_text(&item.text, ACTIVITY_TEXT_MAX_WIDTH),
                 TODO_TEAL,
                 color,
             );
@@ -223,7 +456,10 @@ fn format_todo_lines(
                 );
                 let shown = total_active.min(config.max_todo_lines);
                 let overflow_part = if total_active > shown {
-                    colorize(&format!(", {} active", total_active), tier.secondary, color)
+                    let active = config
+                        .messages
+                        .format("todo.active", &[("count", &total_active)]);
+                    colorize(&format!(", {active}"), tier.secondary, color)
                 } else {
                     String::new()
                 };
@@ -243,14 +479,25 @@ fn format_todo_lines(
     // Task API path with pending only (no in-progress items)
     if todo.is_task_api {
         let prefix = colorize(&glyph(mode, ICON_TODO, "TODO:"), TODO_TEAL, color);
-        let label = colorize(&format!("{} tasks", todo.total), TODO_TEAL, color);
+        let tasks = config
+            .messages
+            .format("todo.tasks", &[("total", &todo.total)]);
+        let label = colorize(&tasks, TODO_TEAL, color);
         let progress = format_progress_count(todo.completed, todo.total, tier, color);
         return vec![format!("{prefix}{label}{progress}")];
     }
 
     // Legacy fallback (TodoWrite path)
     let prefix = colorize(&glyph(mode, ICON_TODO, "TODO:"), TODO_TEAL, color);
-    let text = colorize(&todo.text, TODO_TEAL, color);
+    let progress = config.messages.format(
+        "todo.progress",
+        &[
+            ("completed", &todo.completed),
+            ("total", &todo.total),
+            ("pending", &todo.pending),
+        ],
+    );
+    let text = colorize(&progress, TODO_TEAL, color);
     vec![format!("{prefix}{text}")]
 }
 
@@ -260,7 +507,7 @@ fn format_todo_lines(
 ///
 /// The description field comes from the Agent tool's `description` (3-5 word short summary)
 /// when available, falling back to `prompt` (full text). We truncate to first line,
-/// max ACTIVITY_TEXT_MAX_CHARS to keep activity lines compact.
+/// max ACTIVITY_TEXT_MAX_WIDTH to keep activity lines compact.
 fn format_agent_line(agent: &AgentSummary, config: &RenderConfig, tier: &EmphasisTier) -> String {
     let mode = config.glyph_mode;
     let color = config.color_enabled;
@@ -278,9 +525,9 @@ fn format_agent_line(agent: &AgentSummary, config: &RenderConfig, tier: &Emphasi
         colorize(&glyph(mode, ICON_AGENT, "A:"), AGENT_PURPLE, color)
     };
 
-    // Truncate description: first line only, max ACTIVITY_TEXT_MAX_CHARS visible chars
+    // Truncate description: first line only, max ACTIVITY_TEXT_MAX_WIDTH visible columns
     let first_line = agent.description.lines().next().unwrap_or("");
-    let desc_truncated = truncate_text(first_line, ACTIVITY_TEXT_MAX_CHARS);
+    let desc_truncated = truncate_text(first_line, ACTIVITY_TEXT_MAX_WIDTH);
 
     // Elapsed time
     let elapsed_str = if completed {
@@ -316,7 +563,11 @@ fn format_agent_line(agent: &AgentSummary, config: &RenderConfig, tier: &Emphasi
 
     // Done tag for ASCII completed agents
     let done_tag = if completed && mode == crate::config::GlyphMode::Ascii {
-        colorize(" [done]", tier.structural, color)
+        colorize(
+            &format!(" {}", config.messages.get("agent.done")),
+            tier.structural,
+            color,
+        )
     } else {
         String::new()
     };
@@ -347,161 +598,439 @@ fn format_agent_line(agent: &AgentSummary, config: &RenderConfig, tier: &Emphasi
     }
 }
 
-fn format_line1(frame: &RenderFrame, config: &RenderConfig, tier: &EmphasisTier) -> String {
-    let mode = config.glyph_mode;
-    let color = config.color_enabled;
-    let sep = colorize(" | ", tier.separator, color);
-
-    let mut parts: Vec<String> = Vec::new();
+// Built-in core-line segments, registered in `segment::SegmentRegistry`.
+// Each returns None when its toggle is off or it has nothing to show.
 
-    if config.show_model {
-        let model_label = colorize(&glyph(mode, ICON_MODEL, "M:"), STABLE_BLUE, color);
-        let model_val = colorize(&frame.line1.model, STABLE_BLUE, color);
-        parts.push(format!("{model_label}{model_val}"));
+pub(crate) fn session_phase_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    _tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_phase {
+        return None;
     }
+    let signal = frame.line1.session_phase.as_ref()?;
+    Some(format_session_phase(signal, config))
+}
 
-    if config.show_style {
-        let style_label = colorize(&glyph(mode, ICON_STYLE, "S:"), tier.secondary, color);
-        let style_val = colorize(&frame.line1.output_style, tier.secondary, color);
-        parts.push(format!("{style_label}{style_val}"));
+pub(crate) fn model_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    _tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_model {
+        return None;
     }
+    let color = config.color_enabled;
+    let model_label = colorize(
+        &glyph(config.glyph_mode, ICON_MODEL, "M:"),
+        STABLE_BLUE,
+        color,
+    );
+    let model_val = colorize(&frame.line1.model, STABLE_BLUE, color);
+    Some(format!("{model_label}{model_val}"))
+}
 
-    if config.show_version {
-        let version_label = colorize(&glyph(mode, ICON_VERSION, "CC:"), tier.secondary, color);
-        let version_val = colorize(&frame.line1.claude_code_version, tier.secondary, color);
-        parts.push(format!("{version_label}{version_val}"));
+pub(crate) fn style_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_style {
+        return None;
     }
+    let color = config.color_enabled;
+    let style_label = colorize(
+        &glyph(config.glyph_mode, ICON_STYLE, "S:"),
+        tier.secondary,
+        color,
+    );
+    let style_val = colorize(&frame.line1.output_style, tier.secondary, color);
+    Some(format!("{style_label}{style_val}"))
+}
 
-    if config.show_project {
-        let project_label = colorize(&glyph(mode, ICON_PROJECT, "P:"), tier.secondary, color);
-        let project_val = colorize(&frame.line1.project_path, tier.secondary, color);
-        parts.push(format!("{project_label}{project_val}"));
+pub(crate) fn version_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_version {
+        return None;
     }
+    let color = config.color_enabled;
+    let version_label = colorize(
+        &glyph(config.glyph_mode, ICON_VERSION, "CC:"),
+        tier.secondary,
+        color,
+    );
+    let version_val = colorize(&frame.line1.claude_code_version, tier.secondary, color);
+    Some(format!("{version_label}{version_val}"))
+}
 
-    if config.show_git {
-        let git_label = colorize(&glyph(mode, ICON_GIT, "G:"), GIT_GREEN, color);
-        let git_val = format_git_status(&frame.line1, config, tier);
-        parts.push(format!("{git_label}{git_val}"));
+pub(crate) fn project_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_project {
+        return None;
     }
+    let color = config.color_enabled;
+    let project_label = colorize(
+        &glyph(config.glyph_mode, ICON_PROJECT, "P:"),
+        tier.secondary,
+        color,
+    );
+    let project_val = link(
+        colorize(&frame.line1.project_path, tier.secondary, color),
+        frame.line1.project_root.as_deref().map(file_url),
+        config,
+    );
+    // cwd drifted away from the project root: `P:~/app ↳src/api`
+    let drift_part = frame
+        .line1
+        .cwd_drift
+        .as_ref()
+        .map(|drift| colorize(&format!(" ↳{drift}"), tier.structural, color))
+        .unwrap_or_default();
+    Some(format!("{project_label}{project_val}{drift_part}"))
+}
 
-    parts.join(&sep)
+pub(crate) fn git_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_git {
+        return None;
+    }
+    let git_label = colorize(
+        &glyph(config.glyph_mode, ICON_GIT, "G:"),
+        GIT_GREEN,
+        config.color_enabled,
+    );
+    let git_val = format_git_status(&frame.line1, config, tier);
+    Some(format!("{git_label}{git_val}"))
 }
 
-fn format_line2(
+pub(crate) fn workspace_dirs_segment(
     frame: &RenderFrame,
     config: &RenderConfig,
-    separator: &str,
     tier: &EmphasisTier,
-) -> String {
+) -> Option<String> {
+    if !config.show_workspace_dirs || frame.line1.extra_dirs.is_empty() {
+        return None;
+    }
+    Some(format_workspace_dirs(&frame.line1.extra_dirs, config, tier))
+}
+
+/// Format the session phase segment.
+/// Example: `working`, `waiting for permission`, `idle for 4m`
+fn format_session_phase(signal: &PhaseSignal, config: &RenderConfig) -> String {
     let mode = config.glyph_mode;
     let color = config.color_enabled;
-    let sep = colorize(separator, tier.separator, color);
-
-    // Helper to format: {icon} {count} {label} or {count} {label}
-    // Icon uses per-metric indicator_color; count uses tier.secondary; label uses tier.structural
-    let format_item = |icon: &str, indicator_color: &str, label: &str, count: u32| -> String {
-        let count_str = colorize(&count.to_string(), tier.primary, color);
-        let label_str = colorize(label, tier.structural, color);
+    let messages = &config.messages;
 
-        match mode {
-            crate::config::GlyphMode::Icon => {
-                let icon_str = colorize(&format!("{icon} "), indicator_color, color);
-                format!("{icon_str}{count_str} {label_str}")
-            }
-            crate::config::GlyphMode::Ascii => {
-                format!("{count_str} {label_str}")
-            }
+    let (icon, text, phase_color) = match signal.phase {
+        SessionPhase::Working => (
+            ICON_PHASE_WORKING,
+            messages.get("phase.working").to_string(),
+            TOOL_BLUE,
+        ),
+        SessionPhase::WaitingPermission => (
+            ICON_PHASE_WAITING,
+            messages.get("phase.waiting_permission").to_string(),
+            ALERT_RED,
+        ),
+        SessionPhase::Idle => {
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            let idle_minutes = now_ms.saturating_sub(signal.since_ms) / 60_000;
+            (
+                ICON_PHASE_IDLE,
+                messages.format(
+                    "phase.idle",
+                    &[("duration", &format_duration(idle_minutes))],
+                ),
+                ACTIVE_AMBER,
+            )
         }
     };
 
-    let mut parts: Vec<String> = Vec::new();
+    colorize(
+        &format!("{}{text}", glyph(mode, icon, "")),
+        phase_color,
+        color,
+    )
+}
+
+/// Format an L2 count item: `{icon} {count} {label}` or `{count} {label}`.
+/// Icon uses the per-metric indicator color; count uses tier.primary; label uses tier.structural.
+fn format_config_item(
+    icon: &str,
+    indicator_color: &str,
+    label: &str,
+    count: u32,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> String {
+    let color = config.color_enabled;
+    let count_str = colorize(&count.to_string(), tier.primary, color);
+    let label_str = colorize(label, tier.structural, color);
+
+    match config.glyph_mode {
+        GlyphMode::Icon => {
+            let icon_str = colorize(&format!("{icon} "), indicator_color, color);
+            format!("{icon_str}{count_str} {label_str}")
+        }
+        GlyphMode::Ascii => format!("{count_str} {label_str}"),
+    }
+}
 
-    if config.show_claude_md {
-        parts.push(format_item(
+pub(crate) fn claude_md_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_claude_md.then(|| {
+        format_config_item(
             ICON_CLAUDE_MD,
             INDICATOR_CLAUDE_MD,
-            "CLAUDE.md",
+            config.messages.get("config.claude_md"),
             frame.line2.claude_md_count,
-        ));
-    }
-    if config.show_rules {
-        parts.push(format_item(
+            config,
+            tier,
+        )
+    })
+}
+
+pub(crate) fn rules_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_rules.then(|| {
+        format_config_item(
             ICON_RULES,
             INDICATOR_RULES,
-            "rules",
+            config.messages.get("config.rules"),
             frame.line2.rules_count,
-        ));
-    }
-    if config.show_memory {
-        parts.push(format_item(
+            config,
+            tier,
+        )
+    })
+}
+
+pub(crate) fn memory_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_memory.then(|| {
+        format_config_item(
             ICON_MEMORY,
             INDICATOR_MEMORY,
-            "memories",
+            config.messages.get("config.memories"),
             frame.line2.memory_count,
-        ));
-    }
-    if config.show_hooks {
-        parts.push(format_item(
+            config,
+            tier,
+        )
+    })
+}
+
+pub(crate) fn hooks_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_hooks.then(|| {
+        format_config_item(
             ICON_HOOKS,
             INDICATOR_HOOKS,
-            "hooks",
+            config.messages.get("config.hooks"),
             frame.line2.hooks_count,
-        ));
-    }
-    if config.show_mcp {
-        parts.push(format_item(
+            config,
+            tier,
+        )
+    })
+}
+
+pub(crate) fn mcp_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_mcp.then(|| {
+        format_config_item(
             ICON_MCP,
             INDICATOR_MCP,
-            "MCPs",
+            config.messages.get("config.mcps"),
             frame.line2.mcp_count,
-        ));
-    }
-    if config.show_skills {
-        parts.push(format_item(
+            config,
+            tier,
+        )
+    })
+}
+
+pub(crate) fn skills_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_skills.then(|| {
+        format_config_item(
             ICON_SKILLS,
             INDICATOR_SKILLS,
-            "skills",
+            config.messages.get("config.skills"),
             frame.line2.skills_count,
-        ));
-    }
-    if config.show_duration {
-        let duration_text = format_duration(frame.line2.elapsed_minutes);
-        let item = match mode {
-            crate::config::GlyphMode::Icon => {
-                let icon_str = colorize(&format!("{} ", ICON_ELAPSED), INDICATOR_DURATION, color);
-                let time_str = colorize(&duration_text, tier.primary, color);
-                format!("{icon_str}{time_str}")
-            }
-            crate::config::GlyphMode::Ascii => colorize(&duration_text, tier.primary, color),
-        };
-        parts.push(item);
-    }
-
-    parts.join(&sep)
+            config,
+            tier,
+        )
+    })
 }
 
-fn format_line3(frame: &RenderFrame, config: &RenderConfig, tier: &EmphasisTier) -> String {
+pub(crate) fn duration_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_duration {
+        return None;
+    }
     let color = config.color_enabled;
-    let sep = colorize(" | ", tier.separator, color);
+    let duration_text = format_duration(frame.line2.elapsed_minutes);
+    let item = match config.glyph_mode {
+        GlyphMode::Icon => {
+            let icon_str = colorize(&format!("{} ", ICON_ELAPSED), INDICATOR_DURATION, color);
+            let time_str = colorize(&duration_text, tier.primary, color);
+            format!("{icon_str}{time_str}")
+        }
+        GlyphMode::Ascii => colorize(&duration_text, tier.primary, color),
+    };
+    Some(item)
+}
 
-    let mut parts: Vec<String> = Vec::new();
+pub(crate) fn context_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_context.then(|| {
+        let trend = format_trend(&frame.history.context_pct, Some(100.0), config, tier);
+        format!(
+            "{}{trend}",
+            format_context_segment(&frame.line3, config, tier)
+        )
+    })
+}
 
-    if config.show_context {
-        parts.push(format_context_segment(&frame.line3, config, tier));
-    }
-    if config.show_tokens {
-        let speed = if config.show_speed {
-            frame.line3.output_speed_toks_per_sec
-        } else {
-            None
-        };
-        parts.push(format_tokens_segment(&frame.line3, speed, config, tier));
+pub(crate) fn tokens_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    if !config.show_tokens {
+        return None;
     }
-    if config.show_cost {
-        parts.push(format_cost_segment(&frame.line3, config, tier));
+    let speed = if config.show_speed {
+        frame.line3.output_speed_toks_per_sec
+    } else {
+        None
+    };
+    let trend = speed
+        .map(|_| format_trend(&frame.history.output_speed, None, config, tier))
+        .unwrap_or_default();
+    Some(format_tokens_segment(
+        &frame.line3,
+        speed,
+        &trend,
+        config,
+        tier,
+    ))
+}
+
+pub(crate) fn cost_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config.show_cost.then(|| {
+        let trend = format_trend(&frame.history.cost_per_hour, None, config, tier);
+        format!("{}{trend}", format_cost_segment(&frame.line3, config, tier))
+    })
+}
+
+/// Sparkline of recent samples, prefixed with a space: ` ▂▃▅▇`. Empty when
+/// sparklines are off or there are fewer than two samples.
+fn format_trend(
+    values: &[f64],
+    max: Option<f64>,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> String {
+    if !config.show_sparklines {
+        return String::new();
     }
+    sparkline(values, max)
+        .map(|line| {
+            format!(
+                " {}",
+                colorize(&line, tier.structural, config.color_enabled)
+            )
+        })
+        .unwrap_or_default()
+}
 
-    parts.join(&sep)
+pub(crate) fn lines_changed_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config
+        .show_lines_changed
+        .then(|| format_lines_changed_segment(&frame.line3, config, tier))
+}
+
+pub(crate) fn api_time_segment(
+    frame: &RenderFrame,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    config
+        .show_api_time
+        .then(|| format_api_time_segment(&frame.line3, config, tier))
+}
+
+/// Format additional workspace directories: `+api:main* +web:feat/x`.
+/// Directories outside a git repository show just the name (`+docs`).
+fn format_workspace_dirs(
+    dirs: &[WorkspaceDirStatus],
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> String {
+    let color = config.color_enabled;
+
+    dirs.iter()
+        .map(|dir| {
+            let name_str = colorize(&format!("+{}", dir.name), tier.secondary, color);
+            match &dir.branch {
+                Some(branch) => {
+                    let colon = colorize(":", tier.separator, color);
+                    let branch_str = colorize(branch, GIT_GREEN, color);
+                    let dirty_str = if dir.dirty {
+                        colorize("*", GIT_MODIFIED, color)
+                    } else {
+                        String::new()
+                    };
+                    format!("{name_str}{colon}{branch_str}{dirty_str}")
+                }
+                None => name_str,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 fn format_git_status(line1: &Line1Metrics, config: &RenderConfig, tier: &EmphasisTier) -> String {
@@ -511,7 +1040,14 @@ fn format_git_status(line1: &Line1Metrics, config: &RenderConfig, tier: &Emphasi
         return colorize("unknown", tier.structural, color);
     }
 
-    let mut status = colorize(&line1.git_branch, GIT_GREEN, color);
+    let mut status = link(
+        colorize(&line1.git_branch, GIT_GREEN, color),
+        line1
+            .git_remote_url
+            .as_deref()
+            .map(|repo| branch_url(repo, &line1.git_branch)),
+        config,
+    );
     if line1.git_dirty {
         status.push_str(&colorize("*", GIT_MODIFIED, color));
     }
@@ -558,6 +1094,7 @@ fn format_context_segment(
     tier: &EmphasisTier,
 ) -> String {
     let color = config.color_enabled;
+    let numbers = &config.number_format;
     let mode = config.glyph_mode;
 
     match (line3.context_used_percentage, line3.context_window_size) {
@@ -575,12 +1112,22 @@ fn format_context_segment(
             let label = colorize(&glyph(mode, ICON_CONTEXT, "CTX:"), pct_color, color);
             let pct = colorize(&format!("{}%", used_pct), pct_color, color);
             let open_paren = colorize(" (", tier.separator, color);
-            let usage = colorize(&format_number(used_tokens), tier.primary, color);
+            let usage = colorize(&numbers.number(used_tokens), tier.primary, color);
             let sep = colorize("/", tier.separator, color);
-            let total = colorize(&format_number(size), tier.primary, color);
+            let total = colorize(&numbers.number(size), tier.primary, color);
             let close_paren = colorize(")", tier.separator, color);
 
-            format!("{label}{pct}{open_paren}{usage}{sep}{total}{close_paren}")
+            let bar = format_progress_bar(
+                used_pct as f64,
+                Some(AUTO_COMPACT_PCT),
+                pct_color,
+                config,
+                tier,
+            )
+            .map(|bar| format!("{bar} "))
+            .unwrap_or_default();
+
+            format!("{label}{bar}{pct}{open_paren}{usage}{sep}{total}{close_paren}")
         }
         _ => {
             let label = colorize(&glyph(mode, ICON_CONTEXT, "CTX:"), tier.structural, color);
@@ -594,14 +1141,58 @@ fn format_context_segment(
     }
 }
 
+/// Format a progress bar when `config.bar_style` is set: filled cells in
+/// `fill_color`, empty cells in the separator tier, the marker in structural.
+/// ASCII bars are bracketed: `[######|---]`.
+fn format_progress_bar(
+    pct: f64,
+    marker_pct: Option<f64>,
+    fill_color: &str,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> Option<String> {
+    let style = config.bar_style?;
+    let color = config.color_enabled;
+
+    // Color runs of same-role cells together to keep escapes short
+    let mut bar = String::new();
+    let mut run = String::new();
+    let mut run_color = "";
+    for cell in bar_cells(pct, config.bar_width, style, marker_pct) {
+        let (ch, cell_color) = match cell {
+            BarCell::Filled(ch) => (ch, fill_color),
+            BarCell::Empty(ch) => (ch, tier.separator),
+            BarCell::Marker(ch) => (ch, tier.structural),
+        };
+        if cell_color != run_color && !run.is_empty() {
+            bar.push_str(&colorize(&run, run_color, color));
+            run.clear();
+        }
+        run_color = cell_color;
+        run.push(ch);
+    }
+    if !run.is_empty() {
+        bar.push_str(&colorize(&run, run_color, color));
+    }
+
+    if style == BarStyle::Ascii {
+        let open = colorize("[", tier.separator, color);
+        let close = colorize("]", tier.separator, color);
+        bar = format!("{open}{bar}{close}");
+    }
+    Some(bar)
+}
+
 fn format_tokens_segment(
     line3: &Line3Metrics,
     speed: Option<f64>,
+    speed_trend: &str,
     config: &RenderConfig,
     tier: &EmphasisTier,
 ) -> String {
     let mode = config.glyph_mode;
     let color = config.color_enabled;
+    let numbers = &config.number_format;
 
     let has_data = line3.input_tokens.is_some()
         || line3.output_tokens.is_some()
@@ -617,25 +1208,25 @@ fn format_tokens_segment(
 
     let input_str = line3
         .input_tokens
-        .map(format_number)
+        .map(|n| numbers.number(n))
         .unwrap_or_else(|| "--".to_string());
     let output_str = line3
         .output_tokens
-        .map(format_number)
+        .map(|n| numbers.number(n))
         .unwrap_or_else(|| "--".to_string());
     let cache_str = if has_data {
         format!(
             "{}/{}",
-            format_number(line3.cache_creation_tokens.unwrap_or(0)),
-            format_number(line3.cache_read_tokens.unwrap_or(0)),
+            numbers.number(line3.cache_creation_tokens.unwrap_or(0)),
+            numbers.number(line3.cache_read_tokens.unwrap_or(0)),
         )
     } else {
         "--/--".to_string()
     };
 
-    // Speed inline after output tokens: "O:20.0k ↗1.5K/s"
+    // Speed inline after output tokens: "O:20.0k ↗1.5K/s ▃▅▇"
     let speed_part = speed
-        .map(|s| colorize(&format!(" {}", format_speed(s)), val_color, color))
+        .map(|s| colorize(&format!(" {}", numbers.speed(s)), val_color, color))
         .unwrap_or_default();
 
     let label = colorize("TOK ", tier.structural, color);
@@ -646,7 +1237,7 @@ fn format_tokens_segment(
             colorize(&input_str, val_color, color),
         ),
         format!(
-            "{}{}{}",
+            "{}{}{}{}",
             colorize(
                 &glyph(mode, ICON_TOKEN_OUTPUT, "O:"),
                 tier.structural,
@@ -654,6 +1245,7 @@ fn format_tokens_segment(
             ),
             colorize(&output_str, val_color, color),
             speed_part,
+            speed_trend,
         ),
         format!(
             "{}{}",
@@ -670,6 +1262,7 @@ fn format_tokens_segment(
 
 fn format_cost_segment(line3: &Line3Metrics, config: &RenderConfig, tier: &EmphasisTier) -> String {
     let color = config.color_enabled;
+    let numbers = &config.number_format;
 
     let total_cost = line3.total_cost_usd.unwrap_or(0.0);
     let per_hour = line3
@@ -686,13 +1279,91 @@ fn format_cost_segment(line3: &Line3Metrics, config: &RenderConfig, tier: &Empha
         COST_LOW_RATE
     };
 
-    let total_str = colorize(&format!("${total_cost:.2}"), COST_BASE, color);
+    let total_str = colorize(&numbers.money(total_cost), COST_BASE, color);
     let open_paren = colorize("(", tier.separator, color);
-    let rate_str = colorize(&format!("${per_hour:.2}/h"), rate_color, color);
+    let rate_str = colorize(&format!("{}/h", numbers.money(per_hour)), rate_color, color);
     let close_paren = colorize(")", tier.separator, color);
     format!("{total_str} {open_paren}{rate_str}{close_paren}")
 }
 
+/// Format lines changed with cost efficiency: `L:+156 -23 ($1.91/100L)`.
+fn format_lines_changed_segment(
+    line3: &Line3Metrics,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> String {
+    let color = config.color_enabled;
+    let numbers = &config.number_format;
+    let mode = config.glyph_mode;
+
+    if line3.lines_changed().is_none() {
+        let label = colorize(&glyph(mode, ICON_LINES, "L:"), tier.structural, color);
+        let dash = colorize("--", tier.structural, color);
+        return format!("{label}{dash}");
+    }
+
+    let label = colorize(&glyph(mode, ICON_LINES, "L:"), tier.structural, color);
+    let added = colorize(
+        &format!("+{}", numbers.number(line3.lines_added.unwrap_or(0))),
+        GIT_ADDED,
+        color,
+    );
+    let removed = colorize(
+        &format!("-{}", numbers.number(line3.lines_removed.unwrap_or(0))),
+        GIT_DELETED,
+        color,
+    );
+
+    let rate_part = line3
+        .cost_per_100_lines()
+        .map(|rate| {
+            let open = colorize(" (", tier.separator, color);
+            let rate_str = colorize(&format!("{}/100L", numbers.money(rate)), COST_BASE, color);
+            let close = colorize(")", tier.separator, color);
+            format!("{open}{rate_str}{close}")
+        })
+        .unwrap_or_default();
+
+    format!("{label}{added} {removed}{rate_part}")
+}
+
+/// Format API time and its share of wall time: `API:12m (38%)`.
+fn format_api_time_segment(
+    line3: &Line3Metrics,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> String {
+    let color = config.color_enabled;
+    let mode = config.glyph_mode;
+
+    let Some(api_ms) = line3.total_api_duration_ms else {
+        let label = colorize(&glyph(mode, ICON_API_TIME, "API:"), tier.structural, color);
+        let dash = colorize("--", tier.structural, color);
+        return format!("{label}{dash}");
+    };
+
+    let secs = api_ms / 1000;
+    let time_text = if secs < 60 {
+        format_agent_elapsed(secs)
+    } else {
+        format_duration(secs / 60)
+    };
+
+    let label = colorize(&glyph(mode, ICON_API_TIME, "API:"), tier.structural, color);
+    let time_str = colorize(&time_text, tier.primary, color);
+    let share_part = line3
+        .api_time_share_pct()
+        .map(|share| {
+            let open = colorize(" (", tier.separator, color);
+            let pct = colorize(&format!("{share:.0}%"), tier.secondary, color);
+            let close = colorize(")", tier.separator, color);
+            format!("{open}{pct}{close}")
+        })
+        .unwrap_or_default();
+
+    format!("{label}{time_str}{share_part}")
+}
+
 fn capitalize_first(s: &str) -> String {
     let mut chars = s.chars();
     match chars.next() {
@@ -703,6 +1374,7 @@ fn capitalize_first(s: &str) -> String {
 
 fn format_quota_line(
     quota: &QuotaMetrics,
+    active_model: &str,
     config: &RenderConfig,
     tier: &EmphasisTier,
 ) -> Option<String> {
@@ -728,8 +1400,15 @@ fn format_quota_line(
     if config.show_quota_five_hour {
         parts.push(format_quota_period(
             "5h",
+            false,
             quota.five_hour_pct,
-            quota.five_hour_reset_minutes,
+            quota_reset_text(
+                quota.five_hour_reset_minutes,
+                quota.five_hour_reset_local,
+                false,
+                config,
+            ),
+            quota.five_hour_projection,
             config,
             tier,
         ));
@@ -738,114 +1417,279 @@ fn format_quota_line(
     if config.show_quota_seven_day {
         parts.push(format_quota_period(
             "7d",
+            false,
             quota.seven_day_pct,
-            quota.seven_day_reset_minutes,
+            quota_reset_text(
+                quota.seven_day_reset_minutes,
+                quota.seven_day_reset_local,
+                true,
+                config,
+            ),
+            quota.seven_day_projection,
             config,
             tier,
         ));
     }
 
+    if config.show_quota_model_buckets {
+        for bucket in &quota.model_buckets {
+            parts.push(format_quota_period(
+                &capitalize_first(&bucket.model),
+                bucket.applies_to(active_model),
+                Some(bucket.pct),
+                None,
+                None,
+                config,
+                tier,
+            ));
+        }
+    }
+
+    if config.show_quota_extra_usage {
+        if let Some(extra) = &quota.extra_usage {
+            parts.push(format_extra_usage(extra, config, tier));
+        }
+    }
+
     if parts.is_empty() {
         return None;
     }
+    if quota.stale {
+        parts.push(colorize(
+            config.messages.get("quota.stale"),
+            tier.structural,
+            color,
+        ));
+    }
 
     Some(format!("{prefix}{}", parts.join(" ")))
 }
 
+const WEEKDAY_KEYS: [&str; 7] = [
+    "weekday.sun",
+    "weekday.mon",
+    "weekday.tue",
+    "weekday.wed",
+    "weekday.thu",
+    "weekday.fri",
+    "weekday.sat",
+];
+
+/// "resets 3h 12m", or with `absolute_reset` the local clock time
+/// ("resets 14:30"; "resets Tue 09:00" for the weekly window).
+fn quota_reset_text(
+    minutes: Option<u64>,
+    local: Option<LocalTime>,
+    with_weekday: bool,
+    config: &RenderConfig,
+) -> Option<String> {
+    let messages = &config.messages;
+    if let (true, Some(local)) = (config.quota_absolute_reset, local) {
+        let clock = format!("{:02}:{:02}", local.hour, local.minute);
+        let time = if with_weekday {
+            let day = WEEKDAY_KEYS[usize::from(local.weekday % 7)];
+            format!("{} {clock}", messages.get(day))
+        } else {
+            clock
+        };
+        return Some(messages.format("quota.resets_at", &[("time", &time)]));
+    }
+    minutes.map(|m| {
+        let duration = format_reset_duration(m);
+        messages.format("quota.resets", &[("duration", &duration)])
+    })
+}
+
+/// Usage color for a quota percentage.
+fn quota_pct_color(pct: f64) -> &'static str {
+    if pct >= 85.0 {
+        CTX_CRITICAL
+    } else if pct >= 50.0 {
+        CTX_WARN
+    } else {
+        CTX_GOOD
+    }
+}
+
+/// `Extra: $12.34/$50.00` in the display currency, `Extra: 25%` without
+/// amounts, or `Extra: off` when extra usage is disabled.
+fn format_extra_usage(extra: &ExtraUsage, config: &RenderConfig, tier: &EmphasisTier) -> String {
+    let color = config.color_enabled;
+    let label = colorize(
+        &format!("{}:", config.messages.get("quota.extra_usage")),
+        tier.secondary,
+        color,
+    );
+    if !extra.enabled {
+        let off = colorize(
+            config.messages.get("quota.extra_usage_off"),
+            tier.structural,
+            color,
+        );
+        return format!("{label} {off}");
+    }
+
+    let value_color = extra.pct.map_or(tier.structural, quota_pct_color);
+    let numbers = &config.number_format;
+    let value = match (extra.used_usd, extra.limit_usd, extra.pct) {
+        (Some(used), Some(limit), _) => format!("{}/{}", numbers.money(used), numbers.money(limit)),
+        (Some(used), None, _) => numbers.money(used),
+        (None, _, Some(pct)) => format!("{pct:.0}%"),
+        (None, _, None) => "--".to_string(),
+    };
+    format!("{label} {}", colorize(&value, value_color, color))
+}
+
+/// One quota window: `5h: 42% (resets 2h 0m)`. An `active` label (the model
+/// bucket matching the session's model) is drawn bold in the primary color.
 fn format_quota_period(
     label: &str,
+    active: bool,
     pct: Option<f64>,
-    reset_minutes: Option<u64>,
+    reset_text: Option<String>,
+    projection: Option<QuotaProjection>,
     config: &RenderConfig,
     tier: &EmphasisTier,
 ) -> String {
     let color = config.color_enabled;
 
+    let active_color = format!("{BOLD}{}", tier.primary);
+    let label_color = if active {
+        active_color.as_str()
+    } else {
+        tier.secondary
+    };
+    let label_str = colorize(&format!("{label}:"), label_color, color);
+
     match pct {
         Some(p) => {
-            let pct_color = if p >= 85.0 {
-                CTX_CRITICAL
-            } else if p >= 50.0 {
-                CTX_WARN
+            let pct_color = quota_pct_color(p);
+            let pct_str = colorize(&format!("{p:.0}%"), pct_color, color);
+
+            let mut notes: Vec<String> = reset_text
+                .map(|text| colorize(&text, tier.structural, color))
+                .into_iter()
+                .collect();
+            if let Some(projection) = projection.filter(|_| config.show_quota_projection) {
+                notes.push(format_quota_projection(projection, config, tier));
+            }
+            let reset_part = if notes.is_empty() {
+                String::new()
             } else {
-                CTX_GOOD
+                let open = colorize(" (", tier.separator, color);
+                let close = colorize(")", tier.separator, color);
+                let comma = colorize(", ", tier.separator, color);
+                format!("{open}{}{close}", notes.join(&comma))
             };
 
-            let pct_str = colorize(&format!("{p:.0}%"), pct_color, color);
-            let label_str = colorize(&format!("{label}:"), tier.secondary, color);
-
-            let reset_part = reset_minutes
-                .map(|m| {
-                    let duration = format_reset_duration(m);
-                    let open = colorize(" (", tier.separator, color);
-                    let txt = colorize(&format!("resets {duration}"), tier.structural, color);
-                    let close = colorize(")", tier.separator, color);
-                    format!("{open}{txt}{close}")
-                })
+            let bar = format_progress_bar(p, None, pct_color, config, tier)
+                .map(|bar| format!("{bar} "))
                 .unwrap_or_default();
 
             if p >= 100.0 {
-                let limit_text = colorize("Limit reached", CTX_CRITICAL, color);
-                format!("{label_str} {limit_text}{reset_part}")
+                let limit_text = colorize(
+                    config.messages.get("quota.limit_reached"),
+                    CTX_CRITICAL,
+                    color,
+                );
+                format!("{label_str} {bar}{limit_text}{reset_part}")
             } else {
-                format!("{label_str} {pct_str}{reset_part}")
+                format!("{label_str} {bar}{pct_str}{reset_part}")
             }
         }
         None => {
-            let label_str = colorize(&format!("{label}:"), tier.secondary, color);
             let dash = colorize("--", tier.structural, color);
             format!("{label_str} {dash}")
         }
     }
 }
 
+/// "limit in ~40m" in the critical color when the burn rate reaches the limit
+/// before the reset, otherwise "on pace: 62% at reset".
+fn format_quota_projection(
+    projection: QuotaProjection,
+    config: &RenderConfig,
+    tier: &EmphasisTier,
+) -> String {
+    let color = config.color_enabled;
+    match projection {
+        QuotaProjection::LimitIn { minutes } => {
+            let duration = format_reset_duration(minutes);
+            colorize(
+                &config
+                    .messages
+                    .format("quota.limit_in", &[("duration", &duration)]),
+                CTX_CRITICAL,
+                color,
+            )
+        }
+        QuotaProjection::AtReset { pct } => {
+            let pct = format!("{pct:.0}%");
+            colorize(
+                &config.messages.format("quota.on_pace", &[("pct", &pct)]),
+                tier.structural,
+                color,
+            )
+        }
+    }
+}
+
+/// Fit core and extra lines into `width` by applying `strategies` in order
+/// until everything fits; anything still too wide is truncated with `...`.
 fn apply_width_degradation(
-    mut lines: Vec<String>,
+    mut core_lines: Vec<CoreLine>,
+    mut extra_lines: Vec<String>,
     width: usize,
     strategies: &[WidthDegradeStrategy],
-    compressed_line2: String,
+    compact_separator: &str,
     color_enabled: bool,
 ) -> Vec<String> {
     if width == 0 {
         return Vec::new();
     }
 
-    if lines_fit_width(&lines, width) {
-        return lines;
-    }
-
     for strategy in strategies {
-        if lines_fit_width(&lines, width) {
+        let fits = core_lines.iter().all(|line| line.width() <= width)
+            && extra_lines.iter().all(|line| visible_width(line) <= width);
+        if fits {
             break;
         }
 
+        #[allow(deprecated)]
         match strategy {
             WidthDegradeStrategy::DropActivityLinesFirst => {
-                if lines.len() > CORE_LINE_COUNT {
-                    lines.truncate(CORE_LINE_COUNT);
-                }
+                extra_lines.retain(|line| visible_width(line) <= width);
             }
             WidthDegradeStrategy::CompressLine2 => {
-                if let Some(line2) = lines.get_mut(1) {
-                    *line2 = compressed_line2.clone();
+                if let Some(line2) = core_lines.get_mut(1) {
+                    line2.separator = compact_separator.to_string();
                 }
             }
-            WidthDegradeStrategy::CompressCoreLines => {
-                for index in 0..lines.len().min(CORE_LINE_COUNT) {
-                    lines[index] = truncate_to_width(&lines[index], width, color_enabled);
+            WidthDegradeStrategy::DropLowPrioritySegments
+            | WidthDegradeStrategy::CompressCoreLines => {
+                for line in &mut core_lines {
+                    line.drop_segments_to_fit(width);
                 }
             }
         }
     }
 
-    lines
-        .into_iter()
+    core_lines
+        .iter()
+        .map(CoreLine::join)
+        .chain(extra_lines)
         .map(|line| truncate_to_width(&line, width, color_enabled))
         .collect()
 }
 
-fn lines_fit_width(lines: &[String], width: usize) -> bool {
-    lines.iter().all(|line| visible_width(line) <= width)
+/// Reset colors and close any hyperlink cut open by truncation.
+fn close_escapes(line: &mut String, color_enabled: bool) {
+    if color_enabled {
+        line.push_str(RESET);
+    }
+    if line.contains("\x1b]8;") {
+        line.push_str(LINK_END);
+    }
 }
 
 fn truncate_to_width(line: &str, width: usize, color_enabled: bool) -> String {
@@ -854,17 +1698,13 @@ fn truncate_to_width(line: &str, width: usize, color_enabled: bool) -> String {
     }
 
     if width <= 3 {
-        let mut result = take_visible_chars(line, width);
-        if color_enabled {
-            result.push_str(RESET);
-        }
+        let mut result = take_visible_width(line, width);
+        close_escapes(&mut result, color_enabled);
         return result;
     }
 
-    let mut truncated = take_visible_chars(line, width - 3);
-    if color_enabled {
-        truncated.push_str(RESET);
-    }
+    let mut truncated = take_visible_width(line, width - 3);
+    close_escapes(&mut truncated, color_enabled);
     truncated.push_str("...");
     truncated
 }
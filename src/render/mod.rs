@@ -1,4 +1,7 @@
 pub mod color;
 pub mod fmt;
+pub mod hyperlink;
 pub mod icons;
 pub mod layout;
+pub mod segment;
+pub mod terminal;
@@ -0,0 +1,339 @@
+use std::fmt;
+
+use crate::{config::RenderConfig, types::RenderFrame};
+
+use super::{
+    color::{visible_width, EmphasisTier, SegmentGroup},
+    layout,
+};
+
+/// A rendered segment: ANSI-styled text plus the metadata layout needs.
+///
+/// `priority` ranks segments within a line — higher values are more important
+/// (context and cost sit at the top, version and style at the bottom).
+/// `background` is the 256-color index the span sits on in powerline style;
+/// None falls back to the theme's custom-segment background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyledSpan {
+    pub text: String,
+    pub width: usize,
+    pub priority: u8,
+    pub background: Option<u8>,
+}
+
+impl StyledSpan {
+    /// Build a span, measuring the visible width of `text` (ANSI codes excluded).
+    pub fn new(text: String, priority: u8) -> Self {
+        let width = visible_width(&text);
+        Self {
+            text,
+            width,
+            priority,
+            background: None,
+        }
+    }
+
+    /// Set the powerline background (a 256-color index).
+    pub fn with_background(mut self, background: u8) -> Self {
+        self.background = Some(background);
+        self
+    }
+}
+
+/// One piece of a core line (L1 identity, L2 config, L3 budget).
+///
+/// Implement this to add segments from outside the crate, then register them
+/// with [`SegmentRegistry::register`] and render with
+/// [`layout::render_frame_with`]. Segments are `Send + Sync` so a registry
+/// (and a runner holding one) can be shared across threads.
+pub trait Segment: Send + Sync {
+    /// Stable identifier, used to position or remove segments in a registry.
+    fn id(&self) -> &str;
+
+    /// Core line the segment belongs to (1-3; out-of-range values clamp).
+    fn line(&self) -> usize;
+
+    /// Render the segment, or None to leave it out of the line.
+    fn render(
+        &self,
+        frame: &RenderFrame,
+        config: &RenderConfig,
+        tier: &EmphasisTier,
+    ) -> Option<StyledSpan>;
+}
+
+type RenderFn = fn(&RenderFrame, &RenderConfig, &EmphasisTier) -> Option<String>;
+
+/// A segment shipped with the crate: a layout formatter plus its placement.
+struct BuiltinSegment {
+    id: &'static str,
+    line: usize,
+    priority: u8,
+    group: SegmentGroup,
+    render: RenderFn,
+}
+
+impl Segment for BuiltinSegment {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn line(&self) -> usize {
+        self.line
+    }
+
+    fn render(
+        &self,
+        frame: &RenderFrame,
+        config: &RenderConfig,
+        tier: &EmphasisTier,
+    ) -> Option<StyledSpan> {
+        (self.render)(frame, config, tier).map(|text| {
+            StyledSpan::new(text, self.priority).with_background(tier.backgrounds.get(self.group))
+        })
+    }
+}
+
+/// Built-in segments in display order: (id, line, priority, background group,
+/// formatter).
+const BUILTIN_SEGMENTS: &[(&str, usize, u8, SegmentGroup, RenderFn)] = &[
+    ("model", 1, 70, SegmentGroup::Session, layout::model_segment),
+    (
+        "phase",
+        1,
+        80,
+        SegmentGroup::Session,
+        layout::session_phase_segment,
+    ),
+    ("style", 1, 20, SegmentGroup::Session, layout::style_segment),
+    (
+        "version",
+        1,
+        10,
+        SegmentGroup::Session,
+        layout::version_segment,
+    ),
+    (
+        "project",
+        1,
+        30,
+        SegmentGroup::Workspace,
+        layout::project_segment,
+    ),
+    ("git", 1, 50, SegmentGroup::Workspace, layout::git_segment),
+    (
+        "workspace_dirs",
+        1,
+        25,
+        SegmentGroup::Workspace,
+        layout::workspace_dirs_segment,
+    ),
+    (
+        "claude_md",
+        2,
+        40,
+        SegmentGroup::Config,
+        layout::claude_md_segment,
+    ),
+    ("rules", 2, 40, SegmentGroup::Config, layout::rules_segment),
+    (
+        "memory",
+        2,
+        40,
+        SegmentGroup::Config,
+        layout::memory_segment,
+    ),
+    ("hooks", 2, 40, SegmentGroup::Config, layout::hooks_segment),
+    ("mcp", 2, 40, SegmentGroup::Config, layout::mcp_segment),
+    (
+        "skills",
+        2,
+        40,
+        SegmentGroup::Config,
+        layout::skills_segment,
+    ),
+    (
+        "duration",
+        2,
+        60,
+        SegmentGroup::Session,
+        layout::duration_segment,
+    ),
+    (
+        "context",
+        3,
+        100,
+        SegmentGroup::Budget,
+        layout::context_segment,
+    ),
+    (
+        "tokens",
+        3,
+        45,
+        SegmentGroup::Budget,
+        layout::tokens_segment,
+    ),
+    ("cost", 3, 90, SegmentGroup::Budget, layout::cost_segment),
+    (
+        "lines_changed",
+        3,
+        35,
+        SegmentGroup::Budget,
+        layout::lines_changed_segment,
+    ),
+    (
+        "api_time",
+        3,
+        30,
+        SegmentGroup::Budget,
+        layout::api_time_segment,
+    ),
+];
+
+/// Ordered set of core-line segments.
+///
+/// `SegmentRegistry::default()` holds the built-in segments; `register` appends
+/// after them and `insert_before` places a segment ahead of an existing one.
+pub struct SegmentRegistry {
+    segments: Vec<Box<dyn Segment>>,
+}
+
+impl Default for SegmentRegistry {
+    fn default() -> Self {
+        let segments = BUILTIN_SEGMENTS
+            .iter()
+            .map(|&(id, line, priority, group, render)| {
+                Box::new(BuiltinSegment {
+                    id,
+                    line,
+                    priority,
+                    group,
+                    render,
+                }) as Box<dyn Segment>
+            })
+            .collect();
+        Self { segments }
+    }
+}
+
+impl fmt::Debug for SegmentRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.ids()).finish()
+    }
+}
+
+impl SegmentRegistry {
+    /// A registry with no segments, for embedders that build lines from scratch.
+    pub fn empty() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+
+    /// Append a segment after every segment already on its line.
+    pub fn register(&mut self, segment: impl Segment + 'static) -> &mut Self {
+        self.segments.push(Box::new(segment));
+        self
+    }
+
+    /// Insert a segment before the one with id `before`, or append if absent.
+    pub fn insert_before(&mut self, before: &str, segment: impl Segment + 'static) -> &mut Self {
+        let position = self
+            .segments
+            .iter()
+            .position(|s| s.id() == before)
+            .unwrap_or(self.segments.len());
+        self.segments.insert(position, Box::new(segment));
+        self
+    }
+
+    /// Remove every segment with id `id`. Returns true if any was removed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.segments.len();
+        self.segments.retain(|s| s.id() != id);
+        self.segments.len() != before
+    }
+
+    /// Segment ids in display order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().map(|s| s.id())
+    }
+
+    /// Render the spans for core line `line_no` (1-3) in registry order.
+    pub fn render_line(
+        &self,
+        line_no: usize,
+        frame: &RenderFrame,
+        config: &RenderConfig,
+        tier: &EmphasisTier,
+    ) -> Vec<StyledSpan> {
+        self.segments
+            .iter()
+            .filter(|s| s.line().clamp(1, layout::CORE_LINE_COUNT) == line_no)
+            .filter_map(|s| s.render(frame, config, tier))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::color::emphasis_for_theme;
+
+    struct Fixed(&'static str, usize);
+
+    impl Segment for Fixed {
+        fn id(&self) -> &str {
+            self.0
+        }
+
+        fn line(&self) -> usize {
+            self.1
+        }
+
+        fn render(
+            &self,
+            _: &RenderFrame,
+            _: &RenderConfig,
+            _: &EmphasisTier,
+        ) -> Option<StyledSpan> {
+            Some(StyledSpan::new(self.0.to_string(), 50))
+        }
+    }
+
+    #[test]
+    fn styled_span_width_ignores_ansi() {
+        let span = StyledSpan::new("\x1b[38;5;75mabc\x1b[0m".to_string(), 10);
+        assert_eq!(span.width, 3);
+    }
+
+    #[test]
+    fn default_registry_lists_builtins_in_order() {
+        let registry = SegmentRegistry::default();
+        let ids: Vec<&str> = registry.ids().collect();
+        assert_eq!(ids.first(), Some(&"model"));
+        assert_eq!(ids.last(), Some(&"api_time"));
+        assert_eq!(ids.len(), BUILTIN_SEGMENTS.len());
+    }
+
+    #[test]
+    fn register_insert_and_remove() {
+        let mut registry = SegmentRegistry::empty();
+        registry.register(Fixed("b", 1)).register(Fixed("c", 2));
+        registry.insert_before("b", Fixed("a", 1));
+        assert_eq!(registry.ids().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+
+        assert!(registry.remove("b"));
+        assert!(!registry.remove("b"));
+
+        let frame = RenderFrame::default();
+        let config = RenderConfig::default();
+        let tier = emphasis_for_theme(config.color_theme);
+        let line1: Vec<String> = registry
+            .render_line(1, &frame, &config, &tier)
+            .into_iter()
+            .map(|span| span.text)
+            .collect();
+        assert_eq!(line1, vec!["a"]);
+    }
+}
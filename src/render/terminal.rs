@@ -0,0 +1,81 @@
+//! Terminal size detection for width degradation.
+
+/// Width of the controlling terminal in columns, queried with `TIOCGWINSZ` on
+/// `/dev/tty`. Works even when stdin/stdout are pipes, as they are for the
+/// statusline subprocess. Returns None without a controlling terminal.
+#[cfg(unix)]
+pub fn tty_width() -> Option<usize> {
+    use std::{fs::File, os::unix::io::AsRawFd};
+
+    let tty = File::open("/dev/tty").ok()?;
+    // SAFETY: `winsize` is plain old data, so all-zero is a valid value.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    // SAFETY: TIOCGWINSZ writes one `struct winsize` through the pointer, and
+    // `size` is a live, correctly laid out value for the duration of the call.
+    let result = unsafe { libc::ioctl(tty.as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+#[cfg(not(unix))]
+pub fn tty_width() -> Option<usize> {
+    None
+}
+
+/// Width of the terminal the statusline is drawn in: the controlling
+/// terminal, then `COLUMNS`. Detected once by the binary and passed to
+/// `build_render_config_with_width`.
+pub fn detect_width() -> Option<usize> {
+    let columns = std::env::var("COLUMNS").ok();
+    tty_width()
+        .or_else(|| columns.as_deref().and_then(parse_columns))
+        .filter(|w| *w > 0)
+}
+
+fn parse_columns(value: &str) -> Option<usize> {
+    value.trim().parse().ok()
+}
+
+/// Resolve the render width: an explicit `width` wins; otherwise the
+/// `detected` terminal width minus `offset` for the padding Claude Code draws
+/// around the statusline, or the whole width if the offset would leave none.
+pub fn resolve_width(
+    width: Option<usize>,
+    offset: usize,
+    detected: Option<usize>,
+) -> Option<usize> {
+    if let Some(width) = width.filter(|w| *w > 0) {
+        return Some(width);
+    }
+    detected
+        .filter(|w| *w > 0)
+        .map(|w| w.checked_sub(offset).filter(|w| *w > 0).unwrap_or(w))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_width_wins_and_ignores_offset() {
+        assert_eq!(resolve_width(Some(100), 4, Some(80)), Some(100));
+    }
+
+    #[test]
+    fn detected_width_with_offset() {
+        assert_eq!(resolve_width(None, 4, Some(80)), Some(76));
+        assert_eq!(resolve_width(Some(0), 0, None), None);
+        assert_eq!(resolve_width(None, 0, Some(0)), None);
+    }
+
+    #[test]
+    fn offset_never_leaves_zero_columns() {
+        assert_eq!(resolve_width(None, 10, Some(10)), Some(10));
+        assert_eq!(resolve_width(None, 20, Some(8)), Some(8));
+    }
+
+    #[test]
+    fn columns_parsing() {
+        assert_eq!(parse_columns(" 60\n"), Some(60));
+        assert_eq!(parse_columns("junk"), None);
+    }
+}
@@ -2,18 +2,25 @@ use std::{
     collections::HashMap,
     fs,
     hash::{DefaultHasher, Hash, Hasher},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    time::Duration,
 };
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
     providers::{EnvSnapshot, GitSnapshot},
-    types::{AgentSummary, Line3Metrics, PendingTask, TaskItem, TodoSummary, ToolSummary},
+    types::{
+        AgentSummary, AlertState, CustomSegmentEntry, Line3Metrics, MetricHistory, PendingTask,
+        PhaseSignal, TaskItem, TodoSummary, ToolSummary,
+    },
 };
 
 /// TTL for cached env/git snapshots (10 seconds).
 pub const CACHE_TTL_MS: u64 = 10_000;
+/// How long a render or `--hook` run waits for another process to release a
+/// session before going ahead without the lock.
+pub const SESSION_LOCK_WAIT: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry<T> {
@@ -24,6 +31,10 @@ pub struct CacheEntry<T> {
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SessionCache {
+    /// Bumped on every save, so a process can tell whether another one has
+    /// written the session since it last loaded or saved it.
+    #[serde(default)]
+    pub revision: u64,
     // Transcript state
     pub transcript_offset: u64,
     pub transcript_path: Option<String>,
@@ -56,37 +67,159 @@ pub struct SessionCache {
     // Quota fetch spawn throttle
     #[serde(default)]
     pub last_quota_fetch_spawned_ms: Option<u64>,
+    // Hook ingestion
+    #[serde(default)]
+    pub hook_tool_ids: Vec<String>,
+    #[serde(default)]
+    pub last_hook_event: Option<String>,
+    #[serde(default)]
+    pub last_hook_event_ms: Option<u64>,
+    // Session phase per source
+    #[serde(default)]
+    pub hook_phase: Option<PhaseSignal>,
+    #[serde(default)]
+    pub transcript_phase: Option<PhaseSignal>,
+    // Custom command segments
+    #[serde(default)]
+    pub custom_segments: Vec<CustomSegmentEntry>,
+    // Sparkline samples
+    #[serde(default)]
+    pub history: MetricHistory,
+    // `[[alerts]]` debounce state
+    #[serde(default)]
+    pub alerts: Vec<AlertState>,
     // Env/Git with timestamps
     pub env: Option<CacheEntry<EnvSnapshot>>,
     pub git: Option<CacheEntry<GitSnapshot>>,
+    #[serde(default)]
+    pub workspace_git: Vec<CacheEntry<GitSnapshot>>,
+}
+
+/// Where session caches persist between statusline invocations.
+/// Load and save must never fail loudly — a missing cache just means a cold start.
+pub trait CacheStore: Send + Sync {
+    fn load(&self, session_key: &str) -> Option<SessionCache>;
+    fn save(&self, session_key: &str, cache: &SessionCache);
+
+    /// Directory where background jobs leave results for the next render
+    /// (`[[segments.custom]]` output). None, the default, disables those jobs.
+    fn result_dir(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Hold `session_key` exclusively until the returned guard drops, so a
+    /// `--hook` run and a render cannot interleave load and save. None, the
+    /// default, is for stores that are not shared between processes, or when
+    /// the lock was not free within [`SESSION_LOCK_WAIT`].
+    fn lock(&self, _session_key: &str) -> Option<SessionLock> {
+        None
+    }
+}
+
+/// Guard returned by [`CacheStore::lock`]; the lock is released on drop.
+pub struct SessionLock {
+    _guard: Box<dyn Send>,
+}
+
+impl SessionLock {
+    /// Wrap whatever releases the lock when dropped (a locked file, ...).
+    pub fn new(guard: impl Send + 'static) -> Self {
+        Self {
+            _guard: Box::new(guard),
+        }
+    }
 }
 
-/// Compute the cache file path for a session key.
+/// One JSON file per session in `dir`; the default uses the system temp dir.
+#[derive(Debug, Clone)]
+pub struct FileCacheStore {
+    pub dir: PathBuf,
+}
+
+impl FileCacheStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Compute the cache file path for a session key.
+    pub fn path_for(&self, session_key: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        session_key.hash(&mut hasher);
+        let hash = hasher.finish();
+        self.dir.join(format!("cc-pulseline-{hash:x}.json"))
+    }
+}
+
+impl Default for FileCacheStore {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir())
+    }
+}
+
+impl CacheStore for FileCacheStore {
+    /// Returns None on any error.
+    fn load(&self, session_key: &str) -> Option<SessionCache> {
+        let contents = fs::read_to_string(self.path_for(session_key)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Atomic write (tmp + rename). Silently ignores errors.
+    fn save(&self, session_key: &str, cache: &SessionCache) {
+        let path = self.path_for(session_key);
+        let contents = match serde_json::to_string(cache) {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+        let tmp_path = path.with_extension("tmp");
+        if fs::write(&tmp_path, contents).is_ok() {
+            let _ = fs::rename(&tmp_path, &path);
+        }
+    }
+
+    fn result_dir(&self) -> Option<&Path> {
+        Some(&self.dir)
+    }
+
+    /// `flock` on a `.lock` file next to the cache; the kernel drops it if
+    /// the process dies. Not available off Unix.
+    #[cfg(unix)]
+    fn lock(&self, session_key: &str) -> Option<SessionLock> {
+        use std::os::unix::io::AsRawFd;
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.path_for(session_key).with_extension("lock"))
+            .ok()?;
+        let deadline = std::time::Instant::now() + SESSION_LOCK_WAIT;
+        loop {
+            // SAFETY: flock only reads the descriptor, which `file` keeps open.
+            let result = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+            if result == 0 {
+                return Some(SessionLock::new(file));
+            }
+            if std::time::Instant::now() >= deadline {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
+
+/// Compute the cache file path for a session key in the system temp dir.
 pub fn cache_path(session_key: &str) -> PathBuf {
-    let mut hasher = DefaultHasher::new();
-    session_key.hash(&mut hasher);
-    let hash = hasher.finish();
-    std::env::temp_dir().join(format!("cc-pulseline-{hash:x}.json"))
+    FileCacheStore::default().path_for(session_key)
 }
 
-/// Load a session cache from disk. Returns None on any error.
+/// Load a session cache from the system temp dir. Returns None on any error.
 pub fn load_cache(session_key: &str) -> Option<SessionCache> {
-    let path = cache_path(session_key);
-    let contents = fs::read_to_string(path).ok()?;
-    serde_json::from_str(&contents).ok()
+    FileCacheStore::default().load(session_key)
 }
 
-/// Save a session cache to disk with atomic write. Silently ignores errors.
+/// Save a session cache to the system temp dir with atomic write. Silently ignores errors.
 pub fn save_cache(session_key: &str, cache: &SessionCache) {
-    let path = cache_path(session_key);
-    let contents = match serde_json::to_string(cache) {
-        Ok(c) => c,
-        Err(_) => return,
-    };
-    let tmp_path = path.with_extension("tmp");
-    if fs::write(&tmp_path, contents).is_ok() {
-        let _ = fs::rename(&tmp_path, &path);
-    }
+    FileCacheStore::default().save(session_key, cache)
 }
 
 pub fn now_epoch_ms() -> u64 {
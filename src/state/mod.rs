@@ -8,16 +8,27 @@ use std::{
 use crate::{
     providers::{EnvSnapshot, GitSnapshot},
     types::{
-        AgentSummary, CompletedToolCount, Line3Metrics, PendingTask, TaskItem, TodoInProgressItem,
+        AgentSummary, AlertState, CompletedToolCount, CustomSegmentEntry, Line3Metrics,
+        MetricHistory, PendingTask, PhaseSignal, SessionPhase, TaskItem, TodoInProgressItem,
         TodoSummary, ToolSummary,
     },
 };
 use cache::{CacheEntry, SessionCache, CACHE_TTL_MS};
 
 const MAX_RECENT_TOOLS_CAP: usize = 10;
+const MAX_HOOK_TOOL_IDS: usize = 200;
+/// A transcript tool_use still unanswered after this long is treated as a
+/// permission prompt rather than a running tool.
+const PERMISSION_GRACE_MS: u64 = 3_000;
+/// Sparkline samples kept per metric.
+const HISTORY_CAPACITY: usize = 12;
+/// Minimum gap between sparkline samples (12 samples ≈ 2 minutes).
+const HISTORY_SAMPLE_INTERVAL_MS: u64 = 10_000;
 
 #[derive(Debug, Clone, Default)]
 pub struct SessionState {
+    // Revision of the disk cache this state was last loaded from or saved as
+    pub cache_revision: u64,
     pub last_transcript_offset: u64,
     pub last_transcript_path: Option<String>,
     pub last_transcript_poll: Option<Instant>,
@@ -35,6 +46,9 @@ pub struct SessionState {
     pub task_counter: u32,
     pub cached_env: Option<(String, EnvSnapshot)>,
     pub cached_git: Option<(String, GitSnapshot)>,
+    // Additional workspace directories: one snapshot per directory, stamped
+    // when it was collected so it expires after CACHE_TTL_MS across saves
+    pub cached_workspace_git: Vec<CacheEntry<GitSnapshot>>,
     pub cached_line3: Option<Line3Metrics>,
     // Token speed tracking (output only)
     pub last_output_tokens: Option<u64>,
@@ -42,6 +56,19 @@ pub struct SessionState {
     pub output_speed_toks_per_sec: Option<f64>,
     // Quota fetch spawn throttle (epoch ms of last spawn)
     pub last_quota_fetch_spawned_ms: Option<u64>,
+    // Hook ingestion: tool_use ids already applied from hook events (transcript skips them)
+    pub hook_tool_ids: Vec<String>,
+    pub last_hook_event: Option<String>,
+    pub last_hook_event_ms: Option<u64>,
+    // Session phase (working / waiting for permission / idle), per source
+    pub hook_phase: Option<PhaseSignal>,
+    pub transcript_phase: Option<PhaseSignal>,
+    // `[[segments.custom]]` command output, refreshed in the background
+    pub custom_segments: Vec<CustomSegmentEntry>,
+    // Sparkline samples (speed, context %, cost rate)
+    pub history: MetricHistory,
+    // `[[alerts]]` debounce state: which rules are past their threshold
+    pub alerts: Vec<AlertState>,
 }
 
 impl SessionState {
@@ -64,9 +91,80 @@ impl SessionState {
             self.last_output_tokens = None;
             self.last_output_token_time_ms = None;
             self.output_speed_toks_per_sec = None;
+            self.hook_tool_ids.clear();
+            self.last_hook_event = None;
+            self.last_hook_event_ms = None;
+            self.hook_phase = None;
+            self.transcript_phase = None;
+            self.history = MetricHistory::default();
         }
     }
 
+    /// Remember a tool_use id delivered by a hook event, so the transcript
+    /// fallback does not apply the same tool a second time.
+    pub fn mark_hook_tool(&mut self, id: &str) {
+        if self.is_hook_tool(id) {
+            return;
+        }
+        self.hook_tool_ids.push(id.to_string());
+        if self.hook_tool_ids.len() > MAX_HOOK_TOOL_IDS {
+            self.hook_tool_ids
+                .drain(..self.hook_tool_ids.len() - MAX_HOOK_TOOL_IDS);
+        }
+    }
+
+    pub fn is_hook_tool(&self, id: &str) -> bool {
+        self.hook_tool_ids.iter().any(|known| known == id)
+    }
+
+    pub fn record_hook_event(&mut self, event: &str, message: Option<&str>, at_ms: u64) {
+        self.last_hook_event = Some(event.to_string());
+        self.last_hook_event_ms = Some(at_ms);
+
+        let phase = match event {
+            "Notification" => {
+                let message = message.unwrap_or_default().to_ascii_lowercase();
+                if message.contains("permission") {
+                    SessionPhase::WaitingPermission
+                } else {
+                    SessionPhase::Idle
+                }
+            }
+            "Stop" => SessionPhase::Idle,
+            // A subagent finishing says nothing about the main thread
+            "SubagentStop" => return,
+            _ => SessionPhase::Working,
+        };
+        self.hook_phase = Some(next_phase_signal(self.hook_phase, phase, at_ms));
+    }
+
+    pub fn set_transcript_phase(&mut self, phase: SessionPhase, at_ms: u64) {
+        self.transcript_phase = Some(next_phase_signal(self.transcript_phase, phase, at_ms));
+    }
+
+    /// Current session phase from the freshest source; hook events win ties.
+    /// A transcript tool_use only counts as a permission prompt once it has
+    /// gone unanswered for `PERMISSION_GRACE_MS`, and only in sessions without
+    /// hooks: there the Notification hook reports prompts exactly, so a quiet
+    /// tool is a running one.
+    pub fn session_phase(&self, now_ms: u64) -> Option<PhaseSignal> {
+        let signal = match (self.hook_phase, self.transcript_phase) {
+            (Some(hook), Some(transcript)) if transcript.updated_ms > hook.updated_ms => transcript,
+            (None, transcript) => transcript?,
+            (hook, _) => return hook,
+        };
+
+        let prompt_likely = self.last_hook_event.is_none()
+            && now_ms.saturating_sub(signal.since_ms) >= PERMISSION_GRACE_MS;
+        if signal.phase == SessionPhase::WaitingPermission && !prompt_likely {
+            return Some(PhaseSignal {
+                phase: SessionPhase::Working,
+                ..signal
+            });
+        }
+        Some(signal)
+    }
+
     /// Check if enough time has passed since last quota fetch spawn.
     /// Marks the timestamp and returns true if spawning is allowed.
     pub fn should_spawn_quota_fetch(&mut self, cooldown_ms: u64) -> bool {
@@ -109,6 +207,40 @@ impl SessionState {
         result
     }
 
+    /// Append a sparkline sample of speed, context % and cost rate, at most once
+    /// per `HISTORY_SAMPLE_INTERVAL_MS`. Cost rate is the spend since the
+    /// previous sample scaled to $/h, so the trend reflects current burn rather
+    /// than the session average.
+    pub fn record_history(&mut self, line3: &Line3Metrics, now_ms: u64) {
+        let history = &mut self.history;
+        let elapsed_ms = history
+            .last_sample_ms
+            .map(|last| now_ms.saturating_sub(last));
+        if elapsed_ms.is_some_and(|ms| ms < HISTORY_SAMPLE_INTERVAL_MS) {
+            return;
+        }
+
+        if let Some(speed) = line3.output_speed_toks_per_sec {
+            push_sample(&mut history.output_speed, speed);
+        }
+        if let Some(pct) = line3.context_used_percentage {
+            push_sample(&mut history.context_pct, pct as f64);
+        }
+        if let Some(cost) = line3.total_cost_usd {
+            match (history.last_cost_usd, elapsed_ms) {
+                // A lower total means a new session: restart the series
+                (Some(last), _) if cost < last => history.cost_per_hour.clear(),
+                (Some(last), Some(ms)) if ms > 0 => {
+                    let per_hour = (cost - last) / (ms as f64 / 3_600_000.0);
+                    push_sample(&mut history.cost_per_hour, per_hour);
+                }
+                _ => {}
+            }
+            history.last_cost_usd = Some(cost);
+        }
+        history.last_sample_ms = Some(now_ms);
+    }
+
     pub fn cached_env_for(&self, cwd: &str) -> Option<EnvSnapshot> {
         self.cached_env.as_ref().and_then(|(path, snapshot)| {
             if path == cwd {
@@ -137,11 +269,28 @@ impl SessionState {
         self.cached_git = Some((cwd, snapshot));
     }
 
-    pub fn upsert_tool(&mut self, id: String, name: String, target: Option<String>) {
-        if let Some(position) = self.active_tools.iter().position(|tool| tool.id == id) {
+    pub fn cached_workspace_git_for(&self, dir: &str) -> Option<GitSnapshot> {
+        let now = cache::now_epoch_ms();
+        self.cached_workspace_git
+            .iter()
+            .find(|entry| entry.path == dir)
+            .filter(|entry| now.saturating_sub(entry.cached_at_ms) < CACHE_TTL_MS)
+            .map(|entry| entry.snapshot.clone())
+    }
+
+    pub fn set_cached_workspace_git(&mut self, dir: String, snapshot: GitSnapshot) {
+        self.cached_workspace_git.retain(|entry| entry.path != dir);
+        self.cached_workspace_git.push(CacheEntry {
+            path: dir,
+            snapshot,
+            cached_at_ms: cache::now_epoch_ms(),
+        });
+    }
+
+    pub fn upsert_tool(&mut self, tool: ToolSummary) {
+        if let Some(position) = self.active_tools.iter().position(|t| t.id == tool.id) {
             self.active_tools.remove(position);
         }
-        let tool = ToolSummary { id, name, target };
         self.active_tools.push(tool.clone());
 
         // recent_tools: display list (dedup, push, cap)
@@ -346,7 +495,6 @@ impl SessionState {
         let all_done = pending == 0 && completed > 0;
 
         self.todo = Some(TodoSummary {
-            text: format!("{completed}/{total} done, {pending} pending"),
             pending,
             completed,
             total,
@@ -379,6 +527,7 @@ impl SessionState {
         let remaining = max_total.saturating_sub(result.len());
         if remaining > 0 {
             let mut completed: Vec<&AgentSummary> = self.completed_agents.iter().collect();
+            #[allow(clippy::unnecessary_sort_by)]
             completed.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
             for agent in completed.into_iter().take(remaining) {
                 result.push(agent.clone());
@@ -391,6 +540,7 @@ impl SessionState {
     /// Load state from a disk cache. Only restores env/git if within TTL.
     pub fn load_from_cache(&mut self, cache: SessionCache) {
         let now = cache::now_epoch_ms();
+        self.cache_revision = cache.revision;
 
         // Transcript state
         self.last_transcript_offset = cache.transcript_offset;
@@ -410,6 +560,14 @@ impl SessionState {
         self.last_output_token_time_ms = cache.last_output_token_time_ms;
         self.output_speed_toks_per_sec = cache.output_speed_toks_per_sec;
         self.last_quota_fetch_spawned_ms = cache.last_quota_fetch_spawned_ms;
+        self.hook_tool_ids = cache.hook_tool_ids;
+        self.last_hook_event = cache.last_hook_event;
+        self.last_hook_event_ms = cache.last_hook_event_ms;
+        self.hook_phase = cache.hook_phase;
+        self.transcript_phase = cache.transcript_phase;
+        self.custom_segments = cache.custom_segments;
+        self.alerts = cache.alerts;
+        self.history = cache.history;
 
         // Env/Git only if within TTL
         if let Some(entry) = cache.env {
@@ -422,12 +580,57 @@ impl SessionState {
                 self.cached_git = Some((entry.path, entry.snapshot));
             }
         }
+        self.cached_workspace_git = cache
+            .workspace_git
+            .into_iter()
+            .filter(|entry| now.saturating_sub(entry.cached_at_ms) < CACHE_TTL_MS)
+            .collect();
+    }
+
+    /// Fold in what another process saved since this state was loaded.
+    /// `--hook` runs and renders are separate processes sharing one cache
+    /// file and normally take turns under [`cache::CacheStore::lock`]; when
+    /// one went ahead without the lock, the newer hook event (phase and tool
+    /// ids) and the newer debounce state of each alert are kept rather than
+    /// overwritten.
+    pub fn merge_concurrent(&mut self, disk: SessionCache) {
+        // A different transcript means the session was reset since
+        if disk.transcript_path.is_some()
+            && self.last_transcript_path.is_some()
+            && disk.transcript_path != self.last_transcript_path
+        {
+            return;
+        }
+
+        for id in &disk.hook_tool_ids {
+            self.mark_hook_tool(id);
+        }
+        if disk.last_hook_event_ms > self.last_hook_event_ms {
+            self.last_hook_event = disk.last_hook_event;
+            self.last_hook_event_ms = disk.last_hook_event_ms;
+            self.hook_phase = disk.hook_phase;
+        }
+
+        for theirs in disk.alerts {
+            match self.alerts.iter_mut().find(|ours| ours.name == theirs.name) {
+                Some(ours) => {
+                    let newer = theirs.rule == ours.rule
+                        && (theirs.fired_at_ms > ours.fired_at_ms
+                            || theirs.last_event_ms > ours.last_event_ms);
+                    if newer {
+                        *ours = theirs;
+                    }
+                }
+                None => self.alerts.push(theirs),
+            }
+        }
     }
 
     /// Export current state to a cache struct for disk persistence.
     pub fn to_cache(&self) -> SessionCache {
         let now = cache::now_epoch_ms();
         SessionCache {
+            revision: self.cache_revision,
             transcript_offset: self.last_transcript_offset,
             transcript_path: self.last_transcript_path.clone(),
             active_tools: self.active_tools.clone(),
@@ -445,6 +648,14 @@ impl SessionState {
             last_output_token_time_ms: self.last_output_token_time_ms,
             output_speed_toks_per_sec: self.output_speed_toks_per_sec,
             last_quota_fetch_spawned_ms: self.last_quota_fetch_spawned_ms,
+            hook_tool_ids: self.hook_tool_ids.clone(),
+            last_hook_event: self.last_hook_event.clone(),
+            last_hook_event_ms: self.last_hook_event_ms,
+            hook_phase: self.hook_phase,
+            transcript_phase: self.transcript_phase,
+            custom_segments: self.custom_segments.clone(),
+            alerts: self.alerts.clone(),
+            history: self.history.clone(),
             env: self.cached_env.as_ref().map(|(path, snapshot)| CacheEntry {
                 path: path.clone(),
                 snapshot: snapshot.clone(),
@@ -455,11 +666,37 @@ impl SessionState {
                 snapshot: snapshot.clone(),
                 cached_at_ms: now,
             }),
+            workspace_git: self.cached_workspace_git.clone(),
         }
     }
 }
 
+/// Keep `since_ms` while the phase is unchanged so "idle for 4m" keeps counting.
+fn next_phase_signal(
+    previous: Option<PhaseSignal>,
+    phase: SessionPhase,
+    at_ms: u64,
+) -> PhaseSignal {
+    let since_ms = match previous {
+        Some(previous) if previous.phase == phase => previous.since_ms,
+        _ => at_ms,
+    };
+    PhaseSignal {
+        phase,
+        since_ms,
+        updated_ms: at_ms,
+    }
+}
+
+fn push_sample(samples: &mut Vec<f64>, value: f64) {
+    samples.push(value);
+    if samples.len() > HISTORY_CAPACITY {
+        samples.drain(..samples.len() - HISTORY_CAPACITY);
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod tests {
     use super::*;
 
@@ -560,4 +797,161 @@ mod tests {
         state.last_quota_fetch_spawned_ms = Some(now.saturating_sub(20_000));
         assert!(state.should_spawn_quota_fetch(15_000));
     }
+
+    #[test]
+    fn session_phase_prefers_fresher_source() {
+        let mut state = SessionState::default();
+        state.set_transcript_phase(SessionPhase::Working, 1_000);
+        state.record_hook_event("Stop", None, 2_000);
+        assert_eq!(
+            state.session_phase(10_000).map(|s| s.phase),
+            Some(SessionPhase::Idle)
+        );
+
+        state.set_transcript_phase(SessionPhase::Working, 3_000);
+        assert_eq!(
+            state.session_phase(10_000).map(|s| s.phase),
+            Some(SessionPhase::Working)
+        );
+    }
+
+    #[test]
+    fn merge_concurrent_keeps_newer_hook_and_alert_state() {
+        let mut render = SessionState::default();
+        render.record_hook_event("PreToolUse", None, 1_000);
+        render.mark_hook_tool("toolu_a");
+        render.alerts.push(AlertState {
+            name: "ctx".to_string(),
+            rule: "context>=80".to_string(),
+            active: false,
+            ..Default::default()
+        });
+
+        // Meanwhile a hook process saved a permission prompt, and another
+        // render fired the alert
+        let mut other = render.clone();
+        other.record_hook_event("Notification", Some("needs your permission"), 2_000);
+        other.mark_hook_tool("toolu_b");
+        other.alerts[0].active = true;
+        other.alerts[0].fired_at_ms = Some(2_000);
+
+        render.merge_concurrent(other.to_cache());
+        assert_eq!(
+            render.session_phase(10_000).map(|s| s.phase),
+            Some(SessionPhase::WaitingPermission)
+        );
+        assert!(render.is_hook_tool("toolu_a") && render.is_hook_tool("toolu_b"));
+        assert!(render.alerts[0].active);
+
+        // Older state on disk does not roll ours back
+        let stale = SessionState::default().to_cache();
+        render.merge_concurrent(stale);
+        assert_eq!(render.last_hook_event_ms, Some(2_000));
+        assert!(render.alerts[0].active);
+    }
+
+    #[test]
+    fn transcript_tool_use_waits_out_permission_grace() {
+        let mut state = SessionState::default();
+        state.set_transcript_phase(SessionPhase::WaitingPermission, 1_000);
+        assert_eq!(
+            state.session_phase(2_000).map(|s| s.phase),
+            Some(SessionPhase::Working),
+            "fresh tool_use is probably just running"
+        );
+        assert_eq!(
+            state
+                .session_phase(1_000 + PERMISSION_GRACE_MS)
+                .map(|s| s.phase),
+            Some(SessionPhase::WaitingPermission)
+        );
+
+        // With hooks installed, a prompt would have sent a Notification
+        state.record_hook_event("PreToolUse", None, 500);
+        assert_eq!(
+            state.session_phase(60_000).map(|s| s.phase),
+            Some(SessionPhase::Working)
+        );
+    }
+
+    #[test]
+    fn repeated_idle_keeps_original_since() {
+        let mut state = SessionState::default();
+        state.record_hook_event("Stop", None, 1_000);
+        state.record_hook_event(
+            "Notification",
+            Some("Claude is waiting for your input"),
+            61_000,
+        );
+        let signal = state.session_phase(70_000).expect("phase recorded");
+        assert_eq!(signal.phase, SessionPhase::Idle);
+        assert_eq!(signal.since_ms, 1_000);
+    }
+
+    #[test]
+    fn record_history_samples_at_interval_and_derives_cost_rate() {
+        let mut state = SessionState::default();
+        let mut line3 = Line3Metrics {
+            context_used_percentage: Some(10),
+            total_cost_usd: Some(1.0),
+            output_speed_toks_per_sec: Some(40.0),
+            ..Default::default()
+        };
+        state.record_history(&line3, 0);
+        // Within the sample interval: ignored
+        line3.context_used_percentage = Some(99);
+        state.record_history(&line3, HISTORY_SAMPLE_INTERVAL_MS - 1);
+        assert_eq!(state.history.context_pct, vec![10.0]);
+
+        // $0.50 over 36s = $50/h
+        line3.context_used_percentage = Some(20);
+        line3.total_cost_usd = Some(1.5);
+        state.record_history(&line3, 36_000);
+        assert_eq!(state.history.context_pct, vec![10.0, 20.0]);
+        assert_eq!(state.history.output_speed, vec![40.0, 40.0]);
+        assert_eq!(state.history.cost_per_hour.len(), 1);
+        assert!((state.history.cost_per_hour[0] - 50.0).abs() < 1e-9);
+
+        // Cost going down means a new session: the rate series restarts
+        line3.total_cost_usd = Some(0.1);
+        state.record_history(&line3, 72_000);
+        assert!(state.history.cost_per_hour.is_empty());
+    }
+
+    #[test]
+    fn record_history_caps_series_length() {
+        let mut state = SessionState::default();
+        let line3 = Line3Metrics {
+            context_used_percentage: Some(5),
+            ..Default::default()
+        };
+        for i in 0..(HISTORY_CAPACITY as u64 + 5) {
+            state.record_history(&line3, i * HISTORY_SAMPLE_INTERVAL_MS);
+        }
+        assert_eq!(state.history.context_pct.len(), HISTORY_CAPACITY);
+    }
+
+    #[test]
+    fn workspace_git_keeps_its_fetch_time_across_saves() {
+        let snapshot = GitSnapshot {
+            branch: "main".to_string(),
+            ..Default::default()
+        };
+        let mut state = SessionState::default();
+        state.set_cached_workspace_git("/repo/api".to_string(), snapshot.clone());
+        state.set_cached_workspace_git("/repo/web".to_string(), snapshot.clone());
+        let fetched_at = state.cached_workspace_git[0].cached_at_ms;
+        state.cached_workspace_git[1].cached_at_ms -= CACHE_TTL_MS;
+
+        let saved = state.to_cache();
+        assert_eq!(saved.workspace_git[0].cached_at_ms, fetched_at);
+
+        let mut reloaded = SessionState::default();
+        reloaded.load_from_cache(saved);
+        assert_eq!(
+            reloaded.cached_workspace_git_for("/repo/api"),
+            Some(snapshot)
+        );
+        assert_eq!(reloaded.cached_workspace_git_for("/repo/web"), None);
+    }
 }
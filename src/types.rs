@@ -21,24 +21,70 @@ impl StdinPayload {
             .or_else(|| self.cwd.clone())
     }
 
+    /// Project root Claude Code was launched in (`workspace.project_dir`),
+    /// falling back to the current directory when the payload omits it.
+    pub fn resolve_project_root(&self) -> Option<String> {
+        self.workspace
+            .as_ref()
+            .and_then(|workspace| workspace.project_dir.clone())
+            .or_else(|| self.resolve_project_path())
+    }
+
     pub fn resolve_project_path_display(&self) -> String {
         let raw_path = self
-            .resolve_project_path()
+            .resolve_project_root()
             .unwrap_or_else(|| "unknown".to_string());
+        display_path(&raw_path)
+    }
 
-        // Cross-platform home directory detection
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .ok();
+    /// Where cwd has drifted relative to the project root, or None when they match.
+    /// Inside the root this is the relative path (`src/api`); outside it is the
+    /// home-shortened absolute path.
+    pub fn resolve_cwd_drift(&self) -> Option<String> {
+        let root = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.project_dir.as_deref())?;
+        let current = self.resolve_project_path()?;
 
-        if let Some(home_dir) = home {
-            if raw_path.starts_with(&home_dir) {
-                return raw_path.replacen(&home_dir, "~", 1);
-            }
+        let root = root.trim_end_matches('/');
+        let current = current.trim_end_matches('/');
+        if root == current {
+            return None;
         }
 
-        raw_path
+        match current
+            .strip_prefix(root)
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            Some(relative) => Some(relative.to_string()),
+            None => Some(display_path(current)),
+        }
+    }
+
+    /// Additional directories attached to the session (`/add-dir`, `--add-dir`).
+    pub fn added_dirs(&self) -> &[String] {
+        self.workspace
+            .as_ref()
+            .map(|workspace| workspace.added_dirs.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+/// Replace a leading home directory with `~` for display.
+pub fn display_path(raw_path: &str) -> String {
+    // Cross-platform home directory detection
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok();
+
+    if let Some(home_dir) = home {
+        if raw_path.starts_with(&home_dir) {
+            return raw_path.replacen(&home_dir, "~", 1);
+        }
     }
+
+    raw_path.to_string()
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -55,6 +101,9 @@ pub struct OutputStyleInfo {
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct WorkspaceInfo {
     pub current_dir: Option<String>,
+    pub project_dir: Option<String>,
+    #[serde(default)]
+    pub added_dirs: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -76,6 +125,37 @@ pub struct CurrentUsage {
 pub struct CostInfo {
     pub total_cost_usd: Option<f64>,
     pub total_duration_ms: Option<u64>,
+    pub total_api_duration_ms: Option<u64>,
+    pub total_lines_added: Option<u64>,
+    pub total_lines_removed: Option<u64>,
+}
+
+/// Claude Code hook event JSON, as received on stdin in `--hook` mode.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct HookPayload {
+    pub session_id: Option<String>,
+    pub transcript_path: Option<String>,
+    pub cwd: Option<String>,
+    pub hook_event_name: Option<String>,
+    pub tool_name: Option<String>,
+    pub tool_input: Option<serde_json::Value>,
+    pub tool_response: Option<serde_json::Value>,
+    pub tool_use_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub message: Option<String>,
+}
+
+impl HookPayload {
+    /// The statusline payload fields that identify the same session, so hook
+    /// updates land in the cache the statusline renders from.
+    pub fn session_payload(&self) -> StdinPayload {
+        StdinPayload {
+            session_id: self.session_id.clone(),
+            cwd: self.cwd.clone(),
+            transcript_path: self.transcript_path.clone(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -84,6 +164,8 @@ pub struct Line1Metrics {
     pub output_style: String,
     pub claude_code_version: String,
     pub project_path: String,
+    /// Absolute project root behind `project_path`, used for hyperlinks.
+    pub project_root: Option<String>,
     pub git_branch: String,
     pub git_dirty: bool,
     pub git_ahead: u32,
@@ -92,6 +174,41 @@ pub struct Line1Metrics {
     pub git_added: u32,
     pub git_deleted: u32,
     pub git_untracked: u32,
+    /// Web URL of the repository's `origin` remote, used for branch hyperlinks.
+    pub git_remote_url: Option<String>,
+    /// cwd relative to the project root when they differ (see `resolve_cwd_drift`).
+    pub cwd_drift: Option<String>,
+    /// Compact git status for each additional workspace directory.
+    pub extra_dirs: Vec<WorkspaceDirStatus>,
+    /// What Claude is doing right now, from hook events and transcript state.
+    pub session_phase: Option<PhaseSignal>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPhase {
+    Working,
+    WaitingPermission,
+    Idle,
+}
+
+/// A session phase observation: `since_ms` is when this phase began,
+/// `updated_ms` when it was last confirmed (used to pick the freshest source).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseSignal {
+    pub phase: SessionPhase,
+    pub since_ms: u64,
+    pub updated_ms: u64,
+}
+
+/// Git summary for an additional workspace directory, rendered as `+api:main*`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceDirStatus {
+    /// Last path component of the directory.
+    pub name: String,
+    /// Branch name, or None when the directory is not a git repository.
+    pub branch: Option<String>,
+    pub dirty: bool,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -115,6 +232,12 @@ pub struct Line3Metrics {
     pub cache_read_tokens: Option<u64>,
     pub total_cost_usd: Option<f64>,
     pub total_duration_ms: Option<u64>,
+    #[serde(default)]
+    pub total_api_duration_ms: Option<u64>,
+    #[serde(default)]
+    pub lines_added: Option<u64>,
+    #[serde(default)]
+    pub lines_removed: Option<u64>,
     /// Output speed in tokens/second (independently computed, NOT from payload).
     #[serde(default)]
     pub output_speed_toks_per_sec: Option<f64>,
@@ -131,6 +254,31 @@ impl Line3Metrics {
             || self.cache_read_tokens.is_some()
             || self.total_cost_usd.is_some()
             || self.total_duration_ms.is_some()
+            || self.total_api_duration_ms.is_some()
+            || self.lines_added.is_some()
+            || self.lines_removed.is_some()
+    }
+
+    /// Total lines changed (added + removed), None when the payload reports neither.
+    pub fn lines_changed(&self) -> Option<u64> {
+        match (self.lines_added, self.lines_removed) {
+            (None, None) => None,
+            (added, removed) => Some(added.unwrap_or(0) + removed.unwrap_or(0)),
+        }
+    }
+
+    /// Cost per 100 lines changed; None without cost or with no lines changed.
+    pub fn cost_per_100_lines(&self) -> Option<f64> {
+        let cost = self.total_cost_usd?;
+        let lines = self.lines_changed().filter(|lines| *lines > 0)?;
+        Some(cost / lines as f64 * 100.0)
+    }
+
+    /// API time as a percentage of wall-clock session time.
+    pub fn api_time_share_pct(&self) -> Option<f64> {
+        let api = self.total_api_duration_ms?;
+        let wall = self.total_duration_ms.filter(|wall| *wall > 0)?;
+        Some((api as f64 / wall as f64 * 100.0).min(100.0))
     }
 }
 
@@ -139,6 +287,12 @@ pub struct ToolSummary {
     pub id: String,
     pub name: String,
     pub target: Option<String>,
+    /// Full path behind a file tool's truncated `target`, used for hyperlinks.
+    #[serde(default)]
+    pub file_path: Option<String>,
+    /// Line the tool starts at (Read `offset`), when known.
+    #[serde(default)]
+    pub file_line: Option<u64>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -147,6 +301,50 @@ pub struct CompletedToolCount {
     pub count: u32,
 }
 
+/// Cached output of a `[[segments.custom]]` command, persisted in the session cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomSegmentEntry {
+    pub name: String,
+    /// Command that produced `value`; a config change invalidates the entry.
+    pub command: String,
+    pub value: Option<String>,
+    pub fetched_at_ms: Option<u64>,
+    /// When a background refresh was last spawned (throttles re-spawning).
+    pub refresh_spawned_ms: Option<u64>,
+}
+
+/// Debounce state of one `[[alerts]]` rule, persisted in the session cache.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertState {
+    pub name: String,
+    /// Rule the state belongs to (`metric>=threshold`); a config change resets it.
+    pub rule: String,
+    /// The metric is past the threshold; cleared once it drops back below.
+    pub active: bool,
+    /// Newest event already alerted on (completion time for `agent_done`).
+    pub last_event_ms: Option<u64>,
+    pub fired_at_ms: Option<u64>,
+}
+
+/// Recent L3 samples for sparklines, oldest first. Persisted in the session
+/// cache; see `SessionState::record_history` for the sampling cadence.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricHistory {
+    pub output_speed: Vec<f64>,
+    pub context_pct: Vec<f64>,
+    /// Cost per hour between successive samples (not the session average).
+    pub cost_per_hour: Vec<f64>,
+    pub last_sample_ms: Option<u64>,
+    pub last_cost_usd: Option<f64>,
+}
+
+/// A custom segment value ready to render; placement and color come from config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomSegmentValue {
+    pub name: String,
+    pub text: String,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PendingTask {
     pub tool_use_id: String,
@@ -182,14 +380,72 @@ impl AgentSummary {
     }
 }
 
+/// A model-specific weekly window, render-ready.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelQuotaMetrics {
+    /// Lowercase model family, e.g. `opus`.
+    pub model: String,
+    pub pct: f64,
+    pub reset_minutes: Option<u64>,
+}
+
+impl ModelQuotaMetrics {
+    /// Whether this bucket counts usage of `model` (a display name or id).
+    pub fn applies_to(&self, model: &str) -> bool {
+        model.to_ascii_lowercase().contains(&self.model)
+    }
+}
+
+/// Linear projection of a quota window's utilisation to its reset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuotaProjection {
+    /// The limit is reached this many minutes from now, before the reset.
+    LimitIn { minutes: u64 },
+    /// Utilisation expected when the window resets.
+    AtReset { pct: f64 },
+}
+
+impl QuotaProjection {
+    /// Extrapolate `pct` at `pct_per_hour` over the `reset_minutes` left.
+    /// None without a slope or reset time, or once the limit is already hit.
+    pub fn project(
+        pct: Option<f64>,
+        pct_per_hour: Option<f64>,
+        reset_minutes: Option<u64>,
+    ) -> Option<Self> {
+        let (pct, rate) = (pct?, pct_per_hour?.max(0.0));
+        let reset_minutes = reset_minutes?;
+        if pct >= 100.0 {
+            return None;
+        }
+        let at_reset = pct + rate * reset_minutes as f64 / 60.0;
+        if at_reset >= 100.0 {
+            let minutes = ((100.0 - pct) / rate * 60.0).round() as u64;
+            Some(Self::LimitIn { minutes })
+        } else {
+            Some(Self::AtReset { pct: at_reset })
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct QuotaMetrics {
     pub five_hour_pct: Option<f64>,
     pub five_hour_reset_minutes: Option<u64>,
     pub seven_day_pct: Option<f64>,
     pub seven_day_reset_minutes: Option<u64>,
+    /// Local wall-clock reset times, filled when `absolute_reset` is on.
+    pub five_hour_reset_local: Option<crate::providers::timezone::LocalTime>,
+    pub seven_day_reset_local: Option<crate::providers::timezone::LocalTime>,
+    /// Where the current burn rate leads by the reset, when a slope is known.
+    pub five_hour_projection: Option<QuotaProjection>,
+    pub seven_day_projection: Option<QuotaProjection>,
+    pub model_buckets: Vec<ModelQuotaMetrics>,
+    pub extra_usage: Option<crate::providers::quota::ExtraUsage>,
     pub plan_type: Option<String>,
     pub available: bool,
+    /// Values are from an earlier fetch; the latest one failed.
+    pub stale: bool,
 }
 
 impl QuotaMetrics {
@@ -204,9 +460,42 @@ impl QuotaMetrics {
             five_hour_reset_minutes: snapshot.five_hour_reset_at.map(reset_to_minutes),
             seven_day_pct: snapshot.seven_day_pct,
             seven_day_reset_minutes: snapshot.seven_day_reset_at.map(reset_to_minutes),
+            five_hour_projection: QuotaProjection::project(
+                snapshot.five_hour_pct,
+                snapshot.five_hour_pct_per_hour,
+                snapshot.five_hour_reset_at.map(reset_to_minutes),
+            ),
+            seven_day_projection: QuotaProjection::project(
+                snapshot.seven_day_pct,
+                snapshot.seven_day_pct_per_hour,
+                snapshot.seven_day_reset_at.map(reset_to_minutes),
+            ),
+            model_buckets: snapshot
+                .model_buckets
+                .iter()
+                .map(|bucket| ModelQuotaMetrics {
+                    model: bucket.model.clone(),
+                    pct: bucket.pct,
+                    reset_minutes: bucket.reset_at.map(reset_to_minutes),
+                })
+                .collect(),
+            extra_usage: snapshot.extra_usage.clone(),
             available: snapshot.available,
+            stale: snapshot.stale,
+            ..Self::default()
         }
     }
+
+    /// Fill the local reset times from the snapshot's timestamps in `zone`.
+    pub fn with_local_resets(
+        mut self,
+        snapshot: &crate::providers::quota::QuotaSnapshot,
+        zone: &crate::providers::timezone::TimeZone,
+    ) -> Self {
+        self.five_hour_reset_local = snapshot.five_hour_reset_at.map(|ms| zone.local_time(ms));
+        self.seven_day_reset_local = snapshot.seven_day_reset_at.map(|ms| zone.local_time(ms));
+        self
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -218,7 +507,6 @@ pub struct TodoInProgressItem {
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TodoSummary {
-    pub text: String,
     pub pending: usize,
     pub completed: usize,
     pub total: usize,
@@ -240,6 +528,8 @@ pub struct RenderFrame {
     pub agents: Vec<AgentSummary>,
     pub todo: Option<TodoSummary>,
     pub quota: QuotaMetrics,
+    pub custom: Vec<CustomSegmentValue>,
+    pub history: MetricHistory,
 }
 
 impl RenderFrame {
@@ -279,6 +569,7 @@ impl RenderFrame {
                 output_style,
                 claude_code_version,
                 project_path,
+                project_root: payload.resolve_project_root(),
                 git_branch: "unknown".to_string(),
                 git_dirty: false,
                 git_ahead: 0,
@@ -287,6 +578,10 @@ impl RenderFrame {
                 git_added: 0,
                 git_deleted: 0,
                 git_untracked: 0,
+                git_remote_url: None,
+                cwd_drift: payload.resolve_cwd_drift(),
+                extra_dirs: Vec::new(),
+                session_phase: None,
             },
             line2: Line2Metrics {
                 claude_md_count: 0,
@@ -309,6 +604,18 @@ impl RenderFrame {
                     .cost
                     .as_ref()
                     .and_then(|cost| cost.total_duration_ms),
+                total_api_duration_ms: payload
+                    .cost
+                    .as_ref()
+                    .and_then(|cost| cost.total_api_duration_ms),
+                lines_added: payload
+                    .cost
+                    .as_ref()
+                    .and_then(|cost| cost.total_lines_added),
+                lines_removed: payload
+                    .cost
+                    .as_ref()
+                    .and_then(|cost| cost.total_lines_removed),
                 output_speed_toks_per_sec: None,
             },
             tools: Vec::new(),
@@ -316,6 +623,8 @@ impl RenderFrame {
             agents: Vec::new(),
             todo: None,
             quota: QuotaMetrics::default(),
+            custom: Vec::new(),
+            history: MetricHistory::default(),
         }
     }
 }
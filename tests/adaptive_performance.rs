@@ -4,7 +4,12 @@ use std::{
     time::{Duration, Instant},
 };
 
-use cc_pulseline::{config::RenderConfig, render::color::visible_width, PulseLineRunner};
+use cc_pulseline::{
+    config::{GlyphMode, RenderConfig, WidthDegradeStrategy},
+    render::{color::visible_width, layout::render_frame},
+    types::RenderFrame,
+    PulseLineRunner,
+};
 use serde_json::json;
 use tempfile::TempDir;
 
@@ -83,38 +88,125 @@ fn degrades_layout_for_narrow_terminal_widths() {
         "wide render should include activity lines"
     );
 
-    let narrow = RenderConfig {
+    let narrow = |width| RenderConfig {
         transcript_poll_throttle_ms: 0,
-        terminal_width: Some(36),
+        terminal_width: Some(width),
         ..RenderConfig::default()
     };
     let narrow_lines = runner
-        .run_from_str(&payload, narrow)
+        .run_from_str(&payload, narrow(36))
         .expect("narrow render should succeed");
 
+    assert!(
+        narrow_lines.iter().all(|line| visible_width(line) <= 36),
+        "all lines should fit target width"
+    );
     assert_eq!(
         narrow_lines.len(),
-        3,
-        "narrow render should prioritize core lines"
+        wide_lines.len(),
+        "activity lines that fit should survive degradation: got {narrow_lines:?}"
     );
+
+    // Only the activity lines that still do not fit are dropped
+    let narrower_lines = runner
+        .run_from_str(&payload, narrow(16))
+        .expect("narrow render should succeed");
     assert!(
-        narrow_lines.iter().all(|line| visible_width(line) <= 36),
+        narrower_lines.iter().all(|line| visible_width(line) <= 16),
         "all lines should fit target width"
     );
     assert!(
-        narrow_lines.iter().all(|line| !line.starts_with("T:")),
-        "tool lines should be dropped during degradation"
+        narrower_lines[3..]
+            .iter()
+            .any(|line| line.starts_with("T:")),
+        "short tool line should be kept: got {narrower_lines:?}"
     );
     assert!(
-        narrow_lines.iter().all(|line| !line.starts_with("A:")),
-        "agent lines should be dropped during degradation"
+        narrower_lines.iter().all(|line| !line.starts_with("TODO:")),
+        "too-wide todo line should be dropped, not truncated: got {narrower_lines:?}"
+    );
+}
+
+fn plain_width_config(width: usize) -> RenderConfig {
+    RenderConfig {
+        color_enabled: false,
+        glyph_mode: GlyphMode::Ascii,
+        terminal_width: Some(width),
+        ..RenderConfig::default()
+    }
+}
+
+fn identity_frame() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line1.model = "Opus".to_string();
+    frame.line1.output_style = "default".to_string();
+    frame.line1.claude_code_version = "2.1.0".to_string();
+    frame.line1.project_path = "~/app".to_string();
+    frame.line1.git_branch = "main".to_string();
+    frame.line3.context_used_percentage = Some(72);
+    frame.line3.context_window_size = Some(200_000);
+    frame.line3.total_cost_usd = Some(9.25);
+    frame.line3.total_duration_ms = Some(5_400_000);
+    frame
+}
+
+#[test]
+fn narrow_widths_drop_lowest_priority_segments_whole() {
+    let frame = identity_frame();
+    let line1_at = |width| render_frame(&frame, &plain_width_config(width))[0].clone();
+
+    assert_eq!(
+        line1_at(120),
+        "M:Opus | S:default | CC:2.1.0 | P:~/app | G:main"
+    );
+    assert_eq!(line1_at(40), "M:Opus | S:default | P:~/app | G:main");
+    assert_eq!(line1_at(30), "M:Opus | P:~/app | G:main");
+    assert_eq!(line1_at(20), "M:Opus | G:main");
+}
+
+#[test]
+#[allow(deprecated)]
+fn compress_core_lines_still_drops_segments() {
+    let parsed: WidthDegradeStrategy =
+        serde_json::from_str("\"compress_core_lines\"").expect("old name should parse");
+    assert_eq!(parsed, WidthDegradeStrategy::DropLowPrioritySegments);
+
+    let frame = identity_frame();
+    let config = RenderConfig {
+        degrade_order: vec![WidthDegradeStrategy::CompressCoreLines],
+        ..plain_width_config(30)
+    };
+    assert_eq!(
+        render_frame(&frame, &config)[0],
+        "M:Opus | P:~/app | G:main"
     );
+}
+
+#[test]
+fn context_and_cost_outlive_other_budget_segments() {
+    let frame = identity_frame();
+    let line3 = render_frame(&frame, &plain_width_config(45))[2].clone();
+
+    assert!(line3.starts_with("CTX:72%"), "context kept: got {line3}");
+    assert!(line3.contains("$9.25"), "cost kept: got {line3}");
+    assert!(!line3.contains("TOK"), "tokens dropped first: got {line3}");
     assert!(
-        narrow_lines.iter().all(|line| !line.starts_with("TODO:")),
-        "todo lines should be dropped during degradation"
+        !line3.contains("..."),
+        "no mid-segment truncation: got {line3}"
     );
 }
 
+#[test]
+fn cjk_project_path_is_measured_in_columns() {
+    let mut frame = identity_frame();
+    frame.line1.project_path = "~/專案/前端".to_string();
+
+    // "M:Opus | S:default | CC:2.1.0 | P:~/專案/前端 | G:main" is 54 columns (50 chars)
+    let lines = render_frame(&frame, &plain_width_config(50));
+    assert_eq!(lines[0], "M:Opus | S:default | P:~/專案/前端 | G:main");
+    assert!(lines.iter().all(|line| visible_width(line) <= 50));
+}
+
 fn write_large_transcript(path: &std::path::Path, iterations: usize) {
     let mut file = OpenOptions::new()
         .create(true)
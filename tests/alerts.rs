@@ -0,0 +1,114 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use cc_pulseline::{
+    alerts::AlertRule,
+    config::{AlertConfig, RenderConfig},
+    providers::{StubEnvCollector, StubGitCollector, StubTranscriptCollector},
+    state::cache::FileCacheStore,
+    PulseLineRunner,
+};
+use serde_json::json;
+use tempfile::TempDir;
+
+fn payload(session_id: &str, cwd: &str, context_pct: u64) -> String {
+    json!({
+        "session_id": session_id,
+        "cwd": cwd,
+        "model": {"display_name": "Opus"},
+        "context_window": {"context_window_size": 200000, "used_percentage": context_pct},
+        "cost": {"total_cost_usd": 1.25, "total_duration_ms": 60000}
+    })
+    .to_string()
+}
+
+fn alert(name: &str, action: &str, command: Option<String>) -> AlertRule {
+    AlertRule::from_config(&AlertConfig {
+        name: name.to_string(),
+        metric: "context".to_string(),
+        threshold: Some(80.0),
+        action: action.to_string(),
+        command,
+        message: None,
+    })
+    .unwrap()
+}
+
+fn runner(cache_dir: &TempDir) -> PulseLineRunner {
+    PulseLineRunner::default()
+        .with_env_collector(StubEnvCollector)
+        .with_git_collector(StubGitCollector::default())
+        .with_transcript_collector(StubTranscriptCollector)
+        .with_cache_store(FileCacheStore::new(cache_dir.path()))
+}
+
+#[test]
+fn notify_alert_is_appended_once_per_crossing() {
+    let cache_dir = TempDir::new().unwrap();
+    let config = RenderConfig {
+        alerts: vec![alert("ctx", "notify", None)],
+        ..RenderConfig::default()
+    };
+    let render = |pct| {
+        // A fresh runner each time: the debounce state must come from the cache
+        runner(&cache_dir)
+            .run_from_str(
+                &payload("alerts-notify", "/alerts/project", pct),
+                config.clone(),
+            )
+            .expect("render should succeed")
+    };
+
+    let quiet = render(50);
+    assert!(!quiet.concat().contains('\x07'), "{quiet:?}");
+
+    let lines = render(85);
+    let last = lines.last().unwrap();
+    assert!(
+        last.ends_with("\x1b]9;Context at 85%\x07"),
+        "notification should close the output: {last:?}"
+    );
+    assert_eq!(lines.iter().filter(|line| line.contains('\x07')).count(), 1);
+
+    assert!(
+        !render(90).concat().contains('\x07'),
+        "no repeat while high"
+    );
+    render(40);
+    assert!(render(82).concat().contains("\x1b]9;Context at 82%\x07"));
+}
+
+#[cfg(unix)]
+#[test]
+fn command_alert_runs_with_alert_env() {
+    let cache_dir = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let command = "printf '%s|%s|%s|%s' \"$PULSELINE_ALERT\" \"$PULSELINE_ALERT_METRIC\" \"$PULSELINE_ALERT_VALUE\" \"$PULSELINE_ALERT_MESSAGE\" > alert.out";
+    let config = RenderConfig {
+        alerts: vec![alert("ctx", "command", Some(command.to_string()))],
+        ..RenderConfig::default()
+    };
+
+    let lines = runner(&cache_dir)
+        .run_from_str(
+            &payload("alerts-command", workspace.path().to_str().unwrap(), 91),
+            config,
+        )
+        .expect("render should succeed");
+    assert!(!lines.concat().contains('\x07'), "commands add no output");
+
+    // The command runs detached in the project root
+    let out = workspace.path().join("alert.out");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut contents = String::new();
+    while Instant::now() < deadline {
+        contents = std::fs::read_to_string(&out).unwrap_or_default();
+        if contents.ends_with('%') {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(contents, "ctx|context|91|Context at 91%");
+}
@@ -89,3 +89,199 @@ fn short_version_flag_works() {
         "should show name and version"
     );
 }
+
+#[test]
+fn check_flag_reports_unknown_locale_and_message_keys() {
+    let home = tempfile::TempDir::new().unwrap();
+    let dir = home.path().join(".claude").join("pulseline");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        "[display]\nlocale = \"fr\"\nmessages = \"messages.toml\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("messages.toml"), "[quota]\nresetz = \"x\"\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .arg("--check")
+        .env("HOME", home.path())
+        .output()
+        .expect("failed to run binary");
+
+    assert!(!output.status.success(), "should exit 1");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown locale \"fr\""), "{stderr}");
+    assert!(
+        stderr.contains("messages.toml: unknown message keys: quota.resetz"),
+        "{stderr}"
+    );
+}
+
+fn check_with_quota_config(quota_toml: &str, env: &[(&str, &str)]) -> (String, String) {
+    let home = tempfile::TempDir::new().unwrap();
+    let dir = home.path().join(".claude").join("pulseline");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        format!("[segments.quota]\nenabled = true\n{quota_toml}"),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .arg("--check")
+        .env("HOME", home.path())
+        .env_remove("CLAUDE_CODE_OAUTH_TOKEN")
+        .env_remove("CLAUDE_CONFIG_DIR")
+        .envs(env.iter().copied())
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success(), "credentials never fail --check");
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn check_flag_reports_credential_source() {
+    let (stdout, _) = check_with_quota_config(
+        "credentials_file = \"missing.json\"\ncredential_command = \"echo '{\\\"accessToken\\\": \\\"tok\\\"}'\"\n",
+        &[],
+    );
+    assert!(
+        stdout.contains("OK   quota credentials: credential_command `echo"),
+        "{stdout}"
+    );
+
+    let (stdout, _) = check_with_quota_config("", &[("CLAUDE_CODE_OAUTH_TOKEN", "sk-ant-oat01-x")]);
+    assert!(
+        stdout.contains("OK   quota credentials: env CLAUDE_CODE_OAUTH_TOKEN"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn check_flag_warns_when_no_credential_source_works() {
+    let (stdout, stderr) = check_with_quota_config("credential_command = \"exit 3\"\n", &[]);
+    assert!(!stdout.contains("quota credentials"), "{stdout}");
+    assert!(stderr.contains("WARN quota credentials: "), "{stderr}");
+    assert!(
+        stderr.contains("credential_command `exit 3`: command failed"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains(".credentials.json: read credentials"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn check_flag_uses_claude_config_dir_for_credentials() {
+    let account = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        account.path().join(".credentials.json"),
+        r#"{"claudeAiOauth": {"accessToken": "work-token"}}"#,
+    )
+    .unwrap();
+    let account_dir = account.path().to_str().unwrap();
+
+    let (stdout, _) = check_with_quota_config("", &[("CLAUDE_CONFIG_DIR", account_dir)]);
+    assert!(
+        stdout.contains(&format!("OK   claude config dir: {account_dir}")),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains(&format!(
+            "OK   quota credentials: {}",
+            account.path().join(".credentials.json").display()
+        )),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn check_flag_reads_claude_config_dir_from_config() {
+    let home = tempfile::TempDir::new().unwrap();
+    let dir = home.path().join(".claude").join("pulseline");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        "claude_config_dir = \"~/.claude-personal\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .arg("--check")
+        .env("HOME", home.path())
+        .env_remove("CLAUDE_CONFIG_DIR")
+        .output()
+        .expect("failed to run binary");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(output.status.success());
+    assert!(
+        stderr.contains(&format!(
+            "WARN claude config dir not found: {}",
+            home.path().join(".claude-personal").display()
+        )),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn check_flag_reports_invalid_alert_rules() {
+    let home = tempfile::TempDir::new().unwrap();
+    let dir = home.path().join(".claude").join("pulseline");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        "[[alerts]]\nname = \"ctx\"\nmetric = \"context\"\n\n[[alerts]]\nname = \"run\"\nmetric = \"cost\"\nthreshold = 5\naction = \"command\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .arg("--check")
+        .env("HOME", home.path())
+        .output()
+        .expect("failed to run binary");
+
+    assert!(!output.status.success(), "should exit 1");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("alert \"ctx\": metric \"context\" needs a threshold"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("alert \"run\": action \"command\" needs a command"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn check_flag_reports_project_commands() {
+    let home = tempfile::TempDir::new().unwrap();
+    let project = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(project.path().join(".claude")).unwrap();
+    std::fs::write(
+        project.path().join(".claude").join("pulseline.toml"),
+        "[[segments.custom]]\nname = \"ci\"\ncommand = \"cat .ci/status\"\n\n[[alerts]]\nname = \"ping\"\nmetric = \"cost\"\nthreshold = 1\naction = \"command\"\ncommand = \"true\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .arg("--check")
+        .env("HOME", home.path())
+        .current_dir(project.path())
+        .output()
+        .expect("failed to run binary");
+
+    assert!(!output.status.success(), "should exit 1");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[[segments.custom]] is ignored in project configs"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("alert \"ping\": action \"command\" is ignored in project configs"),
+        "{stderr}"
+    );
+}
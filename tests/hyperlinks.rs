@@ -0,0 +1,104 @@
+use cc_pulseline::{
+    config::RenderConfig,
+    render::{color::visible_width, layout::render_frame},
+    types::{RenderFrame, ToolSummary},
+};
+
+fn link_config(hyperlinks: bool) -> RenderConfig {
+    RenderConfig {
+        color_enabled: false,
+        hyperlinks,
+        ..RenderConfig::default()
+    }
+}
+
+fn frame_with_links() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line1.project_path = "~/app".to_string();
+    frame.line1.project_root = Some("/home/me/app".to_string());
+    frame.line1.git_branch = "feat/x".to_string();
+    frame.line1.git_remote_url = Some("https://github.com/o/app".to_string());
+    frame.tools = vec![ToolSummary {
+        id: "toolu_1".to_string(),
+        name: "Read".to_string(),
+        target: Some(".../main.rs".to_string()),
+        file_path: Some("/home/me/app/src/main.rs".to_string()),
+        file_line: Some(40),
+    }];
+    frame
+}
+
+fn osc8(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+#[test]
+fn project_and_branch_link_to_root_and_remote() {
+    let lines = render_frame(&frame_with_links(), &link_config(true));
+    assert!(
+        lines[0].contains(&osc8("~/app", "file:///home/me/app")),
+        "got {:?}",
+        lines[0]
+    );
+    assert!(
+        lines[0].contains(&osc8("feat/x", "https://github.com/o/app/tree/feat/x")),
+        "got {:?}",
+        lines[0]
+    );
+}
+
+#[test]
+fn tool_targets_link_to_file_or_editor_template() {
+    let lines = render_frame(&frame_with_links(), &link_config(true));
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.contains(&osc8(".../main.rs", "file:///home/me/app/src/main.rs"))),
+        "got {lines:?}"
+    );
+
+    let config = RenderConfig {
+        editor_url: Some("vscode://file{path}:{line}".to_string()),
+        ..link_config(true)
+    };
+    let lines = render_frame(&frame_with_links(), &config);
+    assert!(
+        lines.iter().any(|l| l.contains(&osc8(
+            ".../main.rs",
+            "vscode://file/home/me/app/src/main.rs:40"
+        ))),
+        "got {lines:?}"
+    );
+}
+
+#[test]
+fn links_are_off_by_default_and_keep_width() {
+    let plain = render_frame(&frame_with_links(), &link_config(false));
+    assert!(
+        plain.iter().all(|l| !l.contains("\x1b]8;")),
+        "got {plain:?}"
+    );
+
+    let linked = render_frame(&frame_with_links(), &link_config(true));
+    let widths = |lines: &[String]| lines.iter().map(|l| visible_width(l)).collect::<Vec<_>>();
+    assert_eq!(widths(&linked), widths(&plain));
+}
+
+#[test]
+fn truncation_closes_an_open_link() {
+    let mut frame = frame_with_links();
+    frame.line1.project_path = "~/a-very-long-project-name".to_string();
+    let config = RenderConfig {
+        terminal_width: Some(15),
+        show_model: false,
+        show_style: false,
+        show_version: false,
+        show_git: false,
+        ..link_config(true)
+    };
+    let lines = render_frame(&frame, &config);
+    assert_eq!(
+        lines[0],
+        "P:\x1b]8;;file:///home/me/app\x1b\\~/a-very-l\x1b]8;;\x1b\\..."
+    );
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use cc_pulseline::{
+    config::RenderConfig,
+    messages::Messages,
+    render::layout::render_frame,
+    types::{QuotaMetrics, RenderFrame, TodoSummary},
+};
+
+fn locale_config(messages: Messages) -> RenderConfig {
+    RenderConfig {
+        color_enabled: false,
+        show_quota: true,
+        messages,
+        ..RenderConfig::default()
+    }
+}
+
+fn frame() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line2.rules_count = 2;
+    frame.quota = QuotaMetrics {
+        plan_type: Some("max".to_string()),
+        available: true,
+        five_hour_pct: Some(100.0),
+        five_hour_reset_minutes: Some(90),
+        ..Default::default()
+    };
+    frame.todo = Some(TodoSummary {
+        pending: 2,
+        completed: 1,
+        total: 3,
+        ..Default::default()
+    });
+    frame
+}
+
+#[test]
+fn english_is_the_default_catalog() {
+    let joined = render_frame(&frame(), &locale_config(Messages::default())).join("\n");
+    assert!(joined.contains("2 rules"), "{joined}");
+    assert!(joined.contains("Limit reached (resets 1h 30m)"), "{joined}");
+    assert!(joined.contains("TODO:1/3 done, 2 pending"), "{joined}");
+}
+
+#[test]
+fn japanese_and_chinese_catalogs_translate_labels() {
+    let joined = render_frame(&frame(), &locale_config(Messages::for_locale("ja"))).join("\n");
+    assert!(joined.contains("2 ルール"), "{joined}");
+    assert!(
+        joined.contains("上限に到達 (1h 30m 後にリセット)"),
+        "{joined}"
+    );
+    assert!(joined.contains("TODO:1/3 完了、残り 2"), "{joined}");
+
+    let joined = render_frame(&frame(), &locale_config(Messages::for_locale("zh-TW"))).join("\n");
+    assert!(joined.contains("2 規則"), "{joined}");
+    assert!(joined.contains("已達上限"), "{joined}");
+}
+
+#[test]
+fn user_overrides_replace_single_strings() {
+    let overrides = BTreeMap::from([("config.rules".to_string(), "policies".to_string())]);
+    let messages = Messages::for_locale("ja").with_overrides(overrides);
+    let joined = render_frame(&frame(), &locale_config(messages)).join("\n");
+    assert!(joined.contains("2 policies"), "{joined}");
+    assert!(joined.contains("上限に到達"), "{joined}");
+}
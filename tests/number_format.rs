@@ -0,0 +1,75 @@
+use cc_pulseline::{
+    config::{build_render_config_with_width, PulselineConfig, RenderConfig},
+    render::{fmt::NumberFormat, layout::render_frame},
+    types::RenderFrame,
+};
+
+fn frame() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line3.context_used_percentage = Some(50);
+    frame.line3.context_window_size = Some(200_000);
+    frame.line3.input_tokens = Some(12_345);
+    frame.line3.output_tokens = Some(1_500);
+    frame.line3.total_cost_usd = Some(2_000.0);
+    frame.line3.total_duration_ms = Some(7_200_000);
+    frame.line3.lines_added = Some(1_200);
+    frame.line3.lines_removed = Some(10);
+    frame
+}
+
+fn render_with(number_format: NumberFormat) -> String {
+    let config = RenderConfig {
+        color_enabled: false,
+        show_lines_changed: true,
+        number_format,
+        ..RenderConfig::default()
+    };
+    render_frame(&frame(), &config).remove(2)
+}
+
+#[test]
+fn default_format_keeps_dollars_and_compact_counts() {
+    let line3 = render_with(NumberFormat::default());
+    assert!(line3.contains("(100.0k/200.0k)"), "{line3}");
+    assert!(line3.contains("I:12.3k"), "{line3}");
+    assert!(line3.contains("$2000.00 ($1000.00/h)"), "{line3}");
+    assert!(line3.contains("($165.29/100L)"), "{line3}");
+}
+
+#[test]
+fn euro_format_converts_cost_and_groups_full_numbers() {
+    let config: PulselineConfig = toml::from_str(
+        r#"
+[display]
+numbers = "full"
+decimal_separator = ","
+thousands_separator = "."
+
+[display.currency]
+symbol = "€"
+rate = 0.5
+position = "after"
+"#,
+    )
+    .unwrap();
+    let line3 = render_with(build_render_config_with_width(&config, None).number_format);
+    assert!(line3.contains("(100.000/200.000)"), "{line3}");
+    assert!(line3.contains("I:12.345"), "{line3}");
+    assert!(line3.contains("O:1.500"), "{line3}");
+    assert!(line3.contains("1.000,00 € (500,00 €/h)"), "{line3}");
+    assert!(line3.contains("+1.200"), "{line3}");
+}
+
+#[test]
+fn invalid_rate_falls_back_to_usd_amounts() {
+    let config: PulselineConfig = toml::from_str(
+        r#"
+[display.currency]
+symbol = "£"
+rate = 0.0
+"#,
+    )
+    .unwrap();
+    let line3 = render_with(build_render_config_with_width(&config, None).number_format);
+    assert!(line3.contains("£2000.00"), "{line3}");
+}
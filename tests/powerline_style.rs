@@ -0,0 +1,108 @@
+use cc_pulseline::{
+    config::{GlyphMode, LineStyle, RenderConfig},
+    render::{
+        color::{strip_ansi, visible_width},
+        layout::render_frame,
+    },
+    types::RenderFrame,
+};
+
+const ARROW: char = '\u{e0b0}';
+const THIN_ARROW: char = '\u{e0b1}';
+
+fn powerline_config(line_style: LineStyle) -> RenderConfig {
+    RenderConfig {
+        color_enabled: true,
+        glyph_mode: GlyphMode::Icon,
+        line_style,
+        show_style: false,
+        show_version: false,
+        show_project: false,
+        ..RenderConfig::default()
+    }
+}
+
+fn sample_frame() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line1.model = "Opus".to_string();
+    frame.line1.git_branch = "main".to_string();
+    frame
+}
+
+#[test]
+fn powerline_pads_segments_and_joins_with_dividers() {
+    let lines = render_frame(&sample_frame(), &powerline_config(LineStyle::Powerline));
+    let plain = strip_ansi(&lines[0]);
+
+    assert!(!plain.contains(" | "), "no plain separators: {plain}");
+    assert_eq!(plain.matches(ARROW).count(), 2, "one divider per segment");
+    assert!(
+        plain.starts_with(" \u{e26d} Opus "),
+        "padded model: {plain}"
+    );
+    assert!(
+        plain.ends_with(&format!(" main {ARROW}")),
+        "closing cap: {plain}"
+    );
+}
+
+#[test]
+fn powerline_transitions_use_neighbouring_backgrounds() {
+    let lines = render_frame(&sample_frame(), &powerline_config(LineStyle::Powerline));
+    let line1 = &lines[0];
+
+    // Model on the session background (237), divider drawn fg 237 over the
+    // git segment's workspace background (235)
+    assert!(line1.starts_with("\x1b[48;5;237m "));
+    assert!(line1.contains(&format!("\x1b[48;5;235m\x1b[38;5;237m{ARROW}\x1b[0m")));
+    // Last divider sits on the terminal default background
+    assert!(line1.ends_with(&format!("\x1b[0m\x1b[38;5;235m{ARROW}\x1b[0m")));
+}
+
+#[test]
+fn powerline_segments_in_one_group_share_a_background() {
+    let lines = render_frame(&sample_frame(), &powerline_config(LineStyle::Powerline));
+    let plain = strip_ansi(&lines[1]);
+
+    // The six config counts are split by thin dividers, then the duration
+    // segment (session group) sits behind a full one
+    assert_eq!(plain.matches(THIN_ARROW).count(), 5, "{plain}");
+    assert_eq!(plain.matches(ARROW).count(), 2, "{plain}");
+    assert!(lines[1].starts_with("\x1b[48;5;235m "));
+    assert!(lines[1].contains(&format!("\x1b[48;5;237m\x1b[38;5;235m{ARROW}\x1b[0m")));
+}
+
+#[test]
+fn powerline_round_uses_round_dividers() {
+    let lines = render_frame(
+        &sample_frame(),
+        &powerline_config(LineStyle::PowerlineRound),
+    );
+    assert!(strip_ansi(&lines[0]).contains('\u{e0b4}'));
+}
+
+#[test]
+fn powerline_falls_back_to_plain_without_color() {
+    let config = RenderConfig {
+        color_enabled: false,
+        ..powerline_config(LineStyle::Powerline)
+    };
+    let lines = render_frame(&sample_frame(), &config);
+    assert!(lines[0].contains(" | "));
+    assert!(!lines[0].contains(ARROW));
+}
+
+#[test]
+fn powerline_lines_respect_terminal_width() {
+    let config = RenderConfig {
+        terminal_width: Some(30),
+        ..powerline_config(LineStyle::Powerline)
+    };
+    let mut frame = sample_frame();
+    frame.line3.context_used_percentage = Some(40);
+    frame.line3.context_window_size = Some(200_000);
+
+    let lines = render_frame(&frame, &config);
+    assert!(lines.iter().all(|line| visible_width(line) <= 30));
+    assert!(strip_ansi(&lines[2]).contains("40%"), "context kept whole");
+}
@@ -0,0 +1,88 @@
+use cc_pulseline::{
+    config::{BarStyle, RenderConfig},
+    render::{
+        color::{strip_ansi, CTX_CRITICAL, CTX_WARN},
+        layout::render_frame,
+    },
+    types::{QuotaMetrics, RenderFrame},
+};
+
+fn bar_config(style: BarStyle, color_enabled: bool) -> RenderConfig {
+    RenderConfig {
+        color_enabled,
+        bar_style: Some(style),
+        bar_width: 10,
+        show_quota: true,
+        show_quota_seven_day: true,
+        ..RenderConfig::default()
+    }
+}
+
+fn frame_with(context_pct: u64, five_hour_pct: f64) -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line3.context_used_percentage = Some(context_pct);
+    frame.line3.context_window_size = Some(200_000);
+    frame.quota = QuotaMetrics {
+        plan_type: Some("max".to_string()),
+        available: true,
+        five_hour_pct: Some(five_hour_pct),
+        seven_day_pct: Some(10.0),
+        ..Default::default()
+    };
+    frame
+}
+
+#[test]
+fn context_bar_sits_between_label_and_percentage() {
+    let lines = render_frame(&frame_with(40, 20.0), &bar_config(BarStyle::Blocks, false));
+    assert!(
+        lines[2].starts_with("CTX:▰▰▰▰▱▱▱▱┃▱ 40% (80.0k/200.0k)"),
+        "got {}",
+        lines[2]
+    );
+}
+
+#[test]
+fn ascii_context_bar_is_bracketed_with_compact_marker() {
+    let lines = render_frame(&frame_with(90, 20.0), &bar_config(BarStyle::Ascii, false));
+    assert!(
+        lines[2].starts_with("CTX:[########|-] 90%"),
+        "got {}",
+        lines[2]
+    );
+}
+
+#[test]
+fn context_bar_uses_threshold_colors() {
+    let lines = render_frame(&frame_with(72, 20.0), &bar_config(BarStyle::Blocks, true));
+    assert!(
+        lines[2].contains(&format!("{CTX_CRITICAL}▰▰▰▰▰▰▰")),
+        "filled cells should be critical red: {:?}",
+        lines[2]
+    );
+}
+
+#[test]
+fn quota_periods_get_bars_without_marker() {
+    let lines = render_frame(&frame_with(10, 55.0), &bar_config(BarStyle::Eighths, true));
+    let quota = lines
+        .iter()
+        .find(|line| strip_ansi(line).contains("5h:"))
+        .expect("quota line");
+
+    let plain = strip_ansi(quota);
+    assert!(plain.contains("5h: █████▌░░░░ 55%"), "got {plain}");
+    assert!(plain.contains("7d: █░░░░░░░░░ 10%"), "got {plain}");
+    assert!(!plain.contains('┃'), "no compact marker on quota bars");
+    assert!(quota.contains(&format!("{CTX_WARN}█████▌")));
+}
+
+#[test]
+fn bars_are_off_by_default() {
+    let config = RenderConfig {
+        show_quota: true,
+        ..RenderConfig::default()
+    };
+    let lines = render_frame(&frame_with(40, 20.0), &config);
+    assert!(lines[2].starts_with("CTX:40%"), "got {}", lines[2]);
+}
@@ -1,7 +1,11 @@
 use cc_pulseline::{
     config::RenderConfig,
-    render::color::{CTX_CRITICAL, CTX_GOOD, CTX_WARN},
-    types::{QuotaMetrics, RenderFrame, StdinPayload},
+    providers::{
+        quota::{ExtraUsage, ModelQuotaBucket, QuotaSnapshot},
+        timezone::{LocalTime, TimeZone},
+    },
+    render::color::{visible_width, CTX_CRITICAL, CTX_GOOD, CTX_WARN},
+    types::{QuotaMetrics, QuotaProjection, RenderFrame, StdinPayload},
 };
 use serde_json::json;
 
@@ -85,6 +89,25 @@ fn quota_shows_pct_at_75pct() {
     );
 }
 
+#[test]
+fn stale_quota_keeps_values_with_a_marker() {
+    let quota = QuotaMetrics {
+        plan_type: Some("max".to_string()),
+        five_hour_pct: Some(40.0),
+        available: true,
+        stale: true,
+        ..Default::default()
+    };
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(quota, config);
+    assert!(lines[3].contains("40%"), "{}", lines[3]);
+    assert!(lines[3].ends_with("(stale)"), "{}", lines[3]);
+}
+
 #[test]
 fn quota_shows_limit_reached_at_100pct() {
     let quota = QuotaMetrics {
@@ -162,11 +185,29 @@ fn quota_dropped_in_width_degradation() {
     let config = RenderConfig {
         show_quota: true,
         show_quota_five_hour: true,
-        terminal_width: Some(20), // Very narrow — forces degradation
         ..Default::default()
     };
-    let lines = render_with_quota(quota, config);
-    // Width degradation drops activity lines (including quota) first
+    let wide = render_with_quota(quota.clone(), config.clone());
+    let quota_width = visible_width(&wide[3]);
+
+    // The quota line survives while it fits...
+    let fits = render_with_quota(
+        quota.clone(),
+        RenderConfig {
+            terminal_width: Some(quota_width),
+            ..config.clone()
+        },
+    );
+    assert_eq!(fits.get(3), Some(&wide[3]), "quota line fits: got {fits:?}");
+
+    // ...and is dropped, not truncated, once it does not
+    let lines = render_with_quota(
+        quota,
+        RenderConfig {
+            terminal_width: Some(quota_width - 1),
+            ..config
+        },
+    );
     assert!(
         lines.len() <= 3,
         "quota line should be dropped in narrow width, got {} lines",
@@ -228,6 +269,7 @@ fn quota_shows_seven_day_when_enabled() {
         seven_day_pct: Some(55.0),
         seven_day_reset_minutes: Some(2880), // 2 days
         available: true,
+        ..Default::default()
     };
     let config = RenderConfig {
         show_quota: true,
@@ -412,3 +454,251 @@ fn quota_critical_at_85pct() {
         "85% should use critical color (threshold at 85%), got: {quota_line}"
     );
 }
+
+#[test]
+fn quota_absolute_reset_shows_local_clock_time() {
+    let quota = QuotaMetrics {
+        plan_type: Some("max".to_string()),
+        five_hour_pct: Some(30.0),
+        five_hour_reset_minutes: Some(192),
+        five_hour_reset_local: Some(LocalTime {
+            weekday: 1,
+            hour: 14,
+            minute: 30,
+        }),
+        seven_day_pct: Some(55.0),
+        seven_day_reset_minutes: Some(2880),
+        seven_day_reset_local: Some(LocalTime {
+            weekday: 2,
+            hour: 9,
+            minute: 5,
+        }),
+        available: true,
+        ..Default::default()
+    };
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: true,
+        show_quota_seven_day: true,
+        quota_absolute_reset: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(quota.clone(), config.clone());
+    assert!(lines[3].contains("(resets 14:30)"), "got: {}", lines[3]);
+    assert!(lines[3].contains("(resets Tue 09:05)"), "got: {}", lines[3]);
+
+    let relative = RenderConfig {
+        quota_absolute_reset: false,
+        ..config
+    };
+    let lines = render_with_quota(quota, relative);
+    assert!(lines[3].contains("(resets 3h 12m)"), "got: {}", lines[3]);
+}
+
+#[test]
+fn quota_absolute_reset_falls_back_to_relative_without_local_time() {
+    let quota = QuotaMetrics {
+        plan_type: Some("pro".to_string()),
+        five_hour_pct: Some(10.0),
+        five_hour_reset_minutes: Some(45),
+        available: true,
+        ..Default::default()
+    };
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: true,
+        quota_absolute_reset: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(quota, config);
+    assert!(lines[3].contains("(resets 45m)"), "got: {}", lines[3]);
+}
+
+#[test]
+fn local_resets_convert_snapshot_timestamps() {
+    let snapshot = QuotaSnapshot {
+        five_hour_reset_at: Some(1_719_835_200_000), // 2024-07-01 12:00 UTC, a Monday
+        seven_day_reset_at: Some(1_720_094_400_000), // 2024-07-04 12:00 UTC
+        available: true,
+        ..Default::default()
+    };
+    let quota = QuotaMetrics::from_snapshot(&snapshot, 1_719_830_000_000)
+        .with_local_resets(&snapshot, &TimeZone::utc());
+    assert_eq!(
+        quota.five_hour_reset_local,
+        Some(LocalTime {
+            weekday: 1,
+            hour: 12,
+            minute: 0,
+        })
+    );
+    assert_eq!(quota.seven_day_reset_local.map(|t| t.weekday), Some(4));
+}
+
+#[test]
+fn quota_projection_extrapolates_to_reset() {
+    // 40% now, 20%/h, 2h left: 80% at reset
+    assert_eq!(
+        QuotaProjection::project(Some(40.0), Some(20.0), Some(120)),
+        Some(QuotaProjection::AtReset { pct: 80.0 })
+    );
+    // 60% now, 60%/h, 2h left: limit after 40 minutes
+    assert_eq!(
+        QuotaProjection::project(Some(60.0), Some(60.0), Some(120)),
+        Some(QuotaProjection::LimitIn { minutes: 40 })
+    );
+    // Falling usage never projects below the current value
+    assert_eq!(
+        QuotaProjection::project(Some(30.0), Some(-5.0), Some(60)),
+        Some(QuotaProjection::AtReset { pct: 30.0 })
+    );
+    assert_eq!(QuotaProjection::project(Some(30.0), None, Some(60)), None);
+    assert_eq!(
+        QuotaProjection::project(Some(100.0), Some(5.0), Some(60)),
+        None
+    );
+}
+
+#[test]
+fn quota_projection_renders_next_to_reset() {
+    let snapshot = QuotaSnapshot {
+        plan_type: Some("max".to_string()),
+        five_hour_pct: Some(60.0),
+        five_hour_reset_at: Some(1_000 + 120 * 60_000),
+        five_hour_pct_per_hour: Some(60.0),
+        seven_day_pct: Some(40.0),
+        seven_day_reset_at: Some(1_000 + 2 * 60 * 60_000),
+        seven_day_pct_per_hour: Some(11.0),
+        available: true,
+        ..Default::default()
+    };
+    let quota = QuotaMetrics::from_snapshot(&snapshot, 1_000);
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: true,
+        show_quota_seven_day: true,
+        show_quota_projection: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(quota.clone(), config.clone());
+    assert!(
+        lines[3].contains("5h: 60% (resets 2h 0m, limit in ~40m)"),
+        "got: {}",
+        lines[3]
+    );
+    assert!(
+        lines[3].contains("7d: 40% (resets 2h 0m, on pace: 62% at reset)"),
+        "got: {}",
+        lines[3]
+    );
+
+    let colored = render_with_quota(
+        quota.clone(),
+        RenderConfig {
+            color_enabled: true,
+            ..config.clone()
+        },
+    );
+    assert!(
+        colored[3].contains(&format!("{CTX_CRITICAL}limit in ~40m")),
+        "limit projection is critical: {}",
+        colored[3]
+    );
+
+    let hidden = render_with_quota(
+        quota,
+        RenderConfig {
+            show_quota_projection: false,
+            ..config
+        },
+    );
+    assert!(!hidden[3].contains("on pace"), "got: {}", hidden[3]);
+}
+
+fn bucket_snapshot() -> QuotaSnapshot {
+    QuotaSnapshot {
+        plan_type: Some("max".to_string()),
+        five_hour_pct: Some(20.0),
+        seven_day_pct: Some(35.0),
+        model_buckets: vec![
+            ModelQuotaBucket {
+                model: "opus".to_string(),
+                pct: 60.0,
+                reset_at: None,
+            },
+            ModelQuotaBucket {
+                model: "sonnet".to_string(),
+                pct: 12.0,
+                reset_at: None,
+            },
+        ],
+        extra_usage: Some(ExtraUsage {
+            enabled: true,
+            used_usd: Some(12.34),
+            limit_usd: Some(50.0),
+            pct: Some(24.68),
+        }),
+        available: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn model_buckets_and_extra_usage_hidden_by_default() {
+    let quota = QuotaMetrics::from_snapshot(&bucket_snapshot(), 0);
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(quota, config);
+    assert!(!lines[3].contains("Opus:"), "got: {}", lines[3]);
+    assert!(!lines[3].contains("Extra"), "got: {}", lines[3]);
+}
+
+#[test]
+fn model_buckets_render_with_active_model_highlighted() {
+    let quota = QuotaMetrics::from_snapshot(&bucket_snapshot(), 0);
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: true,
+        show_quota_model_buckets: true,
+        show_quota_extra_usage: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(quota.clone(), config.clone());
+    assert!(
+        lines[3].ends_with("5h: 20% Opus: 60% Sonnet: 12% Extra: $12.34/$50.00"),
+        "got: {}",
+        lines[3]
+    );
+
+    // The session model is "Opus": only that bucket's label is emphasized
+    let colored = render_with_quota(
+        quota,
+        RenderConfig {
+            color_enabled: true,
+            ..config
+        },
+    );
+    let line = &colored[3];
+    assert!(line.contains("\x1b[1m\x1b[38;5;251mOpus:"), "got: {line:?}");
+    assert!(
+        !line.contains("\x1b[1m\x1b[38;5;251mSonnet:"),
+        "got: {line:?}"
+    );
+}
+
+#[test]
+fn extra_usage_shows_off_when_disabled() {
+    let mut snapshot = bucket_snapshot();
+    snapshot.extra_usage = Some(ExtraUsage::default());
+    let config = RenderConfig {
+        show_quota: true,
+        show_quota_five_hour: false,
+        show_quota_extra_usage: true,
+        ..Default::default()
+    };
+    let lines = render_with_quota(QuotaMetrics::from_snapshot(&snapshot, 0), config);
+    assert!(lines[3].ends_with("Extra: off"), "got: {}", lines[3]);
+}
@@ -0,0 +1,193 @@
+//! Quota fetcher against a local mock HTTP server (no curl, no network).
+
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+};
+
+use cc_pulseline::providers::{
+    http::{DefaultTransport, HttpRequest, HttpTransport, PlainHttpTransport},
+    quota_fetch::fetch_usage,
+};
+
+const NOW_MS: u64 = 1_762_000_000_000;
+
+/// Serve one canned `response` on a random port. Returns the usage URL and a
+/// receiver for the raw request the server saw.
+fn mock_server(response: &'static str) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api/oauth/usage", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        // The test may have dropped the receiver
+        let _ = sender.send(String::from_utf8_lossy(&request).into_owned());
+    });
+    (url, receiver)
+}
+
+#[test]
+fn success_response_becomes_snapshot() {
+    let (url, request) = mock_server(concat!(
+        "HTTP/1.1 200 OK\r\n",
+        "Content-Type: application/json\r\n",
+        "Content-Length: 159\r\n",
+        "\r\n",
+        r#"{"five_hour":{"utilization":25.0,"resets_at":"2025-11-04T04:59:59Z"},"seven_day":{"utilization":35.0,"resets_at":"2025-11-06T03:59:59Z"},"seven_day_opus":null}"#,
+    ));
+
+    let snapshot = fetch_usage(&PlainHttpTransport::default(), &url, "tok-123", NOW_MS).unwrap();
+    assert!(snapshot.available);
+    assert_eq!(snapshot.five_hour_pct, Some(25.0));
+    assert_eq!(snapshot.seven_day_pct, Some(35.0));
+    assert!(snapshot.five_hour_reset_at.is_some());
+    assert!(snapshot.model_buckets.is_empty());
+
+    let request = request.recv().unwrap();
+    assert!(
+        request.starts_with("GET /api/oauth/usage HTTP/1.1\r\n"),
+        "{request}"
+    );
+    assert!(
+        request.contains("Authorization: Bearer tok-123\r\n"),
+        "{request}"
+    );
+    assert!(
+        request.contains("anthropic-beta: oauth-2025-04-20\r\n"),
+        "{request}"
+    );
+}
+
+#[test]
+fn chunked_success_response_is_decoded() {
+    let (url, _) = mock_server(concat!(
+        "HTTP/1.1 200 OK\r\n",
+        "Transfer-Encoding: chunked\r\n",
+        "\r\n",
+        "e\r\n{\"five_hour\":{\r\n",
+        "1a\r\n\"utilization\":50.0},\"x\":1}\r\n",
+        "0\r\n\r\n",
+    ));
+    let snapshot = fetch_usage(&DefaultTransport, &url, "tok", NOW_MS).unwrap();
+    assert_eq!(snapshot.five_hour_pct, Some(50.0));
+}
+
+#[test]
+fn unauthorized_response_is_an_error() {
+    let (url, _) = mock_server(concat!(
+        "HTTP/1.1 401 Unauthorized\r\n",
+        "Content-Length: 26\r\n",
+        "\r\n",
+        r#"{"error":"invalid_token"}"#,
+        " ",
+    ));
+    let err = fetch_usage(&PlainHttpTransport::default(), &url, "bad", NOW_MS).unwrap_err();
+    assert_eq!(
+        err.message,
+        "usage API rejected the access token (HTTP 401)"
+    );
+    assert_eq!(err.retry_at_ms, None);
+}
+
+#[test]
+fn rate_limited_response_honours_retry_after_seconds() {
+    let (url, _) = mock_server(concat!(
+        "HTTP/1.1 429 Too Many Requests\r\n",
+        "Retry-After: 120\r\n",
+        "Content-Length: 0\r\n",
+        "\r\n",
+    ));
+    let err = fetch_usage(&PlainHttpTransport::default(), &url, "tok", NOW_MS).unwrap_err();
+    assert_eq!(err.message, "usage API rate limited (HTTP 429)");
+    assert_eq!(err.retry_at_ms, Some(NOW_MS + 120_000));
+}
+
+#[test]
+fn rate_limited_response_honours_retry_after_date() {
+    let (url, _) = mock_server(concat!(
+        "HTTP/1.1 429 Too Many Requests\r\n",
+        "retry-after: Wed, 21 Oct 2015 07:28:00 GMT\r\n",
+        "Content-Length: 0\r\n",
+        "\r\n",
+    ));
+    let err = fetch_usage(&PlainHttpTransport::default(), &url, "tok", NOW_MS).unwrap_err();
+    assert_eq!(err.retry_at_ms, Some(1_445_412_480_000));
+}
+
+#[test]
+fn malformed_body_is_a_parse_error() {
+    let (url, _) = mock_server(concat!(
+        "HTTP/1.1 200 OK\r\n",
+        "Content-Length: 15\r\n",
+        "\r\n",
+        "<html>oops</ht>",
+    ));
+    let err = fetch_usage(&PlainHttpTransport::default(), &url, "tok", NOW_MS).unwrap_err();
+    assert!(
+        err.message.starts_with("parse usage response:"),
+        "{}",
+        err.message
+    );
+}
+
+#[test]
+fn unexpected_status_and_connection_failures_are_errors() {
+    let (url, _) = mock_server("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n");
+    let err = fetch_usage(&PlainHttpTransport::default(), &url, "tok", NOW_MS).unwrap_err();
+    assert_eq!(err.message, "usage API returned HTTP 503");
+
+    let (url, _) = mock_server("not http at all");
+    let err = fetch_usage(&PlainHttpTransport::default(), &url, "tok", NOW_MS).unwrap_err();
+    assert_eq!(err.message, "malformed HTTP response");
+
+    // Nothing listens on a port we just released
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let err = PlainHttpTransport::default()
+        .send(&HttpRequest::get(&format!("http://127.0.0.1:{port}/")))
+        .unwrap_err();
+    assert!(err.starts_with("connect 127.0.0.1:"), "{err}");
+}
+
+/// A test double transport: records the request and returns a fixed response.
+struct Recorded(std::cell::RefCell<Vec<HttpRequest>>);
+
+impl HttpTransport for Recorded {
+    fn send(
+        &self,
+        request: &HttpRequest,
+    ) -> Result<cc_pulseline::providers::http::HttpResponse, String> {
+        self.0.borrow_mut().push(request.clone());
+        Ok(cc_pulseline::providers::http::HttpResponse {
+            status: 200,
+            headers: Vec::new(),
+            body: r#"{"seven_day":{"utilization":10.0}}"#.to_string(),
+        })
+    }
+}
+
+#[test]
+fn custom_transport_receives_the_endpoint_override() {
+    let transport = Recorded(Default::default());
+    let snapshot = fetch_usage(&transport, "https://proxy.corp/usage", "tok", NOW_MS).unwrap();
+    assert_eq!(snapshot.seven_day_pct, Some(10.0));
+    let requests = transport.0.borrow();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url, "https://proxy.corp/usage");
+    assert_eq!(requests[0].method, "GET");
+}
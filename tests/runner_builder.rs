@@ -0,0 +1,291 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use cc_pulseline::{
+    config::RenderConfig,
+    providers::{
+        custom::{custom_result_path, CustomResultFile},
+        quota::{quota_cache_path, QuotaCacheFile},
+        ClaudeConfigDir, GitSnapshot, QuotaSnapshot, StubEnvCollector, StubGitCollector,
+        StubQuotaCollector, StubTranscriptCollector,
+    },
+    state::cache::{now_epoch_ms, CacheStore, FileCacheStore, SessionCache},
+    PulseLineRunner,
+};
+use serde_json::json;
+use tempfile::TempDir;
+
+fn payload(session_id: &str) -> String {
+    json!({
+        "session_id": session_id,
+        "cwd": "/embedded/project",
+        "model": {"display_name": "Opus"},
+        "cost": {"total_cost_usd": 1.25, "total_duration_ms": 60000}
+    })
+    .to_string()
+}
+
+/// Records saves in memory so the test can observe persistence.
+#[derive(Clone, Default)]
+struct MemoryStore {
+    saved: Arc<Mutex<HashMap<String, SessionCache>>>,
+}
+
+impl CacheStore for MemoryStore {
+    fn load(&self, session_key: &str) -> Option<SessionCache> {
+        self.saved.lock().unwrap().get(session_key).cloned()
+    }
+
+    fn save(&self, session_key: &str, cache: &SessionCache) {
+        self.saved
+            .lock()
+            .unwrap()
+            .insert(session_key.to_string(), cache.clone());
+    }
+}
+
+#[test]
+fn injected_collectors_drive_the_frame() {
+    let mut runner = PulseLineRunner::default()
+        .with_env_collector(StubEnvCollector)
+        .with_git_collector(StubGitCollector {
+            snapshot: GitSnapshot {
+                branch: "stub-branch".to_string(),
+                ..Default::default()
+            },
+        })
+        .with_transcript_collector(StubTranscriptCollector)
+        .with_quota_collector(StubQuotaCollector {
+            snapshot: QuotaSnapshot {
+                plan_type: Some("max".to_string()),
+                five_hour_pct: Some(42.0),
+                available: true,
+                ..Default::default()
+            },
+        })
+        .without_cache_store();
+
+    let config = RenderConfig {
+        show_quota: true,
+        ..RenderConfig::default()
+    };
+    let joined = runner
+        .run_from_str(&payload("builder-stubs"), config)
+        .expect("render should succeed")
+        .join("\n");
+
+    assert!(
+        joined.contains("stub-branch"),
+        "git collector should be injected: got {joined}"
+    );
+    assert!(
+        joined.contains("42%"),
+        "quota collector should be injected: got {joined}"
+    );
+}
+
+#[test]
+fn custom_cache_store_receives_session_state() {
+    let store = MemoryStore::default();
+    let saved = Arc::clone(&store.saved);
+
+    let mut runner = PulseLineRunner::default()
+        .with_git_collector(StubGitCollector::default())
+        .with_cache_store(store.clone());
+    runner
+        .run_from_str(&payload("builder-memory-store"), RenderConfig::default())
+        .expect("render should succeed");
+
+    let saved = saved.lock().unwrap();
+    assert_eq!(saved.len(), 1, "one session should be saved");
+    let cache = saved.values().next().unwrap();
+    assert!(cache.line3.is_some(), "L3 metrics should be persisted");
+
+    // A fresh runner over the same store restores the cached L3 values
+    let mut fresh = PulseLineRunner::default()
+        .with_git_collector(StubGitCollector::default())
+        .with_cache_store(store);
+    drop(saved);
+    let empty_l3 = json!({"session_id": "builder-memory-store", "cwd": "/embedded/project"});
+    let joined = fresh
+        .run_from_str(&empty_l3.to_string(), RenderConfig::default())
+        .expect("render should succeed")
+        .join("\n");
+    assert!(
+        joined.contains("$1.25"),
+        "cost should come from the custom store: got {joined}"
+    );
+}
+
+#[test]
+fn file_cache_store_writes_into_chosen_directory() {
+    let dir = TempDir::new().expect("temp cache dir");
+    let store = FileCacheStore::new(dir.path());
+
+    let mut runner = PulseLineRunner::default()
+        .with_git_collector(StubGitCollector::default())
+        .with_cache_store(store.clone());
+    runner
+        .run_from_str(&payload("builder-file-store"), RenderConfig::default())
+        .expect("render should succeed");
+
+    let files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    assert_eq!(files.len(), 1, "cache file should land in the chosen dir");
+}
+
+#[test]
+fn claude_config_dir_selects_the_account_quota_cache() {
+    let account = TempDir::new().expect("temp config root");
+    let claude_dir = ClaudeConfigDir::custom(account.path().to_path_buf());
+    let cache = QuotaCacheFile {
+        fetched_at_ms: cc_pulseline::state::cache::now_epoch_ms(),
+        snapshot: QuotaSnapshot {
+            plan_type: Some("max".to_string()),
+            five_hour_pct: Some(63.0),
+            available: true,
+            ..Default::default()
+        },
+        history: Vec::new(),
+        retry_at_ms: None,
+    };
+    let path = quota_cache_path(Some(&claude_dir));
+    std::fs::write(&path, serde_json::to_string(&cache).unwrap()).unwrap();
+
+    let mut runner = PulseLineRunner::default()
+        .with_claude_config_dir(claude_dir)
+        .with_git_collector(StubGitCollector::default())
+        .without_cache_store();
+    let config = RenderConfig {
+        show_quota: true,
+        ..RenderConfig::default()
+    };
+    let joined = runner
+        .run_from_str(&payload("builder-claude-dir"), config)
+        .expect("render should succeed")
+        .join("\n");
+    let _ = std::fs::remove_file(&path);
+
+    assert!(
+        joined.contains("63%"),
+        "quota should come from the account's cache: got {joined}"
+    );
+}
+
+#[test]
+fn claude_config_dir_and_user_home_apply_in_any_order() {
+    let account = TempDir::new().expect("temp config root");
+    let home = TempDir::new().expect("temp home");
+    let project = TempDir::new().expect("temp project");
+    std::fs::write(account.path().join("CLAUDE.md"), "# account rules\n").unwrap();
+    let claude_dir = ClaudeConfigDir::custom(account.path().to_path_buf());
+    let input = json!({"session_id": "builder-order", "cwd": project.path()}).to_string();
+    let render = |runner: PulseLineRunner| {
+        let mut runner = runner
+            .with_git_collector(StubGitCollector::default())
+            .without_cache_store();
+        runner
+            .run_from_str(&input, RenderConfig::default())
+            .expect("render should succeed")
+            .join("\n")
+    };
+
+    let dir_first = render(
+        PulseLineRunner::default()
+            .with_claude_config_dir(claude_dir.clone())
+            .with_user_home(home.path().to_path_buf()),
+    );
+    let home_first = render(
+        PulseLineRunner::default()
+            .with_user_home(home.path().to_path_buf())
+            .with_claude_config_dir(claude_dir.clone()),
+    );
+    assert!(dir_first.contains("1 CLAUDE.md"), "got {dir_first}");
+    assert_eq!(dir_first, home_first);
+
+    // An injected collector is kept
+    let mut runner = PulseLineRunner::default()
+        .with_quota_collector(StubQuotaCollector {
+            snapshot: QuotaSnapshot {
+                plan_type: Some("max".to_string()),
+                five_hour_pct: Some(42.0),
+                available: true,
+                ..Default::default()
+            },
+        })
+        .with_claude_config_dir(claude_dir)
+        .with_git_collector(StubGitCollector::default())
+        .without_cache_store();
+    let config = RenderConfig {
+        show_quota: true,
+        ..RenderConfig::default()
+    };
+    let joined = runner
+        .run_from_str(&payload("builder-order-quota"), config)
+        .expect("render should succeed")
+        .join("\n");
+    assert!(joined.contains("42%"), "got {joined}");
+}
+
+#[test]
+fn runner_can_move_to_another_thread() {
+    let mut runner = PulseLineRunner::default()
+        .with_env_collector(StubEnvCollector)
+        .with_git_collector(StubGitCollector::default())
+        .with_cache_store(MemoryStore::default());
+    let lines = std::thread::spawn(move || {
+        runner
+            .run_from_str(&payload("builder-thread"), RenderConfig::default())
+            .expect("render should succeed")
+    })
+    .join()
+    .unwrap();
+    assert!(lines.join("\n").contains("Opus"));
+}
+
+#[test]
+fn custom_segment_results_live_in_the_store_directory() {
+    let store_dir = TempDir::new().unwrap();
+    let config = RenderConfig {
+        custom_segments: vec![toml::from_str(
+            "name = \"ci\"\ncommand = \"cat ci-status\"\nlabel = \"CI:\"",
+        )
+        .unwrap()],
+        ..RenderConfig::default()
+    };
+    let session_key = "builder-custom||/embedded/project";
+    let result_path = custom_result_path(store_dir.path(), session_key, "ci");
+    let result = CustomResultFile {
+        command: "cat ci-status".to_string(),
+        finished_at_ms: now_epoch_ms(),
+        value: Some("passing".to_string()),
+        error: None,
+    };
+    std::fs::write(&result_path, serde_json::to_string(&result).unwrap()).unwrap();
+
+    // A memory-only runner has nowhere to collect results and runs nothing
+    let joined = PulseLineRunner::default()
+        .with_env_collector(StubEnvCollector)
+        .with_git_collector(StubGitCollector::default())
+        .without_cache_store()
+        .run_from_str(&payload("builder-custom"), config.clone())
+        .expect("render should succeed")
+        .join("\n");
+    assert!(!joined.contains("CI:"), "got {joined}");
+    assert!(result_path.exists(), "result left for the store's runner");
+
+    let joined = PulseLineRunner::default()
+        .with_env_collector(StubEnvCollector)
+        .with_git_collector(StubGitCollector::default())
+        .with_cache_store(FileCacheStore::new(store_dir.path()))
+        .run_from_str(&payload("builder-custom"), config)
+        .expect("render should succeed")
+        .join("\n");
+    assert!(joined.contains("CI:passing"), "got {joined}");
+    assert!(!result_path.exists(), "result file should be consumed");
+}
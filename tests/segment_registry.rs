@@ -0,0 +1,92 @@
+use cc_pulseline::{
+    config::{GlyphMode, RenderConfig},
+    providers::StubGitCollector,
+    render::{
+        color::{colorize, EmphasisTier},
+        layout::{render_frame, render_frame_with},
+        segment::{Segment, SegmentRegistry, StyledSpan},
+    },
+    types::RenderFrame,
+    PulseLineRunner,
+};
+
+/// An out-of-crate segment, the way an embedder would write one.
+struct TicketSegment;
+
+impl Segment for TicketSegment {
+    fn id(&self) -> &str {
+        "ticket"
+    }
+
+    fn line(&self) -> usize {
+        1
+    }
+
+    fn render(
+        &self,
+        _frame: &RenderFrame,
+        config: &RenderConfig,
+        tier: &EmphasisTier,
+    ) -> Option<StyledSpan> {
+        let text = colorize("JIRA-42", tier.secondary, config.color_enabled);
+        Some(StyledSpan::new(text, 25))
+    }
+}
+
+fn plain_config() -> RenderConfig {
+    RenderConfig {
+        color_enabled: false,
+        glyph_mode: GlyphMode::Ascii,
+        ..RenderConfig::default()
+    }
+}
+
+fn sample_frame() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line1.model = "Opus".to_string();
+    frame.line1.output_style = "default".to_string();
+    frame.line1.claude_code_version = "2.1.0".to_string();
+    frame.line1.project_path = "~/app".to_string();
+    frame.line1.git_branch = "main".to_string();
+    frame
+}
+
+#[test]
+fn default_registry_matches_render_frame() {
+    let frame = sample_frame();
+    let config = plain_config();
+    assert_eq!(
+        render_frame(&frame, &config),
+        render_frame_with(&frame, &config, &SegmentRegistry::default())
+    );
+}
+
+#[test]
+fn extension_segment_is_placed_and_builtin_removed() {
+    let mut registry = SegmentRegistry::default();
+    registry.insert_before("git", TicketSegment);
+    assert!(registry.remove("version"));
+
+    let lines = render_frame_with(&sample_frame(), &plain_config(), &registry);
+    assert_eq!(lines[0], "M:Opus | S:default | P:~/app | JIRA-42 | G:main");
+}
+
+#[test]
+fn runner_renders_with_injected_registry() {
+    let mut registry = SegmentRegistry::default();
+    registry.register(TicketSegment);
+
+    let mut runner = PulseLineRunner::default()
+        .with_git_collector(StubGitCollector::default())
+        .without_cache_store()
+        .with_segments(registry);
+    let lines = runner
+        .run_from_str(r#"{"session_id":"segment-registry"}"#, plain_config())
+        .expect("render should succeed");
+
+    assert!(
+        lines[0].ends_with(" | JIRA-42"),
+        "registered segment should close L1: got {}",
+        lines[0]
+    );
+}
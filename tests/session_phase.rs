@@ -0,0 +1,227 @@
+use std::fs;
+
+use cc_pulseline::{config::RenderConfig, PulseLineRunner};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+fn payload_json(workspace: &TempDir, transcript: &std::path::Path, session_id: &str) -> String {
+    json!({
+        "session_id": session_id,
+        "cwd": workspace.path(),
+        "workspace": {"current_dir": workspace.path()},
+        "model": {"display_name": "Opus"},
+        "transcript_path": transcript,
+    })
+    .to_string()
+}
+
+fn write_transcript(path: &std::path::Path, events: &[Value]) {
+    let lines: Vec<String> = events.iter().map(Value::to_string).collect();
+    fs::write(path, lines.join("\n") + "\n").unwrap();
+}
+
+fn render(workspace: &TempDir, transcript: &std::path::Path, session_id: &str) -> String {
+    let config = RenderConfig {
+        transcript_poll_throttle_ms: 0,
+        show_phase: true,
+        ..RenderConfig::default()
+    };
+    PulseLineRunner::default()
+        .run_from_str(&payload_json(workspace, transcript, session_id), config)
+        .expect("render should succeed")
+        .join("\n")
+}
+
+#[test]
+fn long_unanswered_tool_use_renders_waiting_for_permission() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-waiting.jsonl");
+    write_transcript(
+        &transcript,
+        &[json!({
+            "timestamp": "2026-01-01T10:00:00.000Z",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "toolu_w", "name": "Bash", "input": {"command": "rm -rf build"}}
+            ]}
+        })],
+    );
+
+    let joined = render(&workspace, &transcript, "phase-waiting");
+    assert!(
+        joined.contains("waiting for permission"),
+        "tool_use unanswered past the grace period should wait for permission: got {joined}"
+    );
+}
+
+#[test]
+fn fresh_tool_use_renders_working() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-fresh.jsonl");
+    write_transcript(
+        &transcript,
+        &[json!({"message": {"role": "assistant", "content": [
+            {"type": "tool_use", "id": "toolu_f", "name": "Bash", "input": {"command": "cargo build"}}
+        ]}})],
+    );
+
+    let joined = render(&workspace, &transcript, "phase-fresh");
+    assert!(
+        joined.contains("working") && !joined.contains("waiting for permission"),
+        "a just-started tool is probably running: got {joined}"
+    );
+}
+
+#[test]
+fn ended_assistant_turn_renders_idle_duration() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-idle.jsonl");
+    write_transcript(
+        &transcript,
+        &[
+            json!({"timestamp": "2026-01-01T10:00:00.000Z",
+                   "message": {"role": "user", "content": "fix the build"}}),
+            json!({"timestamp": "2026-01-01T10:01:00.000Z",
+                   "message": {"role": "assistant", "stop_reason": "end_turn",
+                               "content": [{"type": "text", "text": "Done."}]}}),
+        ],
+    );
+
+    let joined = render(&workspace, &transcript, "phase-idle");
+    assert!(
+        joined.contains("idle for "),
+        "finished turn should render idle duration: got {joined}"
+    );
+}
+
+#[test]
+fn tool_result_renders_working() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-working.jsonl");
+    write_transcript(
+        &transcript,
+        &[
+            json!({"message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "toolu_r", "name": "Read", "input": {"file_path": "/a.rs"}}
+            ]}}),
+            json!({"message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "toolu_r"}
+            ]}}),
+        ],
+    );
+
+    let joined = render(&workspace, &transcript, "phase-working");
+    assert!(
+        joined.contains("working"),
+        "answered tool_use should render working: got {joined}"
+    );
+}
+
+#[test]
+fn notification_hook_overrides_transcript_phase() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-hook.jsonl");
+    write_transcript(
+        &transcript,
+        &[json!({"timestamp": "2026-01-01T10:00:00.000Z",
+                 "message": {"role": "user", "content": "deploy it"}})],
+    );
+
+    let mut runner = PulseLineRunner::default();
+    runner
+        .ingest_hook_from_str(
+            &json!({
+                "session_id": "phase-hook",
+                "transcript_path": transcript,
+                "cwd": workspace.path(),
+                "hook_event_name": "Notification",
+                "message": "Claude needs your permission to use Bash",
+            })
+            .to_string(),
+        )
+        .expect("hook JSON should parse");
+
+    let joined = render(&workspace, &transcript, "phase-hook");
+    assert!(
+        joined.contains("waiting for permission"),
+        "permission notification should win over older transcript state: got {joined}"
+    );
+}
+
+#[test]
+fn hook_event_survives_a_concurrent_render_save() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-race.jsonl");
+    write_transcript(
+        &transcript,
+        &[json!({"timestamp": "2026-01-01T10:00:00.000Z",
+                 "message": {"role": "user", "content": "deploy it"}})],
+    );
+    let config = RenderConfig {
+        transcript_poll_throttle_ms: 0,
+        show_phase: true,
+        ..RenderConfig::default()
+    };
+    let input = payload_json(&workspace, &transcript, "phase-race");
+
+    // A long-lived render process loads the cache before the hook runs...
+    let mut render_process = PulseLineRunner::default();
+    render_process
+        .run_from_str(&input, config.clone())
+        .expect("render should succeed");
+    PulseLineRunner::default()
+        .ingest_hook_from_str(
+            &json!({
+                "session_id": "phase-race",
+                "transcript_path": transcript,
+                "cwd": workspace.path(),
+                "hook_event_name": "Notification",
+                "message": "Claude needs your permission to use Bash",
+            })
+            .to_string(),
+        )
+        .expect("hook JSON should parse");
+    // ...and saves again afterwards without dropping the hook's phase
+    render_process
+        .run_from_str(&input, config)
+        .expect("render should succeed");
+
+    let joined = render(&workspace, &transcript, "phase-race");
+    assert!(
+        joined.contains("waiting for permission"),
+        "hook phase should survive the render's save: got {joined}"
+    );
+}
+
+#[test]
+fn phase_segment_is_on_by_default_right_after_the_model() {
+    let workspace = TempDir::new().expect("temp workspace");
+    let transcript = workspace.path().join("phase-default.jsonl");
+    write_transcript(
+        &transcript,
+        &[json!({"message": {"role": "user", "content": "hello"}})],
+    );
+
+    let config = RenderConfig {
+        transcript_poll_throttle_ms: 0,
+        ..RenderConfig::default()
+    };
+    let joined = PulseLineRunner::default()
+        .run_from_str(
+            &payload_json(&workspace, &transcript, "phase-default"),
+            config,
+        )
+        .expect("render should succeed")
+        .join("\n");
+    let model = joined.find("Opus").expect("model should render");
+    let phase = joined
+        .find("working")
+        .expect("phase should render by default");
+    let project_name = workspace.path().file_name().unwrap().to_string_lossy();
+    let project = joined
+        .find(project_name.as_ref())
+        .expect("project should render");
+    assert!(
+        model < phase && phase < project,
+        "phase should follow the model near the front of L1: got {joined}"
+    );
+}
@@ -0,0 +1,65 @@
+use cc_pulseline::{
+    config::RenderConfig,
+    render::layout::render_frame,
+    types::{MetricHistory, RenderFrame},
+};
+
+fn sparkline_config(show_sparklines: bool) -> RenderConfig {
+    RenderConfig {
+        color_enabled: false,
+        show_sparklines,
+        show_speed: true,
+        ..RenderConfig::default()
+    }
+}
+
+fn frame_with_history() -> RenderFrame {
+    let mut frame = RenderFrame::default();
+    frame.line3.context_used_percentage = Some(50);
+    frame.line3.context_window_size = Some(200_000);
+    frame.line3.output_tokens = Some(20_000);
+    frame.line3.output_speed_toks_per_sec = Some(80.0);
+    frame.line3.total_cost_usd = Some(2.0);
+    frame.line3.total_duration_ms = Some(3_600_000);
+    frame.history = MetricHistory {
+        output_speed: vec![20.0, 40.0, 80.0],
+        context_pct: vec![0.0, 50.0, 100.0],
+        cost_per_hour: vec![1.0, 4.0],
+        ..Default::default()
+    };
+    frame
+}
+
+#[test]
+fn sparklines_follow_context_speed_and_cost() {
+    let lines = render_frame(&frame_with_history(), &sparkline_config(true));
+    let line3 = &lines[2];
+    assert!(
+        line3.starts_with("CTX:50% (100.0k/200.0k) ▁▅█"),
+        "got {line3}"
+    );
+    assert!(line3.contains("↗80/s ▃▅█ "), "got {line3}");
+    assert!(line3.contains("$2.00 ($2.00/h) ▃█"), "got {line3}");
+}
+
+#[test]
+fn sparklines_off_by_default() {
+    let lines = render_frame(&frame_with_history(), &sparkline_config(false));
+    assert!(!lines[2].contains('▁'), "got {}", lines[2]);
+    assert!(!lines[2].contains('█'), "got {}", lines[2]);
+}
+
+#[test]
+fn single_sample_renders_no_sparkline() {
+    let mut frame = frame_with_history();
+    frame.history = MetricHistory {
+        context_pct: vec![50.0],
+        ..Default::default()
+    };
+    let lines = render_frame(&frame, &sparkline_config(true));
+    assert!(
+        lines[2].starts_with("CTX:50% (100.0k/200.0k) | "),
+        "got {}",
+        lines[2]
+    );
+}
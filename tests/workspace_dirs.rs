@@ -0,0 +1,149 @@
+use std::{fs, path::Path, process::Command};
+
+use cc_pulseline::{config::RenderConfig, run_from_str, types::StdinPayload};
+use serde_json::json;
+use tempfile::TempDir;
+
+fn run_cmd(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-C", dir.to_str().expect("utf-8 path")])
+        .args(args)
+        .status()
+        .expect("git command should run");
+    assert!(
+        status.success(),
+        "git command failed: git {}",
+        args.join(" ")
+    );
+}
+
+/// Init a repo on `branch` with one commit; optionally leave an untracked file behind.
+fn init_repo(dir: &Path, branch: &str, dirty: bool) {
+    fs::create_dir_all(dir).expect("create repo dir");
+    run_cmd(dir, &["init", "-q"]);
+    run_cmd(dir, &["config", "user.email", "test@test.com"]);
+    run_cmd(dir, &["config", "user.name", "Test User"]);
+    run_cmd(dir, &["checkout", "-q", "-b", branch]);
+    fs::write(dir.join("README.md"), "hi\n").expect("write file");
+    run_cmd(dir, &["add", "."]);
+    run_cmd(dir, &["commit", "-q", "-m", "initial"]);
+    if dirty {
+        fs::write(dir.join("scratch.txt"), "wip\n").expect("write untracked");
+    }
+}
+
+fn make_input(project_dir: &str, current_dir: &str, added_dirs: &[&str]) -> String {
+    json!({
+        "session_id": "workspace-dirs-test",
+        "model": {"display_name": "Opus"},
+        "version": "1.0",
+        "workspace": {
+            "current_dir": current_dir,
+            "project_dir": project_dir,
+            "added_dirs": added_dirs,
+        },
+    })
+    .to_string()
+}
+
+#[test]
+fn payload_deserializes_project_and_added_dirs() {
+    let payload: StdinPayload =
+        serde_json::from_str(&make_input("/work/app", "/work/app/src", &["/work/api"])).unwrap();
+    assert_eq!(payload.resolve_project_root().as_deref(), Some("/work/app"));
+    assert_eq!(
+        payload.resolve_project_path().as_deref(),
+        Some("/work/app/src")
+    );
+    assert_eq!(payload.added_dirs(), ["/work/api".to_string()]);
+}
+
+#[test]
+fn cwd_drift_relative_inside_project() {
+    let payload: StdinPayload =
+        serde_json::from_str(&make_input("/work/app", "/work/app/src/api", &[])).unwrap();
+    assert_eq!(payload.resolve_cwd_drift().as_deref(), Some("src/api"));
+
+    let lines = run_from_str(
+        &make_input("/work/app", "/work/app/src/api", &[]),
+        RenderConfig::default(),
+    )
+    .unwrap();
+    assert!(
+        lines[0].contains("P:/work/app ↳src/api"),
+        "L1 should show project root and drift: {}",
+        lines[0]
+    );
+}
+
+#[test]
+fn cwd_drift_absolute_outside_project() {
+    let payload: StdinPayload =
+        serde_json::from_str(&make_input("/work/app", "/elsewhere/tmp", &[])).unwrap();
+    assert_eq!(
+        payload.resolve_cwd_drift().as_deref(),
+        Some("/elsewhere/tmp")
+    );
+}
+
+#[test]
+fn no_drift_when_cwd_is_project_root() {
+    let payload: StdinPayload =
+        serde_json::from_str(&make_input("/work/app", "/work/app/", &[])).unwrap();
+    assert!(payload.resolve_cwd_drift().is_none());
+
+    // Payloads without project_dir never report drift
+    let legacy: StdinPayload =
+        serde_json::from_str(r#"{"workspace":{"current_dir":"/work/app/src"}}"#).unwrap();
+    assert!(legacy.resolve_cwd_drift().is_none());
+    assert_eq!(
+        legacy.resolve_project_root().as_deref(),
+        Some("/work/app/src")
+    );
+}
+
+#[test]
+fn added_dirs_render_compact_git_status() {
+    let tmp = TempDir::new().unwrap();
+    let app = tmp.path().join("app");
+    let api = tmp.path().join("api");
+    let web = tmp.path().join("web");
+    let docs = tmp.path().join("docs");
+    init_repo(&app, "main", false);
+    init_repo(&api, "main", true);
+    init_repo(&web, "feat/x", false);
+    fs::create_dir_all(&docs).unwrap();
+
+    let app = app.to_str().unwrap();
+    let input = make_input(
+        app,
+        app,
+        &[
+            api.to_str().unwrap(),
+            web.to_str().unwrap(),
+            docs.to_str().unwrap(),
+        ],
+    );
+    let lines = run_from_str(&input, RenderConfig::default()).unwrap();
+
+    assert!(
+        lines[0].contains("+api:main* +web:feat/x +docs"),
+        "L1 should list added dirs with branch/dirty state: {}",
+        lines[0]
+    );
+}
+
+#[test]
+fn added_dirs_hidden_when_disabled() {
+    let tmp = TempDir::new().unwrap();
+    let api = tmp.path().join("api");
+    init_repo(&api, "main", false);
+
+    let config = RenderConfig {
+        show_workspace_dirs: false,
+        ..RenderConfig::default()
+    };
+    let input = make_input("/work/app", "/work/app", &[api.to_str().unwrap()]);
+    let lines = run_from_str(&input, config).unwrap();
+    assert!(!lines[0].contains("+api"), "added dirs should be hidden");
+}